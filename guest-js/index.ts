@@ -23,18 +23,292 @@ export interface ConnectionStatus {
   status: string;
 }
 
+// How a server process ended
+export interface ProcessExit {
+  code: number | null;
+  signal: number | null;
+  success: boolean;
+  description: string;
+  stderr_tail: string[];
+}
+
+// Ping statistics for servers with liveness checks
+export interface LatencyStats {
+  last_ms: number | null;
+  min_ms: number | null;
+  max_ms: number | null;
+  avg_ms: number;
+  samples: number;
+  failures: number;
+  consecutive_failures: number;
+  last_ping_at: number | null;
+}
+
+export type ConnectionState =
+  | 'spawning'
+  | 'initializing'
+  | 'ready'
+  | 'degraded' // Not answering liveness pings
+  | 'reconnecting'
+  | 'disconnecting'
+  | 'disconnected'
+  | 'failed';
+
 export interface ConnectionInfo {
   server_id: string;
   command: string;
   args: string[];
-  status: string;
+  status: ConnectionState;
   connected_at?: number; // Unix timestamp
+  last_error?: string; // Why the server last failed or went away, until it is ready again
+  last_exit?: ProcessExit; // Set once the process has exited on its own
+  latency?: LatencyStats;
+  usage?: RateLimitUsage; // Set when the server has rate or concurrency limits
+  resources?: ResourceStats; // Set when resource monitoring is enabled
+}
+
+// Periodic sampling of a server's process tree (Linux)
+export interface ResourceMonitorConfig {
+  enabled?: boolean;
+  intervalMs?: number;
+  maxRssMb?: number;
+  maxCpuPercent?: number;
+  maxThreads?: number;
+  maxOpenFds?: number;
+  breachSamples?: number;
+  action?: 'warn' | 'restart';
+}
+
+export interface ResourceSample {
+  processes: number;
+  cpu_percent: number;
+  cpu_time_ms: number;
+  rss_bytes: number;
+  threads: number;
+  open_fds: number;
+}
+
+export interface ThresholdBreach {
+  resource: 'rss' | 'cpu' | 'threads' | 'open_fds';
+  value: number;
+  limit: number;
+}
+
+export interface ResourceStats {
+  last: ResourceSample | null;
+  peak_rss_bytes: number;
+  samples: number;
+  last_sample_at: number | null;
+  exceeded: ThresholdBreach[];
+  consecutive_breaches: number;
+}
+
+export interface ResourceThresholdEvent {
+  server_id: string;
+  exceeded: ThresholdBreach[];
+  sample: ResourceSample;
+  action: 'warn' | 'restart';
+  timestamp: number;
+}
+
+// Environment variable value: a literal or a reference to a secret resolved in Rust
+export type EnvValue = string | { secret: string };
+
+export type TransportKind = 'stdio' | 'sse' | 'http' | 'replay';
+
+// Automatic restart of servers whose process exits unexpectedly
+export type RestartMode = 'never' | 'on-failure' | 'always';
+
+export interface RestartPolicy {
+  mode?: RestartMode;
+  maxRetries?: number;
+  initialBackoffMs?: number;
+  maxBackoffMs?: number;
+  multiplier?: number;
+  resetAfterMs?: number;
+}
+
+// Periodic MCP ping checks for hung servers
+export interface LivenessConfig {
+  enabled?: boolean;
+  intervalMs?: number;
+  timeoutMs?: number;
+  failureThreshold?: number;
+  restartUnresponsive?: boolean;
+}
+
+// Grace periods of the shutdown sequence (close stdin, SIGTERM, SIGKILL)
+export interface ShutdownConfig {
+  stdinTimeoutMs?: number;
+  terminateTimeoutMs?: number;
+}
+
+// Recording of the JSON-RPC messages exchanged with a server
+export interface TrafficConfig {
+  enabled?: boolean;
+  maxEntries?: number;
+  maxBytes?: number;
+  emitEvents?: boolean;
+  // JSON Lines file to record the session to, for the replay transport,
+  // inside the plugin's sessionDir
+  recordFile?: string;
+}
+
+export type TrafficDirection = 'sent' | 'received';
+
+export interface TrafficEntry {
+  server_id: string;
+  direction: TrafficDirection;
+  timestamp_ms: number;
+  method?: string;
+  request_id?: string | number;
+  latency_ms?: number;
+  bytes: number;
+  message: any;
+}
+
+// Token-bucket rate and concurrency limit
+export interface Limit {
+  ratePerSec?: number;
+  burst?: number;
+  maxConcurrent?: number;
+}
+
+// Limits on tool calls to a server; `tools` keys are tool name patterns
+export interface RateLimits extends Limit {
+  tools?: Record<string, Limit>;
+  mode?: 'reject' | 'queue';
+  queueTimeoutMs?: number;
+}
+
+export interface LimitUsage {
+  in_flight: number;
+  max_concurrent?: number;
+  tokens_available?: number;
+  rate_per_sec?: number;
+  calls: number;
+  limited: number;
+  queued: number;
+}
+
+export interface RateLimitUsage extends LimitUsage {
+  tools?: Record<string, LimitUsage>;
+}
+
+// Linux sandbox of a server process; paths are absolute or start with `~/`
+export type SandboxProfile = 'off' | 'restricted' | 'isolated';
+
+export interface SandboxConfig {
+  profile?: SandboxProfile;
+  memoryMb?: number;
+  cpuSecs?: number;
+  maxOpenFiles?: number;
+  readPaths?: string[];
+  writePaths?: string[];
+  network?: boolean;
 }
 
 export interface ConnectServerRequest {
   server_id: string;
+  command?: string;
+  args?: string[];
+  env?: Record<string, EnvValue>;
+  cwd?: string;
+  transport?: TransportKind;
+  session?: string;
+  restart?: RestartPolicy;
+  liveness?: LivenessConfig;
+  shutdown?: ShutdownConfig;
+  traffic?: TrafficConfig;
+  limits?: RateLimits;
+  sandbox?: SandboxConfig;
+  resources?: ResourceMonitorConfig;
+}
+
+// Server definition as stored in the `mcpServers` configuration file
+export interface ServerConfig {
   command: string;
+  args?: string[];
+  env?: Record<string, EnvValue>;
+  cwd?: string;
+  transport?: TransportKind;
+  url?: string;
+  session?: string;
+  autoStart?: boolean;
+  restart?: RestartPolicy;
+  liveness?: LivenessConfig;
+  shutdown?: ShutdownConfig;
+  traffic?: TrafficConfig;
+  limits?: RateLimits;
+  sandbox?: SandboxConfig;
+  resources?: ResourceMonitorConfig;
+}
+
+// Protocol conformance checks for vetting third-party servers
+export interface ConformanceOptions {
+  timeoutMs?: number;
+  maxPages?: number;
+}
+
+export interface ConformanceRequest {
+  server_id: string;
+  // Connect with this definition for the run and disconnect afterwards
+  config?: ServerConfig;
+  options?: ConformanceOptions;
+}
+
+export type CheckStatus = 'passed' | 'warning' | 'failed' | 'skipped';
+
+export interface CheckResult {
+  name: string;
+  status: CheckStatus;
+  message?: string;
+  duration_ms: number;
+}
+
+export interface ConformanceReport {
+  server_id: string;
+  passed: boolean;
+  checks: CheckResult[];
+  duration_ms: number;
+}
+
+// Import of server definitions from other MCP hosts
+export type ConfigSource = 'claude-desktop' | 'cursor' | 'vs-code';
+
+export interface ImportOptions {
+  env?: Record<string, string>;
+  inputs?: Record<string, string>;
+  workspace_folder?: string;
+}
+
+export interface ImportServersRequest {
+  source?: ConfigSource;
+  path?: string;
+  contents?: string;
+  options?: ImportOptions;
+  save?: boolean;
+  overwrite?: boolean;
+}
+
+// Normalized server; can be passed straight to connectServer
+export interface ImportedServer extends ServerConfig {
+  server_id: string;
   args: string[];
+  warnings: string[];
+}
+
+// Entry left out of an import, with the reason in its warnings
+export interface SkippedServer {
+  server_id: string;
+  warnings: string[];
+}
+
+export interface ImportResult {
+  source: ConfigSource;
+  servers: ImportedServer[];
+  skipped: SkippedServer[];
+  unresolved_inputs: { id: string; description?: string; password: boolean }[];
 }
 
 // Tool-related types for MCP protocol
@@ -72,6 +346,64 @@ export interface ExecuteToolResponse {
   duration_ms: number;
 }
 
+// A tool call waiting for the user's approval
+export interface ApprovalRequest {
+  id: string;
+  server_id: string;
+  tool_name: string;
+  arguments: any;
+  destructive: boolean;
+  timestamp: number;
+}
+
+export interface ApprovalResolved {
+  id: string;
+  server_id: string;
+  tool_name: string;
+  approved: boolean;
+  reason?: string; // "timeout" or "cancelled" when nobody answered
+}
+
+export interface RespondApprovalRequest {
+  id: string;
+  approved: boolean;
+  remember?: boolean;
+}
+
+export type ToolCallOutcome = 'success' | 'tool_error' | 'failed' | 'rejected';
+
+// One audit log entry; `action` decides which of the other fields are set
+export interface AuditEntry {
+  timestamp_ms: number;
+  server_id: string;
+  action: 'spawn' | 'tool_call';
+  // spawn
+  command?: string;
+  args?: string[];
+  env_keys?: string[];
+  allowed?: boolean;
+  reason?: string;
+  // tool_call
+  window?: string;
+  tool_name?: string;
+  arguments_hash?: string;
+  arguments?: any; // only with `audit.arguments` set to "redacted"
+  duration_ms?: number;
+  outcome?: ToolCallOutcome;
+  error_code?: string;
+}
+
+export interface AuditQuery {
+  server_id?: string;
+  action?: 'spawn' | 'tool_call';
+  tool_name?: string;
+  window?: string;
+  outcome?: ToolCallOutcome;
+  since_ms?: number;
+  until_ms?: number;
+  limit?: number;
+}
+
 // Tool execution result (for history/tracking)
 export interface ToolExecutionResult {
   tool_name: string;
@@ -87,10 +419,28 @@ export const EVENT_CONNECTION_CHANGED = 'mcp://connection-changed';
 export const EVENT_SERVER_CONNECTED = 'mcp://server-connected';
 export const EVENT_SERVER_DISCONNECTED = 'mcp://server-disconnected';
 export const EVENT_PROCESS_ERROR = 'mcp://process-error';
+export const EVENT_SERVER_RECONNECTING = 'mcp://server-reconnecting';
+export const EVENT_SERVER_UNRESPONSIVE = 'mcp://server-unresponsive';
+export const EVENT_TRAFFIC = 'mcp://traffic';
+export const EVENT_APPROVAL_REQUIRED = 'mcp://approval-required';
+export const EVENT_APPROVAL_RESOLVED = 'mcp://approval-resolved';
+export const EVENT_RESOURCE_THRESHOLD = 'mcp://resource-threshold';
+
+let eventPrefix = 'mcp';
+
+// Match a custom `eventPrefix` set in the plugin configuration
+export function setEventPrefix(prefix: string): void {
+  eventPrefix = prefix;
+}
+
+function eventName(event: string): string {
+  return event.replace(/^mcp:\/\//, `${eventPrefix}://`);
+}
 
 export interface ConnectionEvent {
   server_id: string;
-  status: string; // "connected", "disconnected", "error"
+  status: ConnectionState;
+  previous_status: ConnectionState | null; // null for a server seen for the first time
   reason?: string;
   timestamp: number;
   command?: string;
@@ -106,6 +456,21 @@ export interface MCPClient {
   disconnectServer(serverId: string): Promise<string>;
   listTools(serverId: string): Promise<any>; // Raw JSON-RPC response for now
   executeTool(request: ExecuteToolRequest): Promise<ExecuteToolResponse>;
+  listResources(serverId: string): Promise<any>;
+  readResource(serverId: string, uri: string): Promise<any>;
+  listServerConfigs(): Promise<Record<string, ServerConfig>>;
+  addServerConfig(serverId: string, config: ServerConfig): Promise<string>;
+  updateServerConfig(serverId: string, config: ServerConfig): Promise<string>;
+  removeServerConfig(serverId: string): Promise<string>;
+  importServers(request: ImportServersRequest): Promise<ImportResult>;
+  getTraffic(serverId: string): Promise<TrafficEntry[]>;
+  exportTraffic(serverId: string): Promise<string>;
+  clearTraffic(serverId: string): Promise<string>;
+  runConformance(request: ConformanceRequest): Promise<ConformanceReport>;
+  listPendingApprovals(): Promise<ApprovalRequest[]>;
+  respondApproval(request: RespondApprovalRequest): Promise<void>;
+  forgetApproval(serverId: string, toolName: string): Promise<void>;
+  queryAuditLog(query?: AuditQuery): Promise<AuditEntry[]>;
 }
 
 // Health check - now actually calls the plugin
@@ -152,41 +517,158 @@ export async function executeTool(request: ExecuteToolRequest): Promise<ExecuteT
   return await invoke('plugin_execute_tool', { request });
 }
 
+// List resources from an MCP server through the plugin
+export async function listResources(serverId: string): Promise<any> {
+  return await invoke('plugin_list_resources', { serverId });
+}
+
+// Read a resource from an MCP server through the plugin
+export async function readResource(serverId: string, uri: string): Promise<any> {
+  return await invoke('plugin_read_resource', { serverId, uri });
+}
+
+// List servers defined in the plugin configuration file
+export async function listServerConfigs(): Promise<Record<string, ServerConfig>> {
+  return await invoke('plugin_list_server_configs');
+}
+
+// Add a server to the plugin configuration file
+export async function addServerConfig(serverId: string, config: ServerConfig): Promise<string> {
+  return await invoke('plugin_add_server_config', { serverId, config });
+}
+
+// Replace a server in the plugin configuration file
+export async function updateServerConfig(serverId: string, config: ServerConfig): Promise<string> {
+  return await invoke('plugin_update_server_config', { serverId, config });
+}
+
+// Remove a server from the plugin configuration file
+export async function removeServerConfig(serverId: string): Promise<string> {
+  return await invoke('plugin_remove_server_config', { serverId });
+}
+
+// Import servers from Claude Desktop, Cursor or VS Code configuration
+export async function importServers(request: ImportServersRequest): Promise<ImportResult> {
+  return await invoke('plugin_import_servers', { request });
+}
+
+// Recorded JSON-RPC messages of a server with traffic recording enabled
+export async function getTraffic(serverId: string): Promise<TrafficEntry[]> {
+  return await invoke('plugin_get_traffic', { serverId });
+}
+
+// Recorded JSON-RPC messages as JSON Lines
+export async function exportTraffic(serverId: string): Promise<string> {
+  return await invoke('plugin_export_traffic', { serverId });
+}
+
+export async function clearTraffic(serverId: string): Promise<string> {
+  return await invoke('plugin_clear_traffic', { serverId });
+}
+
+// Check a server against the MCP protocol; failed checks don't reject
+export async function runConformance(request: ConformanceRequest): Promise<ConformanceReport> {
+  return await invoke('plugin_run_conformance', { request });
+}
+
+// Tool calls waiting for the user's approval
+export async function listPendingApprovals(): Promise<ApprovalRequest[]> {
+  return await invoke('plugin_list_pending_approvals');
+}
+
+// Let a pending tool call go ahead or reject it
+export async function respondApproval(request: RespondApprovalRequest): Promise<void> {
+  return await invoke('plugin_respond_approval', { request });
+}
+
+// Forget a remembered decision so the next call of the tool asks again
+export async function forgetApproval(serverId: string, toolName: string): Promise<void> {
+  return await invoke('plugin_forget_approval', { request: { server_id: serverId, tool_name: toolName } });
+}
+
+// Audit entries of spawns and tool calls, oldest first
+export async function queryAuditLog(query: AuditQuery = {}): Promise<AuditEntry[]> {
+  return await invoke('plugin_query_audit_log', { query });
+}
+
 // Event listener helpers
 export async function onConnectionChanged(callback: (event: ConnectionEvent) => void): Promise<UnlistenFn> {
-  return await listen<ConnectionEvent>(EVENT_CONNECTION_CHANGED, (event) => {
+  return await listen<ConnectionEvent>(eventName(EVENT_CONNECTION_CHANGED), (event) => {
     console.log('MCP connection changed:', event.payload);
     callback(event.payload);
   });
 }
 
 export async function onServerConnected(callback: (event: ConnectionEvent) => void): Promise<UnlistenFn> {
-  return await listen<ConnectionEvent>(EVENT_SERVER_CONNECTED, (event) => {
+  return await listen<ConnectionEvent>(eventName(EVENT_SERVER_CONNECTED), (event) => {
     console.log('MCP server connected:', event.payload);
     callback(event.payload);
   });
 }
 
 export async function onServerDisconnected(callback: (event: ConnectionEvent) => void): Promise<UnlistenFn> {
-  return await listen<ConnectionEvent>(EVENT_SERVER_DISCONNECTED, (event) => {
+  return await listen<ConnectionEvent>(eventName(EVENT_SERVER_DISCONNECTED), (event) => {
     console.log('MCP server disconnected:', event.payload);
     callback(event.payload);
   });
 }
 
+export async function onServerReconnecting(callback: (event: ConnectionEvent) => void): Promise<UnlistenFn> {
+  return await listen<ConnectionEvent>(eventName(EVENT_SERVER_RECONNECTING), (event) => {
+    console.log('MCP server reconnecting:', event.payload);
+    callback(event.payload);
+  });
+}
+
+export async function onServerUnresponsive(callback: (event: ConnectionEvent) => void): Promise<UnlistenFn> {
+  return await listen<ConnectionEvent>(eventName(EVENT_SERVER_UNRESPONSIVE), (event) => {
+    console.log('MCP server unresponsive:', event.payload);
+    callback(event.payload);
+  });
+}
+
 export async function onProcessError(callback: (event: ConnectionEvent) => void): Promise<UnlistenFn> {
-  return await listen<ConnectionEvent>(EVENT_PROCESS_ERROR, (event) => {
+  return await listen<ConnectionEvent>(eventName(EVENT_PROCESS_ERROR), (event) => {
     console.log('MCP process error:', event.payload);
     callback(event.payload);
   });
 }
 
+// Every recorded message of servers with `traffic.emitEvents` set
+export async function onTraffic(callback: (entry: TrafficEntry) => void): Promise<UnlistenFn> {
+  return await listen<TrafficEntry>(eventName(EVENT_TRAFFIC), (event) => {
+    callback(event.payload);
+  });
+}
+
+// Tool calls waiting for the user's approval
+export async function onApprovalRequired(callback: (request: ApprovalRequest) => void): Promise<UnlistenFn> {
+  return await listen<ApprovalRequest>(eventName(EVENT_APPROVAL_REQUIRED), (event) => {
+    callback(event.payload);
+  });
+}
+
+export async function onApprovalResolved(callback: (resolved: ApprovalResolved) => void): Promise<UnlistenFn> {
+  return await listen<ApprovalResolved>(eventName(EVENT_APPROVAL_RESOLVED), (event) => {
+    callback(event.payload);
+  });
+}
+
+// Servers that stay over their resource thresholds
+export async function onResourceThreshold(callback: (event: ResourceThresholdEvent) => void): Promise<UnlistenFn> {
+  return await listen<ResourceThresholdEvent>(eventName(EVENT_RESOURCE_THRESHOLD), (event) => {
+    callback(event.payload);
+  });
+}
+
 // Convenience function to listen to all MCP events
 export async function onAllMCPEvents(callback: (event: ConnectionEvent) => void): Promise<UnlistenFn[]> {
   const unlisteners = await Promise.all([
     onConnectionChanged(callback),
     onServerConnected(callback),
     onServerDisconnected(callback),
+    onServerReconnecting(callback),
+    onServerUnresponsive(callback),
     onProcessError(callback),
   ]);
   return unlisteners;
@@ -200,5 +682,20 @@ export const mcp: MCPClient = {
   connectServer,
   disconnectServer,
   listTools,
-  executeTool
+  executeTool,
+  listResources,
+  readResource,
+  listServerConfigs,
+  addServerConfig,
+  updateServerConfig,
+  removeServerConfig,
+  importServers,
+  getTraffic,
+  exportTraffic,
+  clearTraffic,
+  runConformance,
+  listPendingApprovals,
+  respondApproval,
+  forgetApproval,
+  queryAuditLog
 };
\ No newline at end of file
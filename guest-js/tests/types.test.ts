@@ -18,6 +18,8 @@ import {
   EVENT_SERVER_CONNECTED,
   EVENT_SERVER_DISCONNECTED,
   EVENT_PROCESS_ERROR,
+  EVENT_SERVER_RECONNECTING,
+  EVENT_SERVER_UNRESPONSIVE,
 } from '../index';
 
 describe('TypeScript API Types and Constants', () => {
@@ -41,14 +43,14 @@ describe('TypeScript API Types and Constants', () => {
         server_id: 'test-server',
         command: 'node',
         args: ['server.js'],
-        status: 'connected',
+        status: 'ready',
         connected_at: 1234567890,
       };
 
       expect(info.server_id).toBe('test-server');
       expect(info.command).toBe('node');
       expect(info.args).toEqual(['server.js']);
-      expect(info.status).toBe('connected');
+      expect(info.status).toBe('ready');
       expect(info.connected_at).toBe(1234567890);
     });
 
@@ -94,7 +96,8 @@ describe('TypeScript API Types and Constants', () => {
     it('should validate ConnectionEvent structure', () => {
       const event: ConnectionEvent = {
         server_id: 'test-server',
-        status: 'connected',
+        status: 'ready',
+        previous_status: 'initializing',
         timestamp: 1234567890,
         reason: 'User requested connection',
         command: 'node',
@@ -102,7 +105,8 @@ describe('TypeScript API Types and Constants', () => {
       };
 
       expect(event.server_id).toBe('test-server');
-      expect(event.status).toBe('connected');
+      expect(event.status).toBe('ready');
+      expect(event.previous_status).toBe('initializing');
       expect(event.timestamp).toBe(1234567890);
       expect(event.reason).toBe('User requested connection');
       expect(event.command).toBe('node');
@@ -184,6 +188,8 @@ describe('TypeScript API Types and Constants', () => {
       expect(EVENT_SERVER_CONNECTED).toBe('mcp://server-connected');
       expect(EVENT_SERVER_DISCONNECTED).toBe('mcp://server-disconnected');
       expect(EVENT_PROCESS_ERROR).toBe('mcp://process-error');
+      expect(EVENT_SERVER_RECONNECTING).toBe('mcp://server-reconnecting');
+      expect(EVENT_SERVER_UNRESPONSIVE).toBe('mcp://server-unresponsive');
     });
   });
 
@@ -230,7 +236,7 @@ describe('TypeScript API Types and Constants', () => {
         server_id: 'test',
         command: 'node',
         args: [],
-        status: 'connected',
+        status: 'ready',
         // connected_at is optional
       };
 
@@ -240,7 +246,8 @@ describe('TypeScript API Types and Constants', () => {
     it('should handle optional fields in ConnectionEvent', () => {
       const minimalEvent: ConnectionEvent = {
         server_id: 'test',
-        status: 'connected',
+        status: 'spawning',
+        previous_status: null,
         timestamp: 1234567890,
         // reason, command, args are optional
       };
@@ -0,0 +1,336 @@
+/// Human-in-the-loop approval of tool calls
+///
+/// Depending on the approval policy, `execute_tool` holds a call until the
+/// user approves it: the registry emits `mcp://approval-required` with the
+/// tool name and arguments and waits for `plugin_respond_approval`. A
+/// decision can be remembered, in which case later calls of the same tool
+/// on the same server don't ask again, across app restarts.
+use crate::config::write_atomically;
+use crate::error::{ErrorCategory, MCPClientError};
+use crate::policy::glob_match;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::oneshot;
+
+/// File in the app data dir remembered decisions are stored in
+pub const APPROVALS_FILE_NAME: &str = "mcp-approvals.json";
+
+/// Which tool calls need the user's approval
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ApprovalMode {
+    /// Never ask
+    #[default]
+    Never,
+    /// Ask for every tool call
+    Always,
+    /// Ask for tools that may be destructive, unless they are in `allowedTools`.
+    /// Following the MCP spec, tools are destructive unless annotated with
+    /// `readOnlyHint: true` or `destructiveHint: false`.
+    Destructive,
+    /// Ask for every tool outside `allowedTools`
+    Allowlist,
+}
+
+fn default_timeout_ms() -> u64 {
+    120_000
+}
+
+/// Approval policy for tool calls
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalPolicy {
+    #[serde(default)]
+    pub mode: ApprovalMode,
+    /// Tools that run without asking in the `destructive` and `allowlist`
+    /// modes. Entries are tool names or globs; `server/tool` entries only
+    /// match on that server.
+    #[serde(default)]
+    pub allowed_tools: Vec<String>,
+    /// How long a call waits for a decision before it is rejected
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+impl Default for ApprovalPolicy {
+    fn default() -> Self {
+        Self {
+            mode: ApprovalMode::Never,
+            allowed_tools: Vec::new(),
+            timeout_ms: default_timeout_ms(),
+        }
+    }
+}
+
+impl ApprovalPolicy {
+    pub fn always() -> Self {
+        Self {
+            mode: ApprovalMode::Always,
+            ..Self::default()
+        }
+    }
+
+    pub fn destructive() -> Self {
+        Self {
+            mode: ApprovalMode::Destructive,
+            ..Self::default()
+        }
+    }
+
+    /// Ask for every tool except these
+    pub fn allowlist<I, S>(tools: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            mode: ApprovalMode::Allowlist,
+            allowed_tools: tools.into_iter().map(Into::into).collect(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = timeout_ms;
+        self
+    }
+
+    fn is_allowed(&self, server_id: &str, tool_name: &str) -> bool {
+        let qualified = format!("{}/{}", server_id, tool_name);
+        self.allowed_tools.iter().any(|pattern| {
+            if pattern.contains('/') {
+                glob_match(pattern, &qualified)
+            } else {
+                glob_match(pattern, tool_name)
+            }
+        })
+    }
+
+    /// Whether the policy needs the tool's annotations to decide
+    pub fn needs_annotations(&self) -> bool {
+        self.mode == ApprovalMode::Destructive
+    }
+
+    /// Whether a call needs approval. `annotations` are the tool's
+    /// annotations from `tools/list`, if known.
+    pub fn requires_approval(&self, server_id: &str, tool_name: &str, annotations: Option<&serde_json::Value>) -> bool {
+        match self.mode {
+            ApprovalMode::Never => false,
+            ApprovalMode::Always => true,
+            ApprovalMode::Allowlist => !self.is_allowed(server_id, tool_name),
+            ApprovalMode::Destructive => !self.is_allowed(server_id, tool_name) && is_destructive(annotations),
+        }
+    }
+}
+
+/// Whether tool annotations describe a possibly destructive tool
+pub fn is_destructive(annotations: Option<&serde_json::Value>) -> bool {
+    let hint = |name: &str| annotations.and_then(|a| a.get(name)).and_then(|v| v.as_bool());
+    hint("readOnlyHint") != Some(true) && hint("destructiveHint") != Some(false)
+}
+
+/// Payload of `mcp://approval-required`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApprovalRequest {
+    pub id: String,
+    pub server_id: String,
+    pub tool_name: String,
+    pub arguments: serde_json::Value,
+    /// Whether the tool's annotations mark it as possibly destructive
+    pub destructive: bool,
+    pub timestamp: u64,
+}
+
+/// Payload of `mcp://approval-resolved`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApprovalResolved {
+    pub id: String,
+    pub server_id: String,
+    pub tool_name: String,
+    pub approved: bool,
+    /// Why the request ended without a decision, such as a timeout
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// The user's answer to an approval request
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ApprovalDecision {
+    pub approved: bool,
+    /// Apply this decision to later calls of the same tool on the same server
+    #[serde(default)]
+    pub remember: bool,
+}
+
+struct Pending {
+    request: ApprovalRequest,
+    sender: oneshot::Sender<bool>,
+}
+
+/// Pending approval requests and remembered decisions, shared by all
+/// clones of the registry
+#[derive(Clone, Default)]
+pub struct ApprovalManager {
+    pending: Arc<Mutex<HashMap<String, Pending>>>,
+    /// Decisions by `server/tool`
+    remembered: Arc<Mutex<BTreeMap<String, bool>>>,
+    path: Arc<Mutex<Option<PathBuf>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+fn key(server_id: &str, tool_name: &str) -> String {
+    format!("{}/{}", server_id, tool_name)
+}
+
+fn lock_error(e: impl std::fmt::Display) -> MCPClientError {
+    MCPClientError::system_error(&format!("Failed to lock approvals: {}", e))
+}
+
+impl ApprovalManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep remembered decisions in `path`, loading the ones already there
+    pub fn set_store(&self, path: PathBuf) -> Result<(), MCPClientError> {
+        let decisions: BTreeMap<String, bool> = if path.exists() {
+            let contents = std::fs::read_to_string(&path).map_err(|e| {
+                MCPClientError::system_error(&format!("Failed to read {}: {}", path.display(), e))
+            })?;
+            serde_json::from_str(&contents).map_err(|e| {
+                MCPClientError::configuration_error("approvals", &format!("Invalid {}: {}", path.display(), e))
+            })?
+        } else {
+            BTreeMap::new()
+        };
+        *self.remembered.lock().map_err(lock_error)? = decisions;
+        *self.path.lock().map_err(lock_error)? = Some(path);
+        Ok(())
+    }
+
+    fn save(&self, decisions: &BTreeMap<String, bool>) -> Result<(), MCPClientError> {
+        let Some(path) = self.path.lock().map_err(lock_error)?.clone() else {
+            return Ok(());
+        };
+        write_decisions(&path, decisions)
+    }
+
+    /// Remembered decision for a tool, if any
+    pub fn remembered(&self, server_id: &str, tool_name: &str) -> Option<bool> {
+        self.remembered.lock().ok()?.get(&key(server_id, tool_name)).copied()
+    }
+
+    /// All remembered decisions by `server/tool`
+    pub fn remembered_decisions(&self) -> BTreeMap<String, bool> {
+        self.remembered.lock().map(|d| d.clone()).unwrap_or_default()
+    }
+
+    /// Forget the remembered decision for a tool
+    pub fn forget(&self, server_id: &str, tool_name: &str) -> Result<(), MCPClientError> {
+        let mut decisions = self.remembered.lock().map_err(lock_error)?;
+        if decisions.remove(&key(server_id, tool_name)).is_some() {
+            self.save(&decisions)?;
+        }
+        Ok(())
+    }
+
+    /// Forget every remembered decision
+    pub fn forget_all(&self) -> Result<(), MCPClientError> {
+        let mut decisions = self.remembered.lock().map_err(lock_error)?;
+        decisions.clear();
+        self.save(&decisions)
+    }
+
+    /// Register a request; the receiver gets the user's decision
+    pub fn request(
+        &self,
+        server_id: &str,
+        tool_name: &str,
+        arguments: serde_json::Value,
+        destructive: bool,
+    ) -> Result<(ApprovalRequest, oneshot::Receiver<bool>), MCPClientError> {
+        let request = ApprovalRequest {
+            id: format!("approval-{}", self.next_id.fetch_add(1, Ordering::SeqCst) + 1),
+            server_id: server_id.to_string(),
+            tool_name: tool_name.to_string(),
+            arguments,
+            destructive,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+        };
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock().map_err(lock_error)?.insert(
+            request.id.clone(),
+            Pending {
+                request: request.clone(),
+                sender,
+            },
+        );
+        Ok((request, receiver))
+    }
+
+    /// Requests waiting for a decision, oldest first
+    pub fn pending(&self) -> Vec<ApprovalRequest> {
+        let mut requests: Vec<ApprovalRequest> = self
+            .pending
+            .lock()
+            .map(|pending| pending.values().map(|p| p.request.clone()).collect())
+            .unwrap_or_default();
+        requests.sort_by_key(|r| r.id.trim_start_matches("approval-").parse::<u64>().unwrap_or_default());
+        requests
+    }
+
+    /// Resolve a pending request with the user's decision
+    pub fn respond(&self, id: &str, decision: ApprovalDecision) -> Result<ApprovalRequest, MCPClientError> {
+        let pending = self
+            .pending
+            .lock()
+            .map_err(lock_error)?
+            .remove(id)
+            .ok_or_else(|| approval_not_found(id))?;
+
+        if decision.remember {
+            let mut decisions = self.remembered.lock().map_err(lock_error)?;
+            decisions.insert(key(&pending.request.server_id, &pending.request.tool_name), decision.approved);
+            if let Err(e) = self.save(&decisions) {
+                warn_log!(error = %e, "Failed to store approval decision");
+            }
+        }
+        // The call may have timed out in the meantime
+        let _ = pending.sender.send(decision.approved);
+        Ok(pending.request)
+    }
+
+    /// Drop a pending request without a decision
+    pub fn cancel(&self, id: &str) -> Option<ApprovalRequest> {
+        self.pending.lock().ok()?.remove(id).map(|p| p.request)
+    }
+}
+
+fn write_decisions(path: &Path, decisions: &BTreeMap<String, bool>) -> Result<(), MCPClientError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| {
+            MCPClientError::system_error(&format!("Failed to create directory {}: {}", parent.display(), e))
+        })?;
+    }
+    let contents = serde_json::to_string_pretty(decisions)
+        .map_err(|e| MCPClientError::system_error(&e.to_string()))?;
+    write_atomically(path, contents.as_bytes()).map_err(|e| {
+        MCPClientError::system_error(&format!("Failed to write {}: {}", path.display(), e))
+    })
+}
+
+fn approval_not_found(id: &str) -> MCPClientError {
+    MCPClientError::new(
+        ErrorCategory::Command,
+        "APPROVAL_NOT_FOUND",
+        &format!("No pending approval request {}", id),
+    )
+    .with_details("The request was already answered or timed out")
+}
@@ -0,0 +1,537 @@
+/// Audit trail of security-relevant decisions and tool calls
+///
+/// The registry records every server spawn it allows or blocks, so blocked
+/// attempts from a compromised frontend leave a trace, and every tool call
+/// with its outcome. The log is kept in memory, capped at `MAX_ENTRIES`, and
+/// shared by all clones of the registry. The plugin also appends it to a
+/// rotating JSON Lines file in the app data dir (see `AuditConfig`).
+use crate::error::{ErrorCategory, MCPClientError};
+use crate::policy::SpawnRequest;
+use crate::secrets::{SecretRedactor, REDACTED};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Oldest entries are dropped past this many
+pub const MAX_ENTRIES: usize = 1_000;
+
+/// Audit file in the app data dir unless configured otherwise
+pub const DEFAULT_AUDIT_FILE_NAME: &str = "mcp-audit.jsonl";
+
+/// Argument keys whose values are never written to the audit log
+const SENSITIVE_KEYS: &[&str] = &["password", "passwd", "secret", "token", "apikey", "api_key", "authorization", "credential", "private_key"];
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_max_file_bytes() -> u64 {
+    5 * 1024 * 1024
+}
+
+fn default_max_files() -> usize {
+    5
+}
+
+/// What the audit log keeps of tool call arguments
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ArgumentsMode {
+    /// A SHA-256 hash only
+    #[default]
+    Hash,
+    /// The hash and the arguments, with secrets and credential-like keys redacted
+    Redacted,
+}
+
+/// Settings of the audit file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditConfig {
+    /// Write the audit log to a file
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Audit file, relative to the app data dir
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+    /// The file is rotated once it grows past this size
+    #[serde(default = "default_max_file_bytes")]
+    pub max_file_bytes: u64,
+    /// Rotated files kept next to the current one
+    #[serde(default = "default_max_files")]
+    pub max_files: usize,
+    #[serde(default)]
+    pub arguments: ArgumentsMode,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            file: None,
+            max_file_bytes: default_max_file_bytes(),
+            max_files: default_max_files(),
+            arguments: ArgumentsMode::default(),
+        }
+    }
+}
+
+/// How a tool call ended
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolCallOutcome {
+    Success,
+    /// The tool ran but reported an error (`isError` in its result)
+    ToolError,
+    /// The call failed, for example because the server exited or timed out
+    Failed,
+    /// The call was blocked by a scope or not approved by the user
+    Rejected,
+}
+
+impl ToolCallOutcome {
+    fn from_error(error: &MCPClientError) -> Self {
+        if error.category == ErrorCategory::Permission || error.code.starts_with("APPROVAL_") {
+            ToolCallOutcome::Rejected
+        } else {
+            ToolCallOutcome::Failed
+        }
+    }
+}
+
+/// What was decided
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// A server process was about to be spawned
+    Spawn {
+        command: String,
+        args: Vec<String>,
+        /// Names of the environment variables set by the server definition
+        env_keys: Vec<String>,
+        allowed: bool,
+        /// Error code of a blocked spawn
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error_code: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+    /// A tool was called
+    ToolCall {
+        /// Label of the window that made the call, if it came from the frontend
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        window: Option<String>,
+        tool_name: String,
+        /// `sha256:` hash of the arguments as JSON with sorted keys
+        arguments_hash: String,
+        /// Redacted arguments, with `ArgumentsMode::Redacted`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        arguments: Option<serde_json::Value>,
+        /// Time from the call to its outcome, including any wait for approval
+        duration_ms: u64,
+        outcome: ToolCallOutcome,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error_code: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditEntry {
+    pub timestamp_ms: u64,
+    pub server_id: String,
+    #[serde(flatten)]
+    pub event: AuditEvent,
+}
+
+impl AuditEntry {
+    /// Whether the entry records a blocked action
+    pub fn is_blocked(&self) -> bool {
+        match self.event {
+            AuditEvent::Spawn { allowed, .. } => !allowed,
+            AuditEvent::ToolCall { outcome, .. } => outcome == ToolCallOutcome::Rejected,
+        }
+    }
+
+    fn action(&self) -> &'static str {
+        match self.event {
+            AuditEvent::Spawn { .. } => "spawn",
+            AuditEvent::ToolCall { .. } => "tool_call",
+        }
+    }
+}
+
+/// Filter for `AuditLog::query`; unset fields match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct AuditQuery {
+    #[serde(default)]
+    pub server_id: Option<String>,
+    /// `spawn` or `tool_call`
+    #[serde(default)]
+    pub action: Option<String>,
+    #[serde(default)]
+    pub tool_name: Option<String>,
+    #[serde(default)]
+    pub window: Option<String>,
+    #[serde(default)]
+    pub outcome: Option<ToolCallOutcome>,
+    /// Only entries at or after this time
+    #[serde(default)]
+    pub since_ms: Option<u64>,
+    /// Only entries before this time
+    #[serde(default)]
+    pub until_ms: Option<u64>,
+    /// Only the most recent entries
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        let (tool_name, window, outcome) = match entry.event {
+            AuditEvent::ToolCall { ref tool_name, ref window, outcome, .. } => {
+                (Some(tool_name.as_str()), window.as_deref(), Some(outcome))
+            }
+            AuditEvent::Spawn { .. } => (None, None, None),
+        };
+        self.server_id.as_ref().is_none_or(|id| *id == entry.server_id)
+            && self.action.as_deref().is_none_or(|action| action == entry.action())
+            && self.tool_name.as_deref().is_none_or(|name| tool_name == Some(name))
+            && self.window.as_deref().is_none_or(|label| window == Some(label))
+            && self.outcome.is_none_or(|wanted| outcome == Some(wanted))
+            && self.since_ms.is_none_or(|since| entry.timestamp_ms >= since)
+            && self.until_ms.is_none_or(|until| entry.timestamp_ms < until)
+    }
+}
+
+/// A tool call about to be made, with its arguments already hashed
+#[derive(Debug, Clone)]
+pub struct ToolCallRecord {
+    pub window: Option<String>,
+    pub tool_name: String,
+    pub arguments_hash: String,
+    pub arguments: Option<serde_json::Value>,
+}
+
+impl ToolCallRecord {
+    pub fn new(
+        window: Option<&str>,
+        tool_name: &str,
+        arguments: &serde_json::Value,
+        mode: ArgumentsMode,
+        redactor: &SecretRedactor,
+    ) -> Self {
+        Self {
+            window: window.map(str::to_string),
+            tool_name: tool_name.to_string(),
+            arguments_hash: hash_arguments(arguments),
+            arguments: (mode == ArgumentsMode::Redacted).then(|| redact_arguments(&redactor.redact_value(arguments))),
+        }
+    }
+}
+
+/// `sha256:` hash of tool arguments. serde_json keeps object keys sorted,
+/// so equal arguments hash the same regardless of key order.
+pub fn hash_arguments(arguments: &serde_json::Value) -> String {
+    let digest = Sha256::digest(arguments.to_string().as_bytes());
+    let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("sha256:{}", hex)
+}
+
+fn is_sensitive(key: &str) -> bool {
+    let normalized = key.to_lowercase().replace('-', "_");
+    SENSITIVE_KEYS.iter().any(|sensitive| normalized.contains(sensitive))
+}
+
+/// Replace the values of credential-like keys
+pub fn redact_arguments(arguments: &serde_json::Value) -> serde_json::Value {
+    match arguments {
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    if is_sensitive(key) {
+                        (key.clone(), serde_json::Value::String(REDACTED.to_string()))
+                    } else {
+                        (key.clone(), redact_arguments(value))
+                    }
+                })
+                .collect(),
+        ),
+        serde_json::Value::Array(items) => serde_json::Value::Array(items.iter().map(redact_arguments).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Replace the values of credential-like command line flags, given as
+/// `--api-key value` or `--api-key=value`, and of `NAME=value` arguments
+pub fn redact_args(args: &[String]) -> Vec<String> {
+    let mut redacted = Vec::with_capacity(args.len());
+    let mut hide_next = false;
+    for arg in args {
+        if std::mem::take(&mut hide_next) {
+            redacted.push(REDACTED.to_string());
+            continue;
+        }
+        match arg.split_once('=') {
+            Some((name, _)) if is_sensitive(name) => redacted.push(format!("{}={}", name, REDACTED)),
+            None if arg.starts_with('-') && is_sensitive(arg) => {
+                hide_next = true;
+                redacted.push(arg.clone());
+            }
+            _ => redacted.push(arg.clone()),
+        }
+    }
+    redacted
+}
+
+/// Append-only JSON Lines file, rotated to `<file>.1`, `<file>.2`, ...
+#[derive(Debug)]
+pub struct AuditFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    file: Option<File>,
+    size: u64,
+}
+
+impl AuditFile {
+    pub fn new(path: PathBuf, max_bytes: u64, max_files: usize) -> Self {
+        Self {
+            path,
+            max_bytes,
+            max_files,
+            file: None,
+            size: 0,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Handle on the same files for reading, so reads don't hold up appends
+    fn reader(&self) -> Self {
+        Self::new(self.path.clone(), self.max_bytes, self.max_files)
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    fn open(&mut self) -> Result<&mut File, MCPClientError> {
+        if self.file.is_none() {
+            if let Some(parent) = self.path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent).map_err(|e| {
+                    MCPClientError::system_error(&format!("Failed to create audit directory {}: {}", parent.display(), e))
+                })?;
+            }
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .map_err(|e| MCPClientError::system_error(&format!("Failed to open audit file {}: {}", self.path.display(), e)))?;
+            self.size = file.metadata().map(|m| m.len()).unwrap_or_default();
+            self.file = Some(file);
+        }
+        Ok(self.file.as_mut().expect("audit file was just opened"))
+    }
+
+    fn rotate(&mut self) -> Result<(), MCPClientError> {
+        self.file = None;
+        let rename = |from: &Path, to: &Path| match std::fs::rename(from, to) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(MCPClientError::system_error(&format!(
+                "Failed to rotate audit file {}: {}",
+                from.display(),
+                e
+            ))),
+            _ => Ok(()),
+        };
+        if self.max_files == 0 {
+            return std::fs::remove_file(&self.path)
+                .map_err(|e| MCPClientError::system_error(&format!("Failed to rotate audit file: {}", e)));
+        }
+        let _ = std::fs::remove_file(self.rotated_path(self.max_files));
+        for index in (1..self.max_files).rev() {
+            rename(&self.rotated_path(index), &self.rotated_path(index + 1))?;
+        }
+        rename(&self.path, &self.rotated_path(1))
+    }
+
+    /// Append one entry, rotating first if it would push the file past its size
+    pub fn append(&mut self, entry: &AuditEntry) -> Result<(), MCPClientError> {
+        let mut line = serde_json::to_string(entry).map_err(|e| MCPClientError::system_error(&e.to_string()))?;
+        line.push('\n');
+        self.open()?;
+        if self.size > 0 && self.size + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let file = self.open()?;
+        file.write_all(line.as_bytes())
+            .and_then(|_| file.flush())
+            .map_err(|e| MCPClientError::system_error(&format!("Failed to write audit file: {}", e)))?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Entries of the rotated files and the current one, oldest first.
+    /// Lines that don't parse are skipped.
+    pub fn read(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>, MCPClientError> {
+        let mut paths: Vec<PathBuf> = (1..=self.max_files).rev().map(|index| self.rotated_path(index)).collect();
+        paths.push(self.path.clone());
+
+        let mut entries = Vec::new();
+        for path in paths {
+            let file = match File::open(&path) {
+                Ok(file) => file,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => {
+                    return Err(MCPClientError::system_error(&format!(
+                        "Failed to read audit file {}: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            };
+            for line in BufReader::new(file).lines() {
+                let line = line.map_err(|e| MCPClientError::system_error(&format!("Failed to read audit file: {}", e)))?;
+                match serde_json::from_str::<AuditEntry>(&line) {
+                    Ok(entry) if query.matches(&entry) => entries.push(entry),
+                    Ok(_) => {}
+                    Err(e) => debug_log!(path = %path.display(), error = %e, "Skipping unreadable audit entry"),
+                }
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// Audit log, in memory and optionally in a file
+#[derive(Debug, Clone, Default)]
+pub struct AuditLog {
+    entries: Arc<Mutex<VecDeque<AuditEntry>>>,
+    file: Arc<Mutex<Option<AuditFile>>>,
+}
+
+impl AuditLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also append every entry to `file`
+    pub fn set_file(&self, file: AuditFile) {
+        if let Ok(mut current) = self.file.lock() {
+            *current = Some(file);
+        }
+    }
+
+    pub fn record(&self, server_id: &str, event: AuditEvent) {
+        let entry = AuditEntry {
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+            server_id: server_id.to_string(),
+            event,
+        };
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(ref mut file) = *file {
+                if let Err(e) = file.append(&entry) {
+                    warn_log!(server_id, error = %e, "Failed to write audit entry");
+                }
+            }
+        }
+        if let Ok(mut entries) = self.entries.lock() {
+            if entries.len() >= MAX_ENTRIES {
+                entries.pop_front();
+            }
+            entries.push_back(entry);
+        }
+    }
+
+    /// Record a spawn decision; `result` is the outcome of the policy checks.
+    /// Credentials in the arguments are redacted (see `redact_args`).
+    pub fn record_spawn(&self, server_id: &str, request: &SpawnRequest, result: Result<(), &MCPClientError>) {
+        let command = request.command;
+        let event = AuditEvent::Spawn {
+            command: command.to_string(),
+            args: redact_args(request.args),
+            env_keys: request.env_keys.iter().map(|key| key.to_string()).collect(),
+            allowed: result.is_ok(),
+            error_code: result.err().map(|e| e.code.clone()),
+            reason: result.err().map(|e| e.message.clone()),
+        };
+        match result {
+            Ok(()) => debug_log!(server_id, command, "Spawn allowed"),
+            Err(e) => warn_log!(server_id, command, code = %e.code, "Spawn blocked"),
+        }
+        self.record(server_id, event);
+    }
+
+    /// Record how a tool call ended; `result` is the `tools/call` result
+    pub fn record_tool_call(
+        &self,
+        server_id: &str,
+        call: ToolCallRecord,
+        duration: Duration,
+        result: Result<&serde_json::Value, &MCPClientError>,
+    ) {
+        let outcome = match result {
+            Ok(result) if result.get("isError").and_then(|e| e.as_bool()) == Some(true) => ToolCallOutcome::ToolError,
+            Ok(_) => ToolCallOutcome::Success,
+            Err(e) => ToolCallOutcome::from_error(e),
+        };
+        self.record(
+            server_id,
+            AuditEvent::ToolCall {
+                window: call.window,
+                tool_name: call.tool_name,
+                arguments_hash: call.arguments_hash,
+                arguments: call.arguments,
+                duration_ms: duration.as_millis() as u64,
+                outcome,
+                error_code: result.err().map(|e| e.code.clone()),
+            },
+        );
+    }
+
+    /// Recorded entries, oldest first
+    pub fn entries(&self) -> Vec<AuditEntry> {
+        self.entries
+            .lock()
+            .map(|entries| entries.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Entries matching `query`, oldest first. Reads the audit file when
+    /// there is one, so entries from earlier app runs are included. Entries
+    /// recorded meanwhile may be missed.
+    pub fn query(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>, MCPClientError> {
+        // Read without the lock, which every recorded entry waits for
+        let reader = self.file.lock().ok().and_then(|file| file.as_ref().map(AuditFile::reader));
+        let from_file = reader.map(|reader| reader.read(query)).transpose()?;
+        let mut entries = from_file.unwrap_or_else(|| {
+            self.entries()
+                .into_iter()
+                .filter(|entry| query.matches(entry))
+                .collect()
+        });
+        if let Some(limit) = query.limit {
+            let skip = entries.len().saturating_sub(limit);
+            entries.drain(..skip);
+        }
+        Ok(entries)
+    }
+
+    /// Clear the in-memory entries. The audit file is append-only and kept.
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+}
@@ -0,0 +1,302 @@
+/// Rust-side access to the plugin for backend code
+///
+/// ```ignore
+/// use tauri_plugin_mcp_client::McpExt;
+///
+/// let mcp = app.mcp();
+/// let tools = mcp.list_tools("github").await?;
+/// let result = mcp.call_tool("github", "search_repositories", json!({ "query": "tauri" })).await?;
+/// ```
+use crate::config::ServerConfig;
+use crate::conformance::{self, ConformanceOptions, ConformanceReport};
+use crate::error::MCPClientError;
+use crate::registry::{ConnectionInfo, ConnectionRegistry};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::{Manager, Runtime};
+
+/// A tool exposed by a server
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Tool {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub input_schema: serde_json::Value,
+}
+
+/// Contents of a resource, either text or base64 encoded binary data
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceContents {
+    pub uri: String,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub blob: Option<String>,
+}
+
+/// A content block in tool results and prompt messages
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Content {
+    Text {
+        text: String,
+    },
+    Image {
+        data: String,
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+    },
+    Audio {
+        data: String,
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+    },
+    Resource {
+        resource: ResourceContents,
+    },
+    /// Content types this client does not know about
+    #[serde(other)]
+    Unknown,
+}
+
+/// Result of a `tools/call` request
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CallToolResult {
+    #[serde(default)]
+    pub content: Vec<Content>,
+    #[serde(default)]
+    pub structured_content: Option<serde_json::Value>,
+    /// True when the tool itself reported a failure
+    #[serde(default)]
+    pub is_error: bool,
+}
+
+/// A resource exposed by a server
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Resource {
+    pub uri: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+}
+
+/// An argument accepted by a prompt
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PromptArgument {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// A prompt template exposed by a server
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Prompt {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub arguments: Vec<PromptArgument>,
+}
+
+/// A message of a rendered prompt
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PromptMessage {
+    pub role: String,
+    pub content: Content,
+}
+
+/// Result of a `prompts/get` request
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GetPromptResult {
+    #[serde(default)]
+    pub description: Option<String>,
+    pub messages: Vec<PromptMessage>,
+}
+
+fn parse_result<T: DeserializeOwned>(method: &str, value: serde_json::Value) -> Result<T, MCPClientError> {
+    serde_json::from_value(value).map_err(|e| {
+        MCPClientError::protocol_error(&format!("Unexpected {} result: {}", method, e))
+    })
+}
+
+/// Cloneable async handle to the plugin's connections
+pub struct McpClient<R: Runtime = tauri::Wry> {
+    registry: ConnectionRegistry<R>,
+}
+
+impl<R: Runtime> McpClient<R> {
+    /// Create a client over an existing registry
+    pub fn new(registry: ConnectionRegistry<R>) -> Self {
+        Self { registry }
+    }
+
+    /// The underlying connection registry
+    pub fn registry(&self) -> &ConnectionRegistry<R> {
+        &self.registry
+    }
+
+    /// Connect to a server
+    pub async fn connect(&self, server_id: &str, config: ServerConfig) -> Result<(), MCPClientError> {
+        self.registry
+            .connect_server_with_config(server_id.to_string(), config)
+            .await
+    }
+
+    /// Disconnect from a server
+    pub async fn disconnect(&self, server_id: &str) -> Result<(), MCPClientError> {
+        self.registry.disconnect_server(server_id).await
+    }
+
+    /// Status of all known connections
+    pub fn connections(&self) -> Result<Vec<ConnectionInfo>, MCPClientError> {
+        self.registry
+            .get_connection_statuses()
+            .map_err(|e| MCPClientError::system_error(&e))
+    }
+
+    /// Whether the server is currently connected
+    pub fn is_connected(&self, server_id: &str) -> bool {
+        self.registry.is_server_connected(server_id).unwrap_or(false)
+    }
+
+    /// Send a raw JSON-RPC request and return its `result`
+    pub async fn request(
+        &self,
+        server_id: &str,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, MCPClientError> {
+        self.registry
+            .request(server_id, method, params, self.registry.settings().request_timeout_ms)
+            .await
+    }
+
+    /// Collect all pages of a paginated list request
+    async fn list_all<T: DeserializeOwned>(
+        &self,
+        server_id: &str,
+        method: &str,
+        field: &str,
+    ) -> Result<Vec<T>, MCPClientError> {
+        let mut items = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match cursor {
+                Some(ref cursor) => serde_json::json!({ "cursor": cursor }),
+                None => serde_json::json!({}),
+            };
+            let mut result = self.request(server_id, method, params).await?;
+            let page = result
+                .get_mut(field)
+                .map(serde_json::Value::take)
+                .unwrap_or_else(|| serde_json::json!([]));
+            items.extend(parse_result::<Vec<T>>(method, page)?);
+
+            cursor = result
+                .get("nextCursor")
+                .and_then(|c| c.as_str())
+                .map(str::to_string);
+            if cursor.is_none() {
+                return Ok(items);
+            }
+        }
+    }
+
+    /// List the tools of a server
+    pub async fn list_tools(&self, server_id: &str) -> Result<Vec<Tool>, MCPClientError> {
+        self.list_all(server_id, "tools/list", "tools").await
+    }
+
+    /// Call a tool
+    pub async fn call_tool(
+        &self,
+        server_id: &str,
+        tool_name: &str,
+        arguments: serde_json::Value,
+    ) -> Result<CallToolResult, MCPClientError> {
+        let (result, _duration_ms) = self
+            .registry
+            .execute_tool(server_id, tool_name, arguments)
+            .await?;
+        parse_result("tools/call", result)
+    }
+
+    /// List the resources of a server
+    pub async fn list_resources(&self, server_id: &str) -> Result<Vec<Resource>, MCPClientError> {
+        self.list_all(server_id, "resources/list", "resources").await
+    }
+
+    /// Read a resource by URI
+    pub async fn read_resource(
+        &self,
+        server_id: &str,
+        uri: &str,
+    ) -> Result<Vec<ResourceContents>, MCPClientError> {
+        let mut result = self
+            .request(server_id, "resources/read", serde_json::json!({ "uri": uri }))
+            .await?;
+        let contents = result
+            .get_mut("contents")
+            .map(serde_json::Value::take)
+            .unwrap_or_else(|| serde_json::json!([]));
+        parse_result("resources/read", contents)
+    }
+
+    /// List the prompts of a server
+    pub async fn list_prompts(&self, server_id: &str) -> Result<Vec<Prompt>, MCPClientError> {
+        self.list_all(server_id, "prompts/list", "prompts").await
+    }
+
+    /// Render a prompt with the given arguments
+    pub async fn get_prompt(
+        &self,
+        server_id: &str,
+        name: &str,
+        arguments: HashMap<String, String>,
+    ) -> Result<GetPromptResult, MCPClientError> {
+        let result = self
+            .request(
+                server_id,
+                "prompts/get",
+                serde_json::json!({ "name": name, "arguments": arguments }),
+            )
+            .await?;
+        parse_result("prompts/get", result)
+    }
+
+    /// Check a connected server against the MCP protocol
+    pub async fn run_conformance(&self, server_id: &str, options: &ConformanceOptions) -> ConformanceReport {
+        conformance::run_conformance(&self.registry, server_id, options).await
+    }
+}
+
+impl<R: Runtime> Clone for McpClient<R> {
+    fn clone(&self) -> Self {
+        Self {
+            registry: self.registry.clone(),
+        }
+    }
+}
+
+/// Access the MCP plugin from an `App`, `AppHandle`, window or webview
+pub trait McpExt<R: Runtime> {
+    fn mcp(&self) -> McpClient<R>;
+}
+
+impl<R: Runtime, T: Manager<R>> McpExt<R> for T {
+    fn mcp(&self) -> McpClient<R> {
+        McpClient::new(self.state::<ConnectionRegistry<R>>().inner().clone())
+    }
+}
@@ -1,6 +1,28 @@
 use serde::{Deserialize, Serialize};
-use tauri::{command, AppHandle, Runtime, State, Window};
+use tauri::{
+    command,
+    ipc::{CommandScope, GlobalScope},
+    AppHandle, Runtime, State, Window,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+use crate::approval::{ApprovalDecision, ApprovalRequest};
+use crate::audit::{AuditEntry, AuditQuery};
+use crate::config::{ServerConfig, ServerConfigStore, TransportKind};
+use crate::conformance::{self, ConformanceOptions, ConformanceReport};
+use crate::error::MCPClientError;
+use crate::import::{self, ConfigSource, ImportOptions, ImportResult};
+use crate::limits::RateLimits;
+use crate::resources::ResourceMonitorConfig;
+use crate::sandbox::SandboxConfig;
+use crate::liveness::LivenessConfig;
+use crate::policy::SpawnRequest;
 use crate::registry::{ConnectionRegistry, ConnectionInfo};
+use crate::restart::RestartPolicy;
+use crate::scope::{McpScope, ScopeRules};
+use crate::secrets::EnvValue;
+use crate::shutdown::ShutdownConfig;
+use crate::traffic::{TrafficConfig, TrafficEntry};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct HealthCheckResponse {
@@ -16,7 +38,7 @@ pub async fn health_check<R: Runtime>(
     _app: AppHandle<R>,
     _window: Window<R>,
 ) -> Result<HealthCheckResponse, String> {
-    println!("Plugin health_check command called!");
+    debug_log!(command = "health_check", "Command called");
     Ok(HealthCheckResponse {
         status: "ok".to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
@@ -30,17 +52,74 @@ pub async fn health_check<R: Runtime>(
 pub async fn get_connection_statuses<R: Runtime>(
     _app: AppHandle<R>,
     _window: Window<R>,
+    global_scope: GlobalScope<McpScope>,
+    scope: CommandScope<McpScope>,
     registry: State<'_, ConnectionRegistry>,
 ) -> Result<Vec<ConnectionInfo>, String> {
-    println!("Plugin get_connection_statuses command called!");
-    registry.get_connection_statuses()
+    debug_log!(command = "get_connection_statuses", "Command called");
+    let rules = ScopeRules::from_scopes(&global_scope, &scope);
+    let mut statuses = registry.get_connection_statuses()?;
+    statuses.retain(|info| rules.server_allowed(&info.server_id));
+    Ok(statuses)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ConnectServerRequest {
     pub server_id: String,
+    /// Not needed for the replay transport
+    #[serde(default)]
     pub command: String,
+    #[serde(default)]
     pub args: Vec<String>,
+    /// Extra environment variables; values may reference secrets by name
+    #[serde(default)]
+    pub env: HashMap<String, EnvValue>,
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub transport: TransportKind,
+    /// Recorded session played back by the replay transport
+    #[serde(default)]
+    pub session: Option<String>,
+    #[serde(default)]
+    pub restart: RestartPolicy,
+    #[serde(default)]
+    pub liveness: LivenessConfig,
+    #[serde(default)]
+    pub shutdown: ShutdownConfig,
+    #[serde(default)]
+    pub traffic: TrafficConfig,
+    #[serde(default)]
+    pub limits: RateLimits,
+    #[serde(default)]
+    pub sandbox: SandboxConfig,
+    #[serde(default)]
+    pub resources: ResourceMonitorConfig,
+}
+
+impl ConnectServerRequest {
+    /// Convert the request into a server definition
+    pub fn into_server_config(self) -> (String, ServerConfig) {
+        (
+            self.server_id,
+            ServerConfig {
+                command: self.command,
+                args: self.args,
+                env: self.env,
+                cwd: self.cwd,
+                transport: self.transport,
+                session: self.session,
+                restart: self.restart,
+                liveness: self.liveness,
+                shutdown: self.shutdown,
+                traffic: self.traffic,
+                limits: self.limits,
+                sandbox: self.sandbox,
+                resources: self.resources,
+                ..Default::default()
+            },
+        )
+    }
 }
 
 /// Connect to an MCP server through the plugin (parallel to main system)
@@ -49,17 +128,21 @@ pub async fn plugin_connect_server<R: Runtime>(
     _app: AppHandle<R>,
     _window: Window<R>,
     registry: State<'_, ConnectionRegistry>,
+    global_scope: GlobalScope<McpScope>,
+    scope: CommandScope<McpScope>,
     request: ConnectServerRequest,
 ) -> Result<String, String> {
-    println!("Plugin connect_server command called for server: {}", request.server_id);
-    
-    match registry.connect_server(request.server_id.clone(), request.command, request.args).await {
+    debug_log!(command = "connect_server", server_id = %request.server_id, "Command called");
+    let (server_id, config) = request.into_server_config();
+    let rules = ScopeRules::from_scopes(&global_scope, &scope);
+    rules.check_server(&server_id).map_err(|e| format!("Failed to connect: {}", e))?;
+
+    // The window's capability may narrow the plugin's spawn policy
+    match registry.connect_server_in_scope(server_id.clone(), config, &rules).await {
         Ok(()) => {
-            println!("Plugin successfully connected to server: {}", request.server_id);
-            Ok(format!("Successfully connected to server: {}", request.server_id))
+            Ok(format!("Successfully connected to server: {}", server_id))
         }
         Err(e) => {
-            println!("Plugin failed to connect to server {}: {}", request.server_id, e);
             Err(format!("Failed to connect: {}", e))
         }
     }
@@ -70,18 +153,21 @@ pub async fn plugin_connect_server<R: Runtime>(
 pub async fn plugin_disconnect_server<R: Runtime>(
     _app: AppHandle<R>,
     _window: Window<R>,
+    global_scope: GlobalScope<McpScope>,
+    scope: CommandScope<McpScope>,
     registry: State<'_, ConnectionRegistry>,
     server_id: String,
 ) -> Result<String, String> {
-    println!("Plugin disconnect_server command called for server: {}", server_id);
+    debug_log!(command = "disconnect_server", server_id = %server_id, "Command called");
+    let rules = ScopeRules::from_scopes(&global_scope, &scope);
+    rules.check_server(&server_id).map_err(|e| format!("Failed to disconnect: {}", e))?;
     
     match registry.disconnect_server(&server_id).await {
         Ok(()) => {
-            println!("Plugin successfully disconnected from server: {}", server_id);
             Ok(format!("Successfully disconnected from server: {}", server_id))
         }
         Err(e) => {
-            println!("Plugin failed to disconnect from server {}: {}", server_id, e);
+            warn_log!(server_id = %server_id, error = %e, "Failed to disconnect from server");
             Err(format!("Failed to disconnect: {}", e))
         }
     }
@@ -92,18 +178,22 @@ pub async fn plugin_disconnect_server<R: Runtime>(
 pub async fn plugin_list_tools<R: Runtime>(
     _app: AppHandle<R>,
     _window: Window<R>,
+    global_scope: GlobalScope<McpScope>,
+    scope: CommandScope<McpScope>,
     registry: State<'_, ConnectionRegistry>,
     server_id: String,
 ) -> Result<serde_json::Value, String> {
-    println!("Plugin list_tools command called for server: {}", server_id);
+    debug_log!(command = "list_tools", server_id = %server_id, "Command called");
+    let rules = ScopeRules::from_scopes(&global_scope, &scope);
+    rules.check_server(&server_id).map_err(|e| format!("Failed to list tools: {}", e))?;
     
     match registry.list_tools(&server_id).await {
-        Ok(tools) => {
-            println!("Plugin successfully listed tools for server: {}", server_id);
+        Ok(mut tools) => {
+            rules.filter_tools(&server_id, &mut tools);
             Ok(tools)
         }
         Err(e) => {
-            println!("Plugin failed to list tools for server {}: {}", server_id, e);
+            warn_log!(server_id = %server_id, error = %e, "Failed to list tools");
             Err(format!("Failed to list tools: {}", e))
         }
     }
@@ -126,23 +216,445 @@ pub struct ExecuteToolResponse {
 #[command]
 pub async fn plugin_execute_tool<R: Runtime>(
     _app: AppHandle<R>,
-    _window: Window<R>,
+    window: Window<R>,
+    global_scope: GlobalScope<McpScope>,
+    scope: CommandScope<McpScope>,
     registry: State<'_, ConnectionRegistry>,
     request: ExecuteToolRequest,
 ) -> Result<ExecuteToolResponse, String> {
-    println!("Plugin execute_tool command called for server: {} tool: {}", request.server_id, request.tool_name);
-    
-    match registry.execute_tool(&request.server_id, &request.tool_name, request.arguments).await {
+    debug_log!(command = "execute_tool", server_id = %request.server_id, tool_name = %request.tool_name, "Command called");
+    let rules = ScopeRules::from_scopes(&global_scope, &scope);
+    if let Err(e) = rules.check_tool(&request.server_id, &request.tool_name) {
+        let record = registry.tool_call_record(Some(window.label()), &request.server_id, &request.tool_name, &request.arguments);
+        registry
+            .audit_log()
+            .record_tool_call(&request.server_id, record, Duration::ZERO, Err(&e));
+        return Err(format!("Failed to execute tool: {}", e));
+    }
+
+    match registry
+        .execute_tool_for(Some(window.label()), &request.server_id, &request.tool_name, request.arguments)
+        .await
+    {
         Ok((result, duration_ms)) => {
-            println!("Plugin successfully executed tool {} for server: {} in {}ms", request.tool_name, request.server_id, duration_ms);
+            info_log!(server_id = %request.server_id, tool_name = %request.tool_name, duration_ms, "Executed tool");
             Ok(ExecuteToolResponse {
                 result,
                 duration_ms,
             })
         }
         Err(e) => {
-            println!("Plugin failed to execute tool {} for server {}: {}", request.tool_name, request.server_id, e);
+            warn_log!(server_id = %request.server_id, tool_name = %request.tool_name, error = %e, "Failed to execute tool");
             Err(format!("Failed to execute tool: {}", e))
         }
     }
-}
\ No newline at end of file
+}
+
+/// Tool calls waiting for the user's approval
+#[command]
+pub async fn plugin_list_pending_approvals<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    global_scope: GlobalScope<McpScope>,
+    scope: CommandScope<McpScope>,
+    registry: State<'_, ConnectionRegistry>,
+) -> Result<Vec<ApprovalRequest>, String> {
+    debug_log!(command = "list_pending_approvals", "Command called");
+    let rules = ScopeRules::from_scopes(&global_scope, &scope);
+    Ok(registry
+        .pending_approvals()
+        .into_iter()
+        .filter(|request| rules.tool_allowed(&request.server_id, &request.tool_name))
+        .collect())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RespondApprovalRequest {
+    pub id: String,
+    pub approved: bool,
+    /// Apply the decision to later calls of the same tool on the same server
+    #[serde(default)]
+    pub remember: bool,
+}
+
+/// Approve or reject a tool call waiting for the user's approval
+#[command]
+pub async fn plugin_respond_approval<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    global_scope: GlobalScope<McpScope>,
+    scope: CommandScope<McpScope>,
+    registry: State<'_, ConnectionRegistry>,
+    request: RespondApprovalRequest,
+) -> Result<(), String> {
+    debug_log!(command = "respond_approval", id = %request.id, approved = request.approved, "Command called");
+    let rules = ScopeRules::from_scopes(&global_scope, &scope);
+    if let Some(pending) = registry.pending_approvals().into_iter().find(|pending| pending.id == request.id) {
+        rules
+            .check_tool(&pending.server_id, &pending.tool_name)
+            .map_err(|e| format!("Failed to respond to approval: {}", e))?;
+    }
+
+    let decision = ApprovalDecision {
+        approved: request.approved,
+        remember: request.remember,
+    };
+    registry.respond_approval(&request.id, decision).map_err(|e| {
+        warn_log!(id = %request.id, error = %e, "Failed to respond to approval");
+        format!("Failed to respond to approval: {}", e)
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ForgetApprovalRequest {
+    pub server_id: String,
+    pub tool_name: String,
+}
+
+/// Forget a remembered approval decision, so the next call asks again
+#[command]
+pub async fn plugin_forget_approval<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    global_scope: GlobalScope<McpScope>,
+    scope: CommandScope<McpScope>,
+    registry: State<'_, ConnectionRegistry>,
+    request: ForgetApprovalRequest,
+) -> Result<(), String> {
+    debug_log!(command = "forget_approval", server_id = %request.server_id, tool_name = %request.tool_name, "Command called");
+    ScopeRules::from_scopes(&global_scope, &scope)
+        .check_tool(&request.server_id, &request.tool_name)
+        .map_err(|e| format!("Failed to forget approval: {}", e))?;
+    registry
+        .approvals()
+        .forget(&request.server_id, &request.tool_name)
+        .map_err(|e| format!("Failed to forget approval: {}", e))
+}
+
+/// Read back the audit log, including entries of earlier app runs
+#[command]
+pub async fn plugin_query_audit_log<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    global_scope: GlobalScope<McpScope>,
+    scope: CommandScope<McpScope>,
+    registry: State<'_, ConnectionRegistry>,
+    query: Option<AuditQuery>,
+) -> Result<Vec<AuditEntry>, String> {
+    debug_log!(command = "query_audit_log", "Command called");
+    let rules = ScopeRules::from_scopes(&global_scope, &scope);
+    let mut query = query.unwrap_or_default();
+    // Apply the limit after dropping the servers the window may not see
+    let limit = query.limit.take();
+    let mut entries = registry
+        .audit_log()
+        .query(&query)
+        .map_err(|e| format!("Failed to read audit log: {}", e))?;
+    entries.retain(|entry| rules.server_allowed(&entry.server_id));
+    if let Some(limit) = limit {
+        entries.drain(..entries.len().saturating_sub(limit));
+    }
+    Ok(entries)
+}
+
+/// List resources from an MCP server through the plugin
+#[command]
+pub async fn plugin_list_resources<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    global_scope: GlobalScope<McpScope>,
+    scope: CommandScope<McpScope>,
+    registry: State<'_, ConnectionRegistry>,
+    server_id: String,
+) -> Result<serde_json::Value, String> {
+    debug_log!(command = "list_resources", server_id = %server_id, "Command called");
+    let rules = ScopeRules::from_scopes(&global_scope, &scope);
+    rules.check_server(&server_id).map_err(|e| format!("Failed to list resources: {}", e))?;
+
+    let mut resources = registry
+        .list_resources(&server_id)
+        .await
+        .map_err(|e| format!("Failed to list resources: {}", e))?;
+    rules.filter_resources(&server_id, &mut resources);
+    Ok(resources)
+}
+
+/// Read a resource from an MCP server through the plugin
+#[command]
+pub async fn plugin_read_resource<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    global_scope: GlobalScope<McpScope>,
+    scope: CommandScope<McpScope>,
+    registry: State<'_, ConnectionRegistry>,
+    server_id: String,
+    uri: String,
+) -> Result<serde_json::Value, String> {
+    debug_log!(command = "read_resource", server_id = %server_id, uri = %uri, "Command called");
+    ScopeRules::from_scopes(&global_scope, &scope)
+        .check_resource(&server_id, &uri)
+        .map_err(|e| format!("Failed to read resource: {}", e))?;
+
+    registry
+        .read_resource(&server_id, &uri)
+        .await
+        .map_err(|e| format!("Failed to read resource: {}", e))
+}
+
+/// Get the recorded JSON-RPC messages of a server
+#[command]
+pub async fn plugin_get_traffic<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    global_scope: GlobalScope<McpScope>,
+    scope: CommandScope<McpScope>,
+    registry: State<'_, ConnectionRegistry>,
+    server_id: String,
+) -> Result<Vec<TrafficEntry>, String> {
+    debug_log!(command = "get_traffic", server_id = %server_id, "Command called");
+    let rules = ScopeRules::from_scopes(&global_scope, &scope);
+    rules.check_server(&server_id).map_err(|e| format!("Failed to get traffic: {}", e))?;
+    registry
+        .traffic(&server_id)
+        .map_err(|e| format!("Failed to get traffic: {}", e))
+}
+
+/// Export the recorded JSON-RPC messages of a server as JSON Lines
+#[command]
+pub async fn plugin_export_traffic<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    global_scope: GlobalScope<McpScope>,
+    scope: CommandScope<McpScope>,
+    registry: State<'_, ConnectionRegistry>,
+    server_id: String,
+) -> Result<String, String> {
+    debug_log!(command = "export_traffic", server_id = %server_id, "Command called");
+    let rules = ScopeRules::from_scopes(&global_scope, &scope);
+    rules.check_server(&server_id).map_err(|e| format!("Failed to export traffic: {}", e))?;
+    registry
+        .export_traffic(&server_id)
+        .map_err(|e| format!("Failed to export traffic: {}", e))
+}
+
+/// Drop the recorded JSON-RPC messages of a server
+#[command]
+pub async fn plugin_clear_traffic<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    global_scope: GlobalScope<McpScope>,
+    scope: CommandScope<McpScope>,
+    registry: State<'_, ConnectionRegistry>,
+    server_id: String,
+) -> Result<String, String> {
+    debug_log!(command = "clear_traffic", server_id = %server_id, "Command called");
+    let rules = ScopeRules::from_scopes(&global_scope, &scope);
+    rules.check_server(&server_id).map_err(|e| format!("Failed to clear traffic: {}", e))?;
+    registry
+        .clear_traffic(&server_id)
+        .map(|()| format!("Cleared traffic for server: {}", server_id))
+        .map_err(|e| format!("Failed to clear traffic: {}", e))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConformanceRequest {
+    pub server_id: String,
+    /// Connect with this definition for the run and disconnect afterwards;
+    /// otherwise the server must already be connected
+    #[serde(default)]
+    pub config: Option<ServerConfig>,
+    #[serde(default)]
+    pub options: ConformanceOptions,
+}
+
+/// Run the MCP conformance checks against a server
+#[command]
+pub async fn plugin_run_conformance<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    global_scope: GlobalScope<McpScope>,
+    scope: CommandScope<McpScope>,
+    registry: State<'_, ConnectionRegistry>,
+    request: ConformanceRequest,
+) -> Result<ConformanceReport, String> {
+    debug_log!(command = "run_conformance", server_id = %request.server_id, "Command called");
+    let rules = ScopeRules::from_scopes(&global_scope, &scope);
+    rules
+        .check_server(&request.server_id)
+        .map_err(|e| format!("Failed to run conformance checks: {}", e))?;
+    let server_id = request.server_id;
+
+    let temporary = request.config.is_some();
+    if let Some(config) = request.config {
+        registry
+            .connect_server_in_scope(server_id.clone(), config, &rules)
+            .await
+            .map_err(|e| format!("Failed to connect: {}", e))?;
+    } else if !registry.is_server_connected(&server_id).unwrap_or(false) {
+        return Err(format!("Server {} is not connected", server_id));
+    }
+
+    let report = conformance::run_conformance(registry.inner(), &server_id, &request.options).await;
+    if temporary {
+        if let Err(e) = registry.disconnect_server(&server_id).await {
+            warn_log!(server_id = %server_id, error = %e, "Failed to disconnect after conformance run");
+        }
+    }
+    Ok(report)
+}
+
+/// List the servers defined in the plugin's configuration file
+#[command]
+pub async fn plugin_list_server_configs<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    global_scope: GlobalScope<McpScope>,
+    scope: CommandScope<McpScope>,
+    store: State<'_, ServerConfigStore>,
+) -> Result<BTreeMap<String, ServerConfig>, String> {
+    debug_log!(command = "list_server_configs", "Command called");
+    let rules = ScopeRules::from_scopes(&global_scope, &scope);
+    let mut configs = store.list().map_err(|e| format!("Failed to list server configs: {}", e))?;
+    configs.retain(|server_id, _| rules.server_allowed(server_id));
+    Ok(configs)
+}
+
+/// Add a server to the configuration file
+#[command]
+pub async fn plugin_add_server_config<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    global_scope: GlobalScope<McpScope>,
+    scope: CommandScope<McpScope>,
+    store: State<'_, ServerConfigStore>,
+    server_id: String,
+    config: ServerConfig,
+) -> Result<String, String> {
+    debug_log!(command = "add_server_config", server_id = %server_id, "Command called");
+    let rules = ScopeRules::from_scopes(&global_scope, &scope);
+    rules.check_server(&server_id).map_err(|e| format!("Failed to add server config: {}", e))?;
+    check_auto_start(&rules, &server_id, &config).map_err(|e| format!("Failed to add server config: {}", e))?;
+    store
+        .add(&server_id, config)
+        .map(|()| format!("Added server config: {}", server_id))
+        .map_err(|e| format!("Failed to add server config: {}", e))
+}
+
+/// Replace a server in the configuration file
+#[command]
+pub async fn plugin_update_server_config<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    global_scope: GlobalScope<McpScope>,
+    scope: CommandScope<McpScope>,
+    store: State<'_, ServerConfigStore>,
+    server_id: String,
+    config: ServerConfig,
+) -> Result<String, String> {
+    debug_log!(command = "update_server_config", server_id = %server_id, "Command called");
+    let rules = ScopeRules::from_scopes(&global_scope, &scope);
+    rules.check_server(&server_id).map_err(|e| format!("Failed to update server config: {}", e))?;
+    check_auto_start(&rules, &server_id, &config).map_err(|e| format!("Failed to update server config: {}", e))?;
+    store
+        .update(&server_id, config)
+        .map(|()| format!("Updated server config: {}", server_id))
+        .map_err(|e| format!("Failed to update server config: {}", e))
+}
+
+/// Servers saved with `autoStart` are spawned at the next launch, with no
+/// window to check, so they must be startable by the saving window
+fn check_auto_start(rules: &ScopeRules, server_id: &str, config: &ServerConfig) -> Result<(), MCPClientError> {
+    if config.auto_start && config.transport == TransportKind::Stdio {
+        rules.check_spawn(server_id, &SpawnRequest::from_config(config))?;
+    }
+    Ok(())
+}
+
+/// Remove a server from the configuration file
+#[command]
+pub async fn plugin_remove_server_config<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    global_scope: GlobalScope<McpScope>,
+    scope: CommandScope<McpScope>,
+    store: State<'_, ServerConfigStore>,
+    server_id: String,
+) -> Result<String, String> {
+    debug_log!(command = "remove_server_config", server_id = %server_id, "Command called");
+    let rules = ScopeRules::from_scopes(&global_scope, &scope);
+    rules.check_server(&server_id).map_err(|e| format!("Failed to remove server config: {}", e))?;
+    store
+        .remove(&server_id)
+        .map(|_| format!("Removed server config: {}", server_id))
+        .map_err(|e| format!("Failed to remove server config: {}", e))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportServersRequest {
+    /// Source format; detected from the contents when omitted
+    pub source: Option<ConfigSource>,
+    /// File to read; must be one of the hosts' user configuration files.
+    /// Defaults to the source host's file.
+    pub path: Option<String>,
+    /// Configuration text to import instead of reading a file
+    pub contents: Option<String>,
+    #[serde(default)]
+    pub options: ImportOptions,
+    /// Save the imported servers into the plugin's configuration file
+    #[serde(default)]
+    pub save: bool,
+    /// Replace existing entries with the same id when saving
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+/// Import servers from Claude Desktop, Cursor or VS Code configuration
+#[command]
+pub async fn plugin_import_servers<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    global_scope: GlobalScope<McpScope>,
+    scope: CommandScope<McpScope>,
+    store: State<'_, ServerConfigStore>,
+    request: ImportServersRequest,
+) -> Result<ImportResult, String> {
+    debug_log!(command = "import_servers", source = ?request.source, "Command called");
+
+    let imported = if let Some(ref contents) = request.contents {
+        import::import_servers(contents, request.source, &request.options)
+    } else {
+        let path = match (request.path, request.source) {
+            (Some(path), _) => std::path::PathBuf::from(path),
+            (None, Some(source)) => import::default_config_path(source)
+                .ok_or_else(|| format!("No default config location known for {:?}", source))?,
+            (None, None) => return Err("Either source, path or contents is required".to_string()),
+        };
+        if !import::is_known_config_path(&path) {
+            let error = MCPClientError::permission_denied(&path.display().to_string())
+                .with_details("Only the Claude Desktop, Cursor and VS Code configuration files can be imported by path");
+            return Err(format!("Failed to import servers: {}", error));
+        }
+        import::import_file(&path, request.source, &request.options)
+    };
+    let mut result = imported.map_err(|e| format!("Failed to import servers: {}", e))?;
+    let rules = ScopeRules::from_scopes(&global_scope, &scope);
+    result.servers.retain(|server| rules.server_allowed(&server.server_id));
+
+    if request.save {
+        for server in result.servers.iter_mut() {
+            let saved = match store.get(&server.server_id) {
+                Ok(Some(_)) if request.overwrite => store.update(&server.server_id, server.config.clone()),
+                Ok(Some(_)) => {
+                    server.warnings.push("Not saved: a server with this id is already configured".to_string());
+                    continue;
+                }
+                _ => store.add(&server.server_id, server.config.clone()),
+            };
+            if let Err(e) = saved {
+                server.warnings.push(format!("Not saved: {}", e.message));
+            }
+        }
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,383 @@
+/// Declarative server configuration using the common `mcpServers` file shape
+///
+/// ```json
+/// {
+///   "mcpServers": {
+///     "github": {
+///       "command": "npx",
+///       "args": ["-y", "@modelcontextprotocol/server-github"],
+///       "env": { "GITHUB_TOKEN": { "secret": "github" } },
+///       "autoStart": true
+///     }
+///   }
+/// }
+/// ```
+///
+/// The same structure can be written as TOML (`[mcpServers.github]`); the format
+/// is picked from the file extension.
+use crate::error::MCPClientError;
+use crate::limits::RateLimits;
+use crate::liveness::LivenessConfig;
+use crate::resources::ResourceMonitorConfig;
+use crate::restart::RestartPolicy;
+use crate::sandbox::SandboxConfig;
+use crate::secrets::EnvValue;
+use crate::shutdown::ShutdownConfig;
+use crate::traffic::TrafficConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// File name used in the app config dir when no path is configured
+pub const DEFAULT_CONFIG_FILE_NAME: &str = "mcp.json";
+
+/// Transport used to talk to a server
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TransportKind {
+    #[default]
+    Stdio,
+    Sse,
+    Http,
+    /// Plays back a recorded session instead of running a server
+    Replay,
+    /// Mock server registered with `ConnectionRegistry::connect_mock`.
+    /// Only connects with the `testing` feature.
+    Mock,
+}
+
+/// Definition of a single MCP server
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerConfig {
+    #[serde(default)]
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, EnvValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub transport: TransportKind,
+    /// Endpoint for remote transports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Recorded session (traffic JSON Lines) played back by the replay
+    /// transport, resolved inside the plugin's `session_dir`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    /// Connect automatically when the plugin starts
+    #[serde(default)]
+    pub auto_start: bool,
+    /// What to do when the server process exits unexpectedly
+    #[serde(default, skip_serializing_if = "RestartPolicy::is_default")]
+    pub restart: RestartPolicy,
+    /// Periodic ping checks for hung servers
+    #[serde(default, skip_serializing_if = "LivenessConfig::is_default")]
+    pub liveness: LivenessConfig,
+    /// Grace periods used when the server is stopped
+    #[serde(default, skip_serializing_if = "ShutdownConfig::is_default")]
+    pub shutdown: ShutdownConfig,
+    /// Recording of the JSON-RPC messages exchanged with the server
+    #[serde(default, skip_serializing_if = "TrafficConfig::is_default")]
+    pub traffic: TrafficConfig,
+    /// Rate limits and concurrency quotas for tool calls, on top of the
+    /// plugin-wide ones
+    #[serde(default, skip_serializing_if = "RateLimits::is_default")]
+    pub limits: RateLimits,
+    /// Resource limits, seccomp filter and namespaces the process runs in
+    #[serde(default, skip_serializing_if = "SandboxConfig::is_default")]
+    pub sandbox: SandboxConfig,
+    /// Periodic sampling of the process's CPU, memory, threads and open files
+    #[serde(default, skip_serializing_if = "ResourceMonitorConfig::is_default")]
+    pub resources: ResourceMonitorConfig,
+}
+
+impl ServerConfig {
+    /// Create a stdio server definition
+    pub fn new(command: &str, args: Vec<String>) -> Self {
+        Self {
+            command: command.to_string(),
+            args,
+            ..Default::default()
+        }
+    }
+
+    /// Check that the definition can be used to spawn a server
+    pub fn validate(&self, server_id: &str) -> Result<(), MCPClientError> {
+        self.validate_definition(server_id)?;
+        match self.transport {
+            TransportKind::Stdio | TransportKind::Replay => Ok(()),
+            TransportKind::Mock if cfg!(feature = "testing") => Ok(()),
+            TransportKind::Mock => Err(MCPClientError::new(
+                crate::error::ErrorCategory::Configuration,
+                "UNSUPPORTED_TRANSPORT",
+                &format!("Transport 'Mock' is not supported for server {}", server_id),
+            )
+            .with_details("The mock transport is only available with the testing feature")
+            .with_suggestion("Enable the testing feature of tauri-plugin-mcp-client")),
+            other => Err(MCPClientError::new(
+                crate::error::ErrorCategory::Configuration,
+                "UNSUPPORTED_TRANSPORT",
+                &format!("Transport '{:?}' is not supported for server {}", other, server_id),
+            )
+            .with_details("Only stdio and replay servers can currently be connected")
+            .with_suggestion("Use a stdio server or a local proxy such as mcp-remote")),
+        }
+    }
+
+    /// Check that the definition is complete enough to be stored, whether
+    /// or not its transport can be connected yet
+    pub fn validate_definition(&self, server_id: &str) -> Result<(), MCPClientError> {
+        if server_id.trim().is_empty() {
+            return Err(MCPClientError::configuration_error(
+                "server_id",
+                "Server id must not be empty",
+            ));
+        }
+        self.limits.validate(&format!("{}.limits", server_id))?;
+        match self.transport {
+            TransportKind::Stdio => {
+                if self.command.trim().is_empty() {
+                    return Err(MCPClientError::configuration_error(
+                        &format!("{}.command", server_id),
+                        "A command is required for stdio servers",
+                    ));
+                }
+                Ok(())
+            }
+            TransportKind::Replay => {
+                if self.session.as_deref().is_none_or(|s| s.trim().is_empty()) {
+                    return Err(MCPClientError::configuration_error(
+                        &format!("{}.session", server_id),
+                        "A recorded session is required for replay servers",
+                    ));
+                }
+                Ok(())
+            }
+            TransportKind::Sse | TransportKind::Http => {
+                if self.url.as_deref().is_none_or(|url| url.trim().is_empty()) {
+                    return Err(MCPClientError::configuration_error(
+                        &format!("{}.url", server_id),
+                        "A url is required for remote servers",
+                    ));
+                }
+                Ok(())
+            }
+            TransportKind::Mock => Ok(()),
+        }
+    }
+}
+
+/// Contents of a server configuration file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ServersFile {
+    #[serde(rename = "mcpServers", default)]
+    pub mcp_servers: BTreeMap<String, ServerConfig>,
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.eq_ignore_ascii_case("toml"))
+        .unwrap_or(false)
+}
+
+/// Parse a configuration file from its text
+pub fn parse_servers_file(contents: &str, toml_format: bool) -> Result<ServersFile, MCPClientError> {
+    let file: ServersFile = if toml_format {
+        toml::from_str(contents)
+            .map_err(|e| MCPClientError::configuration_error("mcpServers", &e.to_string()))?
+    } else {
+        serde_json::from_str(contents)
+            .map_err(|e| MCPClientError::configuration_error("mcpServers", &e.to_string()))?
+    };
+    for (server_id, config) in &file.mcp_servers {
+        config.limits.validate(&format!("{}.limits", server_id))?;
+    }
+    Ok(file)
+}
+
+/// Load a configuration file; a missing file yields an empty configuration
+pub fn load_servers_file(path: &Path) -> Result<ServersFile, MCPClientError> {
+    if !path.exists() {
+        return Ok(ServersFile::default());
+    }
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        MCPClientError::configuration_error(
+            "config file",
+            &format!("Failed to read {}: {}", path.display(), e),
+        )
+    })?;
+    parse_servers_file(&contents, is_toml(path))
+}
+
+/// Write a configuration file in the format matching its extension
+pub fn save_servers_file(path: &Path, file: &ServersFile) -> Result<(), MCPClientError> {
+    let contents = if is_toml(path) {
+        toml::to_string_pretty(file)
+            .map_err(|e| MCPClientError::configuration_error("mcpServers", &e.to_string()))?
+    } else {
+        serde_json::to_string_pretty(file)
+            .map_err(|e| MCPClientError::configuration_error("mcpServers", &e.to_string()))?
+    };
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| {
+            MCPClientError::system_error(&format!(
+                "Failed to create config directory {}: {}",
+                parent.display(),
+                e
+            ))
+        })?;
+    }
+    write_atomically(path, contents.as_bytes()).map_err(|e| {
+        MCPClientError::system_error(&format!(
+            "Failed to write config file {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// Write to a temporary file next to `path` and rename it over `path`, so a
+/// crash never leaves a half-written file behind
+pub(crate) fn write_atomically(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+    let result = std::fs::File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|()| std::fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Runtime view of the server configuration file
+pub struct ServerConfigStore {
+    path: Option<PathBuf>,
+    servers: Mutex<BTreeMap<String, ServerConfig>>,
+}
+
+impl ServerConfigStore {
+    /// Create an in-memory store that is never written to disk
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            servers: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Load the store from a file (created on first save if missing)
+    pub fn load(path: PathBuf) -> Result<Self, MCPClientError> {
+        let file = load_servers_file(&path)?;
+        Ok(Self {
+            path: Some(path),
+            servers: Mutex::new(file.mcp_servers),
+        })
+    }
+
+    /// Path of the backing file, if any
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, BTreeMap<String, ServerConfig>>, MCPClientError> {
+        self.servers
+            .lock()
+            .map_err(|e| MCPClientError::system_error(&format!("Failed to lock server configs: {}", e)))
+    }
+
+    /// Write `servers` to the backing file; callers swap them in only once
+    /// this succeeded, so memory and disk never disagree
+    fn save(&self, servers: &BTreeMap<String, ServerConfig>) -> Result<(), MCPClientError> {
+        if let Some(ref path) = self.path {
+            save_servers_file(
+                path,
+                &ServersFile {
+                    mcp_servers: servers.clone(),
+                },
+            )?;
+        }
+        Ok(())
+    }
+
+    /// List all configured servers
+    pub fn list(&self) -> Result<BTreeMap<String, ServerConfig>, MCPClientError> {
+        Ok(self.lock()?.clone())
+    }
+
+    /// Get a single server definition
+    pub fn get(&self, server_id: &str) -> Result<Option<ServerConfig>, MCPClientError> {
+        Ok(self.lock()?.get(server_id).cloned())
+    }
+
+    /// Servers marked with `autoStart`
+    pub fn auto_start_servers(&self) -> Result<Vec<(String, ServerConfig)>, MCPClientError> {
+        Ok(self
+            .lock()?
+            .iter()
+            .filter(|(_, config)| config.auto_start)
+            .map(|(id, config)| (id.clone(), config.clone()))
+            .collect())
+    }
+
+    /// Add a new server definition and save the file
+    pub fn add(&self, server_id: &str, config: ServerConfig) -> Result<(), MCPClientError> {
+        config.validate_definition(server_id)?;
+        let mut servers = self.lock()?;
+        if servers.contains_key(server_id) {
+            return Err(MCPClientError::configuration_error(
+                server_id,
+                &format!("A server named '{}' is already configured", server_id),
+            ));
+        }
+        let mut updated = servers.clone();
+        updated.insert(server_id.to_string(), config);
+        self.save(&updated)?;
+        *servers = updated;
+        Ok(())
+    }
+
+    /// Replace an existing server definition and save the file
+    pub fn update(&self, server_id: &str, config: ServerConfig) -> Result<(), MCPClientError> {
+        config.validate_definition(server_id)?;
+        let mut servers = self.lock()?;
+        let mut updated = servers.clone();
+        match updated.get_mut(server_id) {
+            Some(existing) => *existing = config,
+            None => {
+                return Err(MCPClientError::configuration_error(
+                    server_id,
+                    &format!("No server named '{}' is configured", server_id),
+                ))
+            }
+        }
+        self.save(&updated)?;
+        *servers = updated;
+        Ok(())
+    }
+
+    /// Remove a server definition and save the file
+    pub fn remove(&self, server_id: &str) -> Result<ServerConfig, MCPClientError> {
+        let mut servers = self.lock()?;
+        let mut updated = servers.clone();
+        let removed = updated.remove(server_id).ok_or_else(|| {
+            MCPClientError::configuration_error(
+                server_id,
+                &format!("No server named '{}' is configured", server_id),
+            )
+        })?;
+        self.save(&updated)?;
+        *servers = updated;
+        Ok(removed)
+    }
+}
@@ -0,0 +1,341 @@
+/// MCP protocol conformance checks against a connected server
+///
+/// Meant for vetting third-party servers before they are enabled: the
+/// runner exercises `initialize`, `ping`, list pagination, `tools/call`
+/// error shapes, cancellation and notification handling, and reports each
+/// check separately instead of stopping at the first failure.
+use crate::error::MCPClientError;
+use crate::registry::ConnectionRegistry;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::time::Instant;
+use tauri::Runtime;
+
+/// Tool name no server is expected to have
+const UNKNOWN_TOOL: &str = "__conformance_unknown_tool__";
+
+fn default_timeout_ms() -> u64 {
+    5_000
+}
+
+fn default_max_pages() -> usize {
+    20
+}
+
+/// Settings of a conformance run
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConformanceOptions {
+    /// Timeout of each request
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Pages followed per list before giving up
+    #[serde(default = "default_max_pages")]
+    pub max_pages: usize,
+}
+
+impl Default for ConformanceOptions {
+    fn default() -> Self {
+        Self {
+            timeout_ms: default_timeout_ms(),
+            max_pages: default_max_pages(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Passed,
+    /// Allowed by the specification but likely to cause problems
+    Warning,
+    Failed,
+    /// Not applicable, e.g. the server has no tools
+    Skipped,
+}
+
+/// Outcome of a single check
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: CheckStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// Structured result of a conformance run
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConformanceReport {
+    pub server_id: String,
+    /// True when no check failed
+    pub passed: bool,
+    pub checks: Vec<CheckResult>,
+    pub duration_ms: u64,
+}
+
+impl ConformanceReport {
+    pub fn check(&self, name: &str) -> Option<&CheckResult> {
+        self.checks.iter().find(|check| check.name == name)
+    }
+
+    pub fn count(&self, status: CheckStatus) -> usize {
+        self.checks.iter().filter(|check| check.status == status).count()
+    }
+}
+
+type Outcome = (CheckStatus, Option<String>);
+
+fn passed() -> Outcome {
+    (CheckStatus::Passed, None)
+}
+
+fn failed(message: impl Into<String>) -> Outcome {
+    (CheckStatus::Failed, Some(message.into()))
+}
+
+fn warning(message: impl Into<String>) -> Outcome {
+    (CheckStatus::Warning, Some(message.into()))
+}
+
+fn skipped(message: impl Into<String>) -> Outcome {
+    (CheckStatus::Skipped, Some(message.into()))
+}
+
+/// Run all checks against a connected server
+pub async fn run_conformance<R: Runtime>(
+    registry: &ConnectionRegistry<R>,
+    server_id: &str,
+    options: &ConformanceOptions,
+) -> ConformanceReport {
+    let start = Instant::now();
+    info_log!(server_id, "Running conformance checks");
+    let runner = Runner { registry, server_id, options };
+    let capabilities = registry
+        .initialize_result(server_id)
+        .and_then(|result| result.get("capabilities").cloned())
+        .unwrap_or(Value::Null);
+
+    let mut checks = Vec::new();
+    macro_rules! check {
+        ($name:expr, $outcome:expr) => {{
+            let check_start = Instant::now();
+            let (status, message) = $outcome;
+            debug_log!(server_id, check = $name, status = ?status, "Conformance check finished");
+            checks.push(CheckResult {
+                name: $name.to_string(),
+                status,
+                message,
+                duration_ms: check_start.elapsed().as_millis() as u64,
+            });
+        }};
+    }
+
+    check!("initialize", runner.check_initialize());
+    check!("ping", runner.check_ping().await);
+    check!("unknown-method", runner.check_unknown_method().await);
+    for (capability, method, field, key) in [
+        ("tools", "tools/list", "tools", "name"),
+        ("resources", "resources/list", "resources", "uri"),
+        ("prompts", "prompts/list", "prompts", "name"),
+    ] {
+        let name = format!("{}-pagination", capability);
+        if capabilities.get(capability).is_none() {
+            check!(name, skipped(format!("Server does not advertise {}", capability)));
+        } else {
+            check!(name, runner.check_pagination(method, field, key).await);
+        }
+    }
+    if capabilities.get("tools").is_none() {
+        check!("tools-call-error", skipped("Server does not advertise tools"));
+    } else {
+        check!("tools-call-error", runner.check_tool_call_error().await);
+    }
+    check!("cancellation", runner.check_cancellation().await);
+    check!("unknown-notification", runner.check_unknown_notification().await);
+
+    let report = ConformanceReport {
+        server_id: server_id.to_string(),
+        passed: checks.iter().all(|check| check.status != CheckStatus::Failed),
+        checks,
+        duration_ms: start.elapsed().as_millis() as u64,
+    };
+    info_log!(
+        server_id,
+        passed = report.passed,
+        failed = report.count(CheckStatus::Failed),
+        warnings = report.count(CheckStatus::Warning),
+        "Conformance checks finished"
+    );
+    report
+}
+
+struct Runner<'a, R: Runtime> {
+    registry: &'a ConnectionRegistry<R>,
+    server_id: &'a str,
+    options: &'a ConformanceOptions,
+}
+
+impl<R: Runtime> Runner<'_, R> {
+    async fn request(&self, method: &str, params: Value) -> Result<Value, MCPClientError> {
+        self.registry
+            .raw_request(self.server_id, method, params, self.options.timeout_ms)
+            .await
+    }
+
+    /// Whether the server still answers after a notification
+    async fn still_responsive(&self, notified: &str) -> Outcome {
+        match self.request("ping", json!({})).await {
+            Ok(_) => passed(),
+            Err(e) => failed(format!("Server stopped responding after {}: {}", notified, e)),
+        }
+    }
+
+    fn check_initialize(&self) -> Outcome {
+        let Some(result) = self.registry.initialize_result(self.server_id) else {
+            return failed("No initialize response was received");
+        };
+        if !result.get("protocolVersion").is_some_and(Value::is_string) {
+            return failed("initialize result has no protocolVersion string");
+        }
+        if !result.get("capabilities").is_some_and(Value::is_object) {
+            return failed("initialize result has no capabilities object");
+        }
+        if !result.pointer("/serverInfo/name").is_some_and(Value::is_string) {
+            return failed("initialize result has no serverInfo.name");
+        }
+        passed()
+    }
+
+    async fn check_ping(&self) -> Outcome {
+        match self.request("ping", json!({})).await {
+            Ok(response) => match response.get("result") {
+                Some(result) if result.is_object() => passed(),
+                Some(result) => failed(format!("ping result is not an object: {}", result)),
+                None => failed(format!("ping was not answered with a result: {}", response)),
+            },
+            Err(e) => failed(format!("ping failed: {}", e)),
+        }
+    }
+
+    async fn check_unknown_method(&self) -> Outcome {
+        let response = match self.request("conformance/unknown-method", json!({})).await {
+            Ok(response) => response,
+            Err(e) => return failed(format!("No response to an unknown method: {}", e)),
+        };
+        let Some(error) = response.get("error") else {
+            return failed("Unknown method was answered with a result instead of an error");
+        };
+        if let Err(problem) = validate_error(error) {
+            return failed(problem);
+        }
+        match error["code"].as_i64() {
+            Some(-32601) => passed(),
+            Some(code) => warning(format!("Unknown method returned code {} instead of -32601", code)),
+            None => unreachable!("validated above"),
+        }
+    }
+
+    async fn check_pagination(&self, method: &str, field: &str, key: &str) -> Outcome {
+        let mut cursor: Option<String> = None;
+        let mut cursors = HashSet::new();
+        let mut seen = HashSet::new();
+        let mut duplicates = Vec::new();
+
+        for page in 1..=self.options.max_pages {
+            let params = match cursor {
+                Some(ref cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let response = match self.request(method, params).await {
+                Ok(response) => response,
+                Err(e) => return failed(format!("{} page {} failed: {}", method, page, e)),
+            };
+            if let Some(error) = response.get("error") {
+                return failed(format!("{} page {} returned an error: {}", method, page, error));
+            }
+            let Some(items) = response.pointer(&format!("/result/{}", field)).and_then(Value::as_array) else {
+                return failed(format!("{} page {} has no {} array", method, page, field));
+            };
+            for item in items {
+                match item.get(key).and_then(Value::as_str) {
+                    Some(id) if !seen.insert(id.to_string()) => duplicates.push(id.to_string()),
+                    Some(_) => {}
+                    None => return failed(format!("{} entry without a {}: {}", method, key, item)),
+                }
+            }
+
+            match response.pointer("/result/nextCursor") {
+                None | Some(Value::Null) => {
+                    return if duplicates.is_empty() {
+                        passed()
+                    } else {
+                        warning(format!("{} listed duplicates: {}", method, duplicates.join(", ")))
+                    };
+                }
+                Some(Value::String(next)) => {
+                    if !cursors.insert(next.clone()) {
+                        return failed(format!("{} returned cursor {:?} twice", method, next));
+                    }
+                    cursor = Some(next.clone());
+                }
+                Some(other) => return failed(format!("{} nextCursor is not a string: {}", method, other)),
+            }
+        }
+        warning(format!("{} still had more pages after {}", method, self.options.max_pages))
+    }
+
+    async fn check_tool_call_error(&self) -> Outcome {
+        let params = json!({ "name": UNKNOWN_TOOL, "arguments": {} });
+        let response = match self.request("tools/call", params).await {
+            Ok(response) => response,
+            Err(e) => return failed(format!("No response to a call of an unknown tool: {}", e)),
+        };
+        if let Some(error) = response.get("error") {
+            return match validate_error(error) {
+                Ok(()) => passed(),
+                Err(problem) => failed(problem),
+            };
+        }
+        match response.get("result") {
+            Some(result) if result.get("isError") == Some(&Value::Bool(true)) => {
+                if result.get("content").is_some_and(Value::is_array) {
+                    passed()
+                } else {
+                    failed("Tool error result has no content array")
+                }
+            }
+            Some(_) => failed("Calling an unknown tool succeeded"),
+            None => failed("tools/call response has neither result nor error"),
+        }
+    }
+
+    async fn check_cancellation(&self) -> Outcome {
+        // Cancelling a request that isn't in flight must be ignored
+        let params = json!({ "requestId": "conformance-cancelled", "reason": "conformance check" });
+        if let Err(e) = self.registry.notify(self.server_id, "notifications/cancelled", params).await {
+            return failed(format!("Failed to send notifications/cancelled: {}", e));
+        }
+        self.still_responsive("notifications/cancelled").await
+    }
+
+    async fn check_unknown_notification(&self) -> Outcome {
+        let method = "notifications/conformance/unknown";
+        if let Err(e) = self.registry.notify(self.server_id, method, json!({})).await {
+            return failed(format!("Failed to send {}: {}", method, e));
+        }
+        self.still_responsive(method).await
+    }
+}
+
+/// Check a JSON-RPC error object has an integer code and a message
+fn validate_error(error: &Value) -> Result<(), String> {
+    if !error.get("code").is_some_and(|code| code.is_i64()) {
+        return Err(format!("Error object has no integer code: {}", error));
+    }
+    if !error.get("message").is_some_and(Value::is_string) {
+        return Err(format!("Error object has no message string: {}", error));
+    }
+    Ok(())
+}
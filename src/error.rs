@@ -1,6 +1,7 @@
 /// Enhanced error handling with specific error types, codes, and categories
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use crate::state::ConnectionState;
 
 /// Error categories for classification
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -133,6 +134,23 @@ impl MCPClientError {
         ])
     }
 
+    /// Create an error for a server answering `initialize` with a protocol version the client doesn't speak
+    pub fn unsupported_protocol_version(version: &str) -> Self {
+        Self::new(
+            ErrorCategory::Protocol,
+            "UNSUPPORTED_PROTOCOL_VERSION",
+            &format!("Server uses unsupported MCP protocol version '{}'", version),
+        )
+        .with_details(&format!(
+            "Supported versions: {}",
+            crate::process::SUPPORTED_PROTOCOL_VERSIONS.join(", ")
+        ))
+        .with_suggestions(vec![
+            "Update the server or the plugin to a common protocol version",
+            "Check the server's documentation for supported protocol versions",
+        ])
+    }
+
     /// Create a configuration error
     pub fn configuration_error(field: &str, details: &str) -> Self {
         Self::new(
@@ -148,6 +166,220 @@ impl MCPClientError {
         ])
     }
 
+    /// Create a secret not found error
+    pub fn secret_not_found(name: &str) -> Self {
+        Self::new(
+            ErrorCategory::Configuration,
+            "SECRET_NOT_FOUND",
+            &format!("Secret '{}' not found", name),
+        )
+        .with_details(&format!(
+            "The secret provider has no value stored under '{}'",
+            name
+        ))
+        .with_suggestions(vec![
+            "Check the secret name in the server configuration",
+            "Verify the secret has been stored in the secret provider",
+        ])
+    }
+
+    /// Create an error for a command outside the configured allow-list
+    pub fn command_not_allowed(command: &str) -> Self {
+        Self::new(
+            ErrorCategory::Permission,
+            "COMMAND_NOT_ALLOWED",
+            &format!("Command '{}' is not allowed", command),
+        )
+        .with_details("The command is not in the plugin's allowed commands")
+        .with_suggestions(vec![
+            "Add the command to spawnPolicy.commands in the plugin configuration",
+            "Use one of the allowed commands to start the server",
+        ])
+    }
+
+    /// Create an error for a server argument outside the spawn policy
+    pub fn argument_not_allowed(argument: &str) -> Self {
+        Self::new(
+            ErrorCategory::Permission,
+            "ARGUMENT_NOT_ALLOWED",
+            &format!("Argument '{}' is not allowed", argument),
+        )
+        .with_details("The argument matches none of the patterns in spawnPolicy.args")
+        .with_suggestions(vec![
+            "Add a matching pattern to spawnPolicy.args in the plugin configuration",
+            "Start the server with allowed arguments only",
+        ])
+    }
+
+    /// Create an error for a server environment variable outside the spawn policy
+    pub fn env_not_allowed(key: &str) -> Self {
+        Self::new(
+            ErrorCategory::Permission,
+            "ENV_NOT_ALLOWED",
+            &format!("Environment variable '{}' is not allowed", key),
+        )
+        .with_details("The variable matches none of the patterns in spawnPolicy.envKeys")
+        .with_suggestions(vec![
+            "Add a matching pattern to spawnPolicy.envKeys in the plugin configuration",
+            "Remove the variable from the server definition",
+        ])
+    }
+
+    /// Create an error for a spawn the caller's capability scope doesn't permit
+    pub fn spawn_denied_by_scope(command: &str) -> Self {
+        Self::new(
+            ErrorCategory::Permission,
+            "SPAWN_NOT_PERMITTED",
+            &format!("Starting '{}' is not permitted for this window", command),
+        )
+        .with_details("The server definition doesn't match the spawn entries of the window's capability scopes")
+        .with_suggestions(vec![
+            "Add an allow entry for the command to the window's capability",
+            "Connect the server from a window with a broader capability",
+        ])
+    }
+
+    /// Create an error for a server, tool or resource outside the caller's
+    /// capability scope
+    pub fn not_permitted(kind: &str, name: &str) -> Self {
+        Self::new(
+            ErrorCategory::Permission,
+            &format!("{}_NOT_PERMITTED", kind.to_uppercase()),
+            &format!("Access to {} '{}' is not permitted for this window", kind, name),
+        )
+        .with_details("The window's capability scope doesn't allow it")
+        .with_suggestions(vec![
+            "Add an allow entry for it to the window's capability",
+            "Check the deny entries of the window's capability",
+        ])
+    }
+
+    pub fn tool_call_rejected(tool_name: &str, remembered: bool) -> Self {
+        let error = Self::new(
+            ErrorCategory::Permission,
+            "TOOL_CALL_REJECTED",
+            &format!("The call of tool '{}' was rejected", tool_name),
+        );
+        if remembered {
+            error
+                .with_details("A remembered decision rejects every call of this tool")
+                .with_suggestion("Forget the remembered decision to be asked again")
+        } else {
+            error.with_details("The user rejected the call")
+        }
+    }
+
+    pub fn approval_timeout(tool_name: &str, timeout_ms: u64) -> Self {
+        Self::new(
+            ErrorCategory::Timeout,
+            "APPROVAL_TIMEOUT",
+            &format!("No decision on the call of tool '{}' within {}ms", tool_name, timeout_ms),
+        )
+        .with_suggestions(vec![
+            "Listen for approval-required events and answer them",
+            "Increase the approval timeout",
+        ])
+    }
+
+    pub fn rate_limited(server_id: &str, limit: &str, retry_after: Option<std::time::Duration>) -> Self {
+        let error = Self::new(
+            ErrorCategory::Permission,
+            "RATE_LIMITED",
+            &format!("Too many tool calls to server {} ({} limit)", server_id, limit),
+        );
+        let error = match retry_after {
+            Some(wait) => error.with_details(&format!("Retry in {}ms", wait.as_millis().max(1))),
+            None => error.with_details("Too many calls are in flight"),
+        };
+        error.with_suggestions(vec![
+            "Wait for running calls to finish before making more",
+            "Raise the server's limits or queue calls instead of rejecting them",
+        ])
+    }
+
+    /// Create an error for a sandbox this system can't set up
+    pub fn sandbox_unavailable(reason: &str) -> Self {
+        Self::new(
+            ErrorCategory::Configuration,
+            "SANDBOX_UNAVAILABLE",
+            "The server's sandbox can't be set up on this system",
+        )
+        .with_details(reason)
+        .with_suggestions(vec![
+            "Install bubblewrap (bwrap) for the isolated profile",
+            "Use the restricted profile, or turn the sandbox off for this server",
+        ])
+    }
+
+    /// Create an error for a sandbox path outside the plugin-wide sandbox
+    pub fn sandbox_path_not_allowed(path: &str) -> Self {
+        Self::new(
+            ErrorCategory::Permission,
+            "SANDBOX_PATH_NOT_ALLOWED",
+            &format!("Sandbox path '{}' is not allowed", path),
+        )
+        .with_details("The path is outside the paths of the plugin-wide sandbox")
+        .with_suggestions(vec![
+            "Add the path, or a parent directory, to the plugin-wide sandbox",
+            "Remove the path from the server's sandbox",
+        ])
+    }
+
+    /// Create an error for a record or replay file outside the session directory
+    pub fn session_path_not_allowed(path: &str, reason: &str) -> Self {
+        Self::new(
+            ErrorCategory::Permission,
+            "SESSION_PATH_NOT_ALLOWED",
+            &format!("Session path '{}' is not allowed", path),
+        )
+        .with_details(reason)
+        .with_suggestions(vec![
+            "Set sessionDir in the plugin configuration or on the Builder",
+            "Use a path inside the session directory",
+        ])
+    }
+
+    pub fn approval_required(tool_name: &str) -> Self {
+        Self::new(
+            ErrorCategory::Permission,
+            "APPROVAL_REQUIRED",
+            &format!("The call of tool '{}' needs the user's approval", tool_name),
+        )
+        .with_details("Blocking calls can't wait for a decision")
+        .with_suggestion("Call the tool through the async API")
+    }
+
+    /// Create an error for exceeding the maximum number of connections
+    pub fn connection_limit_reached(limit: usize) -> Self {
+        Self::new(
+            ErrorCategory::Connection,
+            "CONNECTION_LIMIT_REACHED",
+            "Maximum number of concurrent connections reached",
+        )
+        .with_details(&format!("At most {} servers can be connected at the same time", limit))
+        .with_suggestions(vec![
+            "Disconnect a server before connecting another one",
+            "Raise maxConnections in the plugin configuration",
+        ])
+    }
+
+    /// Create an error for a connection state change the state machine refuses
+    pub fn invalid_state_transition(server_id: &str, from: Option<ConnectionState>, to: ConnectionState) -> Self {
+        Self::new(
+            ErrorCategory::Connection,
+            "INVALID_STATE_TRANSITION",
+            &format!("Server {} can't become {}", server_id, to),
+        )
+        .with_details(&match from {
+            Some(from) => format!("The server is {}", from),
+            None => "The server has never been connected".to_string(),
+        })
+        .with_suggestions(vec![
+            "Wait for the pending connect or disconnect to finish",
+            "Disconnect the server and connect it again",
+        ])
+    }
+
     /// Create a database error
     pub fn database_error(operation: &str, details: &str) -> Self {
         Self::new(
@@ -0,0 +1,113 @@
+/// Handlers for requests sent by servers to the client
+///
+/// MCP servers may ask the client to sample from a model
+/// (`sampling/createMessage`), to collect input from the user
+/// (`elicitation/create`) or for its filesystem roots (`roots/list`).
+/// Those capabilities are only advertised for registered handlers.
+use crate::error::MCPClientError;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const INTERNAL_ERROR: i64 = -32603;
+
+/// First protocol version defining `elicitation/create`
+const ELICITATION_VERSION: &str = "2025-06-18";
+
+/// Answers one kind of server request.
+///
+/// Requests are handled on a few worker threads per server, so handlers may
+/// block, e.g. while asking the user, without holding up the server's
+/// connection.
+pub trait RequestHandler: Send + Sync {
+    /// Return the JSON-RPC `result` for the request `params`
+    fn handle(&self, server_id: &str, params: Value) -> Result<Value, MCPClientError>;
+}
+
+impl<F> RequestHandler for F
+where
+    F: Fn(&str, Value) -> Result<Value, MCPClientError> + Send + Sync,
+{
+    fn handle(&self, server_id: &str, params: Value) -> Result<Value, MCPClientError> {
+        self(server_id, params)
+    }
+}
+
+/// The registered server request handlers
+#[derive(Clone, Default)]
+pub struct ClientHandlers {
+    pub sampling: Option<Arc<dyn RequestHandler>>,
+    pub elicitation: Option<Arc<dyn RequestHandler>>,
+    pub roots: Option<Arc<dyn RequestHandler>>,
+}
+
+impl ClientHandlers {
+    /// Client capabilities sent with `initialize`
+    pub fn capabilities(&self) -> Value {
+        let mut capabilities = serde_json::Map::new();
+        if self.sampling.is_some() {
+            capabilities.insert("sampling".to_string(), json!({}));
+        }
+        if self.elicitation.is_some() {
+            capabilities.insert("elicitation".to_string(), json!({}));
+        }
+        if self.roots.is_some() {
+            capabilities.insert("roots".to_string(), json!({ "listChanged": false }));
+        }
+        Value::Object(capabilities)
+    }
+
+    /// The handlers a server on `protocol_version` may use. Versions are
+    /// dates, so they compare as strings.
+    pub fn for_protocol(&self, protocol_version: &str) -> Self {
+        let mut handlers = self.clone();
+        if protocol_version < ELICITATION_VERSION {
+            handlers.elicitation = None;
+        }
+        handlers
+    }
+
+    fn handler_for(&self, method: &str) -> Option<&Arc<dyn RequestHandler>> {
+        match method {
+            "sampling/createMessage" => self.sampling.as_ref(),
+            "elicitation/create" => self.elicitation.as_ref(),
+            "roots/list" => self.roots.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Build the JSON-RPC response to a request sent by a server
+    pub fn respond(&self, server_id: &str, request: &Value) -> Value {
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let method = request.get("method").and_then(|m| m.as_str()).unwrap_or("");
+        let params = request.get("params").cloned().unwrap_or_else(|| json!({}));
+
+        let outcome = if method == "ping" {
+            Ok(json!({}))
+        } else {
+            match self.handler_for(method) {
+                Some(handler) => handler.handle(server_id, params).map_err(|e| (INTERNAL_ERROR, e.to_string())),
+                None => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+            }
+        };
+
+        match outcome {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, &message),
+        }
+    }
+}
+
+/// Response refusing a server request because too many are already waiting
+pub(crate) fn busy_response(request: &Value) -> Value {
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    error_response(id, INTERNAL_ERROR, "Too many requests in progress, try again later")
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message }
+    })
+}
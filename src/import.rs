@@ -0,0 +1,501 @@
+/// Import MCP server definitions from other hosts' configuration files
+///
+/// Supports Claude Desktop (`claude_desktop_config.json`), Cursor
+/// (`.cursor/mcp.json`) and VS Code (`mcp.json`, or the `mcp` section of
+/// `settings.json`). Placeholders such as `${env:NAME}`, `${input:id}`,
+/// `${workspaceFolder}` and `${userHome}` are substituted, and the result is
+/// normalized into [`ServerConfig`] values usable with `plugin_connect_server`
+/// or the plugin's own configuration file.
+///
+/// `${env:NAME}` is only filled in from [`ImportOptions::env`], never from the
+/// app's own environment. An env value that is a single unresolved `${env:NAME}`
+/// or `${input:id}` placeholder becomes a secret reference, left to the app's
+/// [`SecretProvider`](crate::secrets::SecretProvider) at spawn time.
+use crate::config::{ServerConfig, TransportKind};
+use crate::error::MCPClientError;
+use crate::secrets::EnvValue;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Configuration file formats understood by the importer
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConfigSource {
+    ClaudeDesktop,
+    Cursor,
+    VsCode,
+}
+
+/// Values used to substitute placeholders while importing
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportOptions {
+    /// Values for `${env:NAME}` / `${NAME}`
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Values for VS Code `${input:id}` prompts
+    #[serde(default)]
+    pub inputs: HashMap<String, String>,
+    /// Value for `${workspaceFolder}`
+    #[serde(default)]
+    pub workspace_folder: Option<String>,
+}
+
+/// A server definition normalized from another host's format
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ImportedServer {
+    pub server_id: String,
+    #[serde(flatten)]
+    pub config: ServerConfig,
+    /// Anything that could not be carried over exactly
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+/// An entry that could not be imported, such as one with an unknown type
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SkippedServer {
+    pub server_id: String,
+    /// Why the entry was skipped
+    pub warnings: Vec<String>,
+}
+
+/// VS Code input prompt declared in the `inputs` section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InputPrompt {
+    pub id: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub password: bool,
+}
+
+/// Result of importing a configuration file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportResult {
+    pub source: ConfigSource,
+    pub servers: Vec<ImportedServer>,
+    /// Entries left out; the other servers are still imported
+    #[serde(default)]
+    pub skipped: Vec<SkippedServer>,
+    /// Input prompts declared by the file that had no value in [`ImportOptions::inputs`]
+    pub unresolved_inputs: Vec<InputPrompt>,
+}
+
+/// Guess the format of a parsed configuration file
+pub fn detect_source(value: &serde_json::Value) -> Option<ConfigSource> {
+    if value.get("servers").is_some() || value.pointer("/mcp/servers").is_some() {
+        return Some(ConfigSource::VsCode);
+    }
+    let servers = value.get("mcpServers")?.as_object()?;
+    // Remote `url` entries are only understood by Cursor
+    if servers.values().any(|s| s.get("url").is_some()) {
+        Some(ConfigSource::Cursor)
+    } else {
+        Some(ConfigSource::ClaudeDesktop)
+    }
+}
+
+/// Default location of a host's user-level configuration file
+pub fn default_config_path(source: ConfigSource) -> Option<PathBuf> {
+    let home = home_dir()?;
+    let path = match source {
+        ConfigSource::ClaudeDesktop => {
+            app_support_dir(&home)?.join("Claude").join("claude_desktop_config.json")
+        }
+        ConfigSource::Cursor => home.join(".cursor").join("mcp.json"),
+        ConfigSource::VsCode => app_support_dir(&home)?.join("Code").join("User").join("mcp.json"),
+    };
+    Some(path)
+}
+
+/// Configuration files `plugin_import_servers` may read: the user-level files
+/// of every supported host, and VS Code's `settings.json`
+pub fn known_config_paths() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = [ConfigSource::ClaudeDesktop, ConfigSource::Cursor, ConfigSource::VsCode]
+        .into_iter()
+        .filter_map(default_config_path)
+        .collect();
+    if let Some(app_support) = home_dir().and_then(|home| app_support_dir(&home)) {
+        paths.push(app_support.join("Code").join("User").join("settings.json"));
+    }
+    paths
+}
+
+/// Whether `path` is one of [`known_config_paths`]
+pub fn is_known_config_path(path: &Path) -> bool {
+    let canonical = |path: &Path| std::fs::canonicalize(path).ok();
+    let Some(path) = canonical(path) else {
+        return false;
+    };
+    known_config_paths()
+        .iter()
+        .any(|known| canonical(known).as_deref() == Some(path.as_path()))
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+fn app_support_dir(home: &Path) -> Option<PathBuf> {
+    if cfg!(target_os = "macos") {
+        Some(home.join("Library").join("Application Support"))
+    } else if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        Some(
+            std::env::var_os("XDG_CONFIG_HOME")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| home.join(".config")),
+        )
+    }
+}
+
+/// Read and import a configuration file, detecting its format when `source` is `None`
+pub fn import_file(
+    path: &Path,
+    source: Option<ConfigSource>,
+    options: &ImportOptions,
+) -> Result<ImportResult, MCPClientError> {
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        MCPClientError::configuration_error(
+            "import file",
+            &format!("Failed to read {}: {}", path.display(), e),
+        )
+    })?;
+    import_servers(&contents, source, options)
+}
+
+/// Import server definitions from configuration file contents
+pub fn import_servers(
+    contents: &str,
+    source: Option<ConfigSource>,
+    options: &ImportOptions,
+) -> Result<ImportResult, MCPClientError> {
+    let value: serde_json::Value = serde_json::from_str(&strip_jsonc(contents))
+        .map_err(|e| MCPClientError::configuration_error("import file", &e.to_string()))?;
+
+    let source = match source.or_else(|| detect_source(&value)) {
+        Some(source) => source,
+        None => {
+            return Err(MCPClientError::configuration_error(
+                "import file",
+                "No `mcpServers` or `servers` section found",
+            ))
+        }
+    };
+
+    let (servers, inputs) = match source {
+        ConfigSource::ClaudeDesktop | ConfigSource::Cursor => (value.get("mcpServers"), None),
+        ConfigSource::VsCode => {
+            // Either a dedicated mcp.json or the `mcp` section of settings.json
+            let root = value.get("mcp").unwrap_or(&value);
+            (root.get("servers"), root.get("inputs"))
+        }
+    };
+
+    let servers = servers.and_then(|s| s.as_object()).ok_or_else(|| {
+        MCPClientError::configuration_error("import file", "Server section is missing or not an object")
+    })?;
+
+    let prompts: Vec<InputPrompt> = inputs
+        .and_then(|i| serde_json::from_value(i.clone()).ok())
+        .unwrap_or_default();
+
+    let resolver = PlaceholderResolver { source, options };
+    let mut imported = Vec::new();
+    let mut skipped = Vec::new();
+    for (server_id, entry) in servers {
+        match resolver.import_entry(server_id, entry) {
+            Ok(server) => imported.push(server),
+            Err(e) => skipped.push(SkippedServer {
+                server_id: server_id.clone(),
+                warnings: vec![format!("Not imported: {}", e.details.as_deref().unwrap_or(&e.message))],
+            }),
+        }
+    }
+
+    let unresolved_inputs = prompts
+        .into_iter()
+        .filter(|p| !options.inputs.contains_key(&p.id))
+        .collect();
+
+    Ok(ImportResult {
+        source,
+        servers: imported,
+        skipped,
+        unresolved_inputs,
+    })
+}
+
+/// Remove `//` and `/* */` comments and trailing commas, which VS Code allows
+fn strip_jsonc(contents: &str) -> String {
+    // Comments first, so a comment between a comma and a closing brace doesn't hide it
+    let without_comments = scan_jsonc(contents, |c, chars, out| match c {
+        '/' if chars.peek() == Some(&'/') => {
+            for c in chars.by_ref() {
+                if c == '\n' {
+                    out.push('\n');
+                    break;
+                }
+            }
+        }
+        '/' if chars.peek() == Some(&'*') => {
+            chars.next();
+            let mut prev = ' ';
+            for c in chars.by_ref() {
+                if prev == '*' && c == '/' {
+                    break;
+                }
+                prev = c;
+            }
+        }
+        _ => out.push(c),
+    });
+
+    scan_jsonc(&without_comments, |c, chars, out| {
+        // Drop the comma if the next significant character closes a container
+        let next = chars.clone().find(|c| !c.is_whitespace());
+        if c != ',' || (next != Some('}') && next != Some(']')) {
+            out.push(c);
+        }
+    })
+}
+
+/// Copy string literals verbatim and hand every other character to `handle`
+fn scan_jsonc<F>(contents: &str, mut handle: F) -> String
+where
+    F: FnMut(char, &mut std::iter::Peekable<std::str::Chars<'_>>, &mut String),
+{
+    let mut out = String::with_capacity(contents.len());
+    let mut chars = contents.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            if c == '\\' {
+                if let Some(next) = chars.next() {
+                    out.push(next);
+                }
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '"' {
+            in_string = true;
+            out.push(c);
+        } else {
+            handle(c, &mut chars, &mut out);
+        }
+    }
+    out
+}
+
+struct PlaceholderResolver<'a> {
+    source: ConfigSource,
+    options: &'a ImportOptions,
+}
+
+/// Outcome of substituting placeholders in one string
+enum Substitution {
+    Resolved(String),
+    /// The whole value is a single unresolved `${input:id}` or `${env:NAME}`
+    /// placeholder; holds the input id or variable name
+    Reference(String),
+    Partial(String, Vec<String>),
+}
+
+impl PlaceholderResolver<'_> {
+    fn import_entry(&self, server_id: &str, entry: &serde_json::Value) -> Result<ImportedServer, MCPClientError> {
+        let field = |name: &str| entry.get(name);
+        let mut warnings = Vec::new();
+
+        let url = field("url").and_then(|u| u.as_str()).map(|u| self.text(u, &mut warnings));
+        let transport = match field("type").and_then(|t| t.as_str()) {
+            Some("sse") => TransportKind::Sse,
+            Some("http") | Some("streamable-http") => TransportKind::Http,
+            Some("stdio") => TransportKind::Stdio,
+            Some(other) => {
+                return Err(MCPClientError::configuration_error(
+                    &format!("{}.type", server_id),
+                    &format!("Unknown server type '{}'", other),
+                ))
+            }
+            None => match url {
+                Some(ref url) if url.trim_end_matches('/').ends_with("/sse") => TransportKind::Sse,
+                Some(_) => TransportKind::Http,
+                None => TransportKind::Stdio,
+            },
+        };
+
+        let command = field("command")
+            .and_then(|c| c.as_str())
+            .map(|c| self.text(c, &mut warnings))
+            .unwrap_or_default();
+        if transport == TransportKind::Stdio && command.is_empty() {
+            return Err(MCPClientError::configuration_error(
+                &format!("{}.command", server_id),
+                "A command is required for stdio servers",
+            ));
+        }
+
+        let args = field("args")
+            .and_then(|a| a.as_array())
+            .map(|args| {
+                args.iter()
+                    .map(|a| match a.as_str() {
+                        Some(s) => self.text(s, &mut warnings),
+                        None => a.to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut env = HashMap::new();
+        if let Some(vars) = field("env").and_then(|e| e.as_object()) {
+            for (key, value) in vars {
+                let raw = match value.as_str() {
+                    Some(s) => s.to_string(),
+                    None => value.to_string(),
+                };
+                let value = match self.substitute(&raw) {
+                    Substitution::Resolved(value) => EnvValue::Plain(value),
+                    // Leave it to the app's secret provider
+                    Substitution::Reference(name) => {
+                        warnings.push(format!("{} references '{}', imported as a secret reference", key, name));
+                        EnvValue::Secret { secret: name }
+                    }
+                    Substitution::Partial(value, missing) => {
+                        warnings.push(format!("{} has unresolved placeholders: {}", key, missing.join(", ")));
+                        EnvValue::Plain(value)
+                    }
+                };
+                env.insert(key.clone(), value);
+            }
+        }
+
+        let cwd = field("cwd").and_then(|c| c.as_str()).map(|c| self.text(c, &mut warnings));
+
+        if field("envFile").is_some() {
+            warnings.push("envFile is not supported; copy its variables into env".to_string());
+        }
+        if field("headers").is_some() {
+            warnings.push("HTTP headers are not supported and were dropped".to_string());
+        }
+        if transport != TransportKind::Stdio {
+            warnings.push(format!("{:?} transport servers can be saved but not connected yet", transport));
+        }
+        if field("disabled").and_then(|d| d.as_bool()) == Some(true) {
+            warnings.push("Server is disabled in the source configuration".to_string());
+        }
+
+        Ok(ImportedServer {
+            server_id: server_id.to_string(),
+            config: ServerConfig {
+                command,
+                args,
+                env,
+                cwd,
+                transport,
+                url,
+                ..Default::default()
+            },
+            warnings,
+        })
+    }
+
+    /// Substitute placeholders in a non-env field, recording any that remain
+    fn text(&self, raw: &str, warnings: &mut Vec<String>) -> String {
+        match self.substitute(raw) {
+            Substitution::Resolved(value) => value,
+            Substitution::Reference(_) => {
+                warnings.push(format!("Unresolved placeholder: {}", raw));
+                raw.to_string()
+            }
+            Substitution::Partial(value, missing) => {
+                warnings.push(format!("Unresolved placeholders: {}", missing.join(", ")));
+                value
+            }
+        }
+    }
+
+    fn substitute(&self, raw: &str) -> Substitution {
+        let mut out = String::new();
+        let mut missing = Vec::new();
+        let mut rest = raw;
+
+        while let Some(start) = rest.find("${") {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let Some(end) = after.find('}') else {
+                out.push_str(&rest[start..]);
+                rest = "";
+                break;
+            };
+            let name = &after[..end];
+            let placeholder = &rest[start..start + end + 3];
+
+            if let Some(id) = name.strip_prefix("input:") {
+                match self.options.inputs.get(id) {
+                    Some(value) => out.push_str(value),
+                    None if placeholder == raw => return Substitution::Reference(id.to_string()),
+                    None => {
+                        missing.push(placeholder.to_string());
+                        out.push_str(placeholder);
+                    }
+                }
+            } else {
+                match self.lookup(name) {
+                    Some(value) => out.push_str(&value),
+                    None => match self.env_var(name) {
+                        Some(var) if placeholder == raw => return Substitution::Reference(var.to_string()),
+                        _ => {
+                            missing.push(placeholder.to_string());
+                            out.push_str(placeholder);
+                        }
+                    },
+                }
+            }
+            rest = &after[end + 1..];
+        }
+        out.push_str(rest);
+
+        if missing.is_empty() {
+            Substitution::Resolved(out)
+        } else {
+            Substitution::Partial(out, missing)
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<String> {
+        match name {
+            "workspaceFolder" => return self.options.workspace_folder.clone(),
+            "userHome" => return home_dir().map(|h| h.to_string_lossy().to_string()),
+            "pathSeparator" | "/" => return Some(std::path::MAIN_SEPARATOR.to_string()),
+            _ => {}
+        }
+
+        // `${env:NAME}` everywhere, bare `${NAME}` / `${NAME:-default}` outside VS Code
+        let (var, default) = match self.env_var(name) {
+            Some(var) => (var, None),
+            None if self.source == ConfigSource::VsCode => return None,
+            None => name.split_once(":-").map(|(var, default)| (var, Some(default)))?,
+        };
+        self.options
+            .env
+            .get(var)
+            .cloned()
+            .or_else(|| default.map(|d| d.to_string()))
+    }
+
+    /// Variable named by `${env:NAME}`, or by a bare `${NAME}` outside VS Code
+    fn env_var<'n>(&self, name: &'n str) -> Option<&'n str> {
+        match name.strip_prefix("env:") {
+            Some(var) => Some(var),
+            None if self.source == ConfigSource::VsCode || name.contains(":-") => None,
+            None => Some(name),
+        }
+    }
+}
@@ -1,33 +1,392 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 use tauri::{
-    plugin::{Builder, TauriPlugin},
-    Manager, Runtime,
+    plugin::{Builder as PluginBuilder, TauriPlugin},
+    AppHandle, Manager, RunEvent, Runtime,
 };
 
+#[macro_use]
+pub mod logging;
+pub mod approval;
+pub mod audit;
+pub mod client;
 pub mod commands;
+pub mod config;
+pub mod conformance;
+pub mod handlers;
+pub mod import;
+pub mod limits;
+pub mod liveness;
+pub mod policy;
 pub mod registry;
+pub mod replay;
 pub mod process;
 pub mod error;
+pub mod resources;
+pub mod restart;
+pub mod sandbox;
+pub mod scope;
+pub mod secrets;
+pub mod settings;
+pub mod shutdown;
+pub mod state;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod traffic;
 
+use approval::ApprovalPolicy;
+use audit::{AuditConfig, AuditFile};
+pub use client::{McpClient, McpExt};
+use config::ServerConfigStore;
+use handlers::{ClientHandlers, RequestHandler};
+use logging::LogLevel;
+use policy::SpawnPolicy;
 use registry::ConnectionRegistry;
+use restart::RestartPolicy;
+use sandbox::SandboxConfig;
+use secrets::SecretProvider;
+use settings::PluginConfig;
+
+/// How long app exit waits for servers to shut down by default
+pub const DEFAULT_EXIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Builder for configuring the MCP plugin.
+///
+/// Settings start from the optional `plugins.mcp` section of
+/// `tauri.conf.json`; anything set on the builder takes precedence.
+#[derive(Default)]
+pub struct Builder {
+    secret_provider: Option<Arc<dyn SecretProvider>>,
+    config_file: Option<PathBuf>,
+    session_dir: Option<PathBuf>,
+    exit_timeout: Option<Duration>,
+    request_timeout: Option<Duration>,
+    tool_timeout: Option<Duration>,
+    max_connections: Option<usize>,
+    spawn_policy: Option<SpawnPolicy>,
+    approval_policy: Option<ApprovalPolicy>,
+    audit: Option<AuditConfig>,
+    sandbox: Option<SandboxConfig>,
+    restart_policy: Option<RestartPolicy>,
+    event_prefix: Option<String>,
+    log_level: Option<LogLevel>,
+    log_payloads: Option<bool>,
+    handlers: ClientHandlers,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolve `{ "secret": "name" }` environment references through this provider
+    pub fn secret_provider<P: SecretProvider + 'static>(mut self, provider: P) -> Self {
+        self.secret_provider = Some(Arc::new(provider));
+        self
+    }
+
+    /// Server configuration file (`.json` or `.toml`); relative paths are
+    /// resolved against the app config dir. Defaults to `mcp.json` there.
+    pub fn config_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_file = Some(path.into());
+        self
+    }
+
+    /// Directory traffic recordings are written to and replay sessions read
+    /// from; relative paths are resolved against the app data dir. Recording
+    /// to a file and the replay transport are refused until it is set.
+    pub fn session_dir(mut self, path: impl Into<PathBuf>) -> Self {
+        self.session_dir = Some(path.into());
+        self
+    }
+
+    /// How long app exit is blocked while servers shut down gracefully
+    /// (default 5s). `Duration::ZERO` kills servers right away.
+    pub fn exit_timeout(mut self, timeout: Duration) -> Self {
+        self.exit_timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout for `initialize`, listing and other requests (default 5s)
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout for tool calls (default 10s)
+    pub fn tool_timeout(mut self, timeout: Duration) -> Self {
+        self.tool_timeout = Some(timeout);
+        self
+    }
+
+    /// Maximum number of servers connected at the same time
+    pub fn max_connections(mut self, max: usize) -> Self {
+        self.max_connections = Some(max);
+        self
+    }
+
+    /// Limit the commands, arguments and environment variables servers may
+    /// be spawned with. Blocked spawns fail with a `Permission` error and
+    /// are recorded in the registry's audit log.
+    pub fn spawn_policy(mut self, policy: SpawnPolicy) -> Self {
+        self.spawn_policy = Some(policy);
+        self
+    }
+
+    /// Hold tool calls until the user approves them. Pending calls emit
+    /// `mcp://approval-required` and wait for `plugin_respond_approval`.
+    pub fn approval_policy(mut self, policy: ApprovalPolicy) -> Self {
+        self.approval_policy = Some(policy);
+        self
+    }
+
+    /// Where and how spawns and tool calls are audited. By default they
+    /// are appended to `mcp-audit.jsonl` in the app data dir, with argument
+    /// hashes only.
+    pub fn audit(mut self, config: AuditConfig) -> Self {
+        self.audit = Some(config);
+        self
+    }
+
+    /// Sandbox for servers that don't configure their own. Servers that do
+    /// can only tighten it.
+    pub fn sandbox(mut self, config: SandboxConfig) -> Self {
+        self.sandbox = Some(config);
+        self
+    }
+
+    /// Restart policy for servers that don't configure their own
+    pub fn restart_policy(mut self, policy: RestartPolicy) -> Self {
+        self.restart_policy = Some(policy);
+        self
+    }
+
+    /// Prefix of emitted event names (default `mcp`, giving `mcp://server-connected`)
+    pub fn event_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.event_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Most verbose level the plugin emits through `tracing` (default `LogLevel::Debug`).
+    /// The app's subscriber filters further.
+    pub fn log_level(mut self, level: LogLevel) -> Self {
+        self.log_level = Some(level);
+        self
+    }
+
+    /// Include message payloads in debug events (off by default, as they
+    /// can contain user data)
+    pub fn log_payloads(mut self, enabled: bool) -> Self {
+        self.log_payloads = Some(enabled);
+        self
+    }
+
+    /// Answer `sampling/createMessage` requests from servers
+    pub fn sampling_handler<H: RequestHandler + 'static>(mut self, handler: H) -> Self {
+        self.handlers.sampling = Some(Arc::new(handler));
+        self
+    }
+
+    /// Answer `elicitation/create` requests from servers
+    pub fn elicitation_handler<H: RequestHandler + 'static>(mut self, handler: H) -> Self {
+        self.handlers.elicitation = Some(Arc::new(handler));
+        self
+    }
+
+    /// Answer `roots/list` requests from servers
+    pub fn roots_handler<H: RequestHandler + 'static>(mut self, handler: H) -> Self {
+        self.handlers.roots = Some(Arc::new(handler));
+        self
+    }
+
+    /// Apply the builder settings on top of the `tauri.conf.json` ones
+    pub fn resolve_config(&self, base: Option<PluginConfig>) -> PluginConfig {
+        let mut config = base.unwrap_or_default();
+        if let Some(ref path) = self.config_file {
+            config.config_file = Some(path.clone());
+        }
+        if let Some(ref path) = self.session_dir {
+            config.session_dir = Some(path.clone());
+        }
+        if let Some(timeout) = self.exit_timeout {
+            config.exit_timeout_ms = timeout.as_millis() as u64;
+        }
+        if let Some(timeout) = self.request_timeout {
+            config.request_timeout_ms = timeout.as_millis() as u64;
+        }
+        if let Some(timeout) = self.tool_timeout {
+            config.tool_timeout_ms = timeout.as_millis() as u64;
+        }
+        if let Some(max) = self.max_connections {
+            config.max_connections = Some(max);
+        }
+        if let Some(ref policy) = self.spawn_policy {
+            config.spawn_policy = policy.clone();
+        }
+        if let Some(ref policy) = self.approval_policy {
+            config.approval = policy.clone();
+        }
+        if let Some(ref audit) = self.audit {
+            config.audit = audit.clone();
+        }
+        if let Some(ref sandbox) = self.sandbox {
+            config.sandbox = sandbox.clone();
+        }
+        if let Some(ref policy) = self.restart_policy {
+            config.restart = policy.clone();
+        }
+        if let Some(ref prefix) = self.event_prefix {
+            config.event_prefix = prefix.clone();
+        }
+        if let Some(level) = self.log_level {
+            config.log_level = level;
+        }
+        if let Some(enabled) = self.log_payloads {
+            config.log_payloads = enabled;
+        }
+        config
+    }
+
+    /// Build the configured plugin
+    pub fn build<R: Runtime>(self) -> TauriPlugin<R, Option<PluginConfig>> {
+        PluginBuilder::<R, Option<PluginConfig>>::new("mcp")
+            .setup(move |app, api| {
+                let mut settings = self.resolve_config(api.config().clone());
+                settings.limits.validate("limits").map_err(|e| e.to_string())?;
+                settings.session_dir = settings.session_dir.and_then(|dir| resolve_data_path(app, dir));
+                logging::set_level(settings.log_level);
+                logging::set_log_payloads(settings.log_payloads);
+                let config_file = settings.config_file.clone();
+                let audit = settings.audit.clone();
+
+                // Initialize connection registry
+                let mut registry = ConnectionRegistry::new();
+                registry.set_app_handle(app.app_handle().clone());
+                if let Some(provider) = self.secret_provider {
+                    registry.set_secret_provider(provider);
+                }
+                registry.set_settings(settings);
+                registry.set_handlers(self.handlers);
+                match app.path().app_data_dir() {
+                    Ok(dir) => {
+                        if let Err(e) = registry.approvals().set_store(dir.join(approval::APPROVALS_FILE_NAME)) {
+                            warn_log!(error = %e, "Failed to load remembered approval decisions");
+                        }
+                        if audit.enabled {
+                            let file = audit
+                                .file
+                                .unwrap_or_else(|| PathBuf::from(audit::DEFAULT_AUDIT_FILE_NAME));
+                            registry.audit_log().set_file(AuditFile::new(
+                                dir.join(file),
+                                audit.max_file_bytes,
+                                audit.max_files,
+                            ));
+                        }
+                    }
+                    Err(e) => warn_log!(error = %e, "No app data dir available, approvals and audit entries won't persist"),
+                }
+                registry::install_shared_registry(registry.clone());
+                app.manage(registry);
+
+                // Load declarative server configuration
+                let store = match resolve_config_path(app, config_file) {
+                    Some(path) => ServerConfigStore::load(path).unwrap_or_else(|e| {
+                        // Keep the broken file untouched rather than overwriting it
+                        error_log!(error = %e, "Failed to load MCP server config");
+                        ServerConfigStore::in_memory()
+                    }),
+                    None => ServerConfigStore::in_memory(),
+                };
+                let auto_start = store.auto_start_servers().unwrap_or_default();
+                app.manage(store);
+
+                if !auto_start.is_empty() {
+                    let app_handle = app.app_handle().clone();
+                    tauri::async_runtime::spawn(async move {
+                        let registry = app_handle.state::<ConnectionRegistry<R>>();
+                        // No window is involved, so only the spawn policy applies.
+                        // Servers saved from a window were checked against its
+                        // scopes when saved with autoStart.
+                        for (server_id, config) in auto_start {
+                            if let Err(e) = registry.connect_server_with_config(server_id.clone(), config).await {
+                                warn_log!(server_id = %server_id, error = %e, "Auto-connect failed");
+                            }
+                        }
+                    });
+                }
+
+                info_log!("MCP plugin initialized");
+                Ok(())
+            })
+            .on_event(move |app, event| {
+                // ExitRequested can still be prevented, so only shut down on Exit
+                if let RunEvent::Exit = event {
+                    if let Some(registry) = app.try_state::<ConnectionRegistry<R>>() {
+                        let exit_timeout = registry.settings().exit_timeout();
+                        registry.disconnect_all_blocking(exit_timeout, "Application exiting");
+                    }
+                }
+            })
+            .invoke_handler(tauri::generate_handler![
+                commands::health_check,
+                commands::get_connection_statuses,
+                commands::plugin_connect_server,
+                commands::plugin_disconnect_server,
+                commands::plugin_list_tools,
+                commands::plugin_execute_tool,
+                commands::plugin_list_pending_approvals,
+                commands::plugin_respond_approval,
+                commands::plugin_forget_approval,
+                commands::plugin_query_audit_log,
+                commands::plugin_list_resources,
+                commands::plugin_read_resource,
+                commands::plugin_list_server_configs,
+                commands::plugin_add_server_config,
+                commands::plugin_update_server_config,
+                commands::plugin_remove_server_config,
+                commands::plugin_import_servers,
+                commands::plugin_get_traffic,
+                commands::plugin_export_traffic,
+                commands::plugin_clear_traffic,
+                commands::plugin_run_conformance
+            ])
+            .build()
+    }
+}
+
+/// Resolve the configuration file location against the app config dir
+fn resolve_config_path<R: Runtime>(app: &AppHandle<R>, configured: Option<PathBuf>) -> Option<PathBuf> {
+    match configured {
+        Some(path) if path.is_absolute() => Some(path),
+        configured => {
+            let file = configured.unwrap_or_else(|| PathBuf::from(config::DEFAULT_CONFIG_FILE_NAME));
+            match app.path().app_config_dir() {
+                Ok(dir) => Some(dir.join(file)),
+                Err(e) => {
+                    warn_log!(error = %e, "No app config dir available for MCP server config");
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// Resolve a path against the app data dir
+fn resolve_data_path<R: Runtime>(app: &AppHandle<R>, path: PathBuf) -> Option<PathBuf> {
+    if path.is_absolute() {
+        return Some(path);
+    }
+    match app.path().app_data_dir() {
+        Ok(dir) => Some(dir.join(path)),
+        Err(e) => {
+            warn_log!(error = %e, "No app data dir available for MCP sessions");
+            None
+        }
+    }
+}
 
 /// Initialize the MCP plugin
-pub fn init<R: Runtime>() -> TauriPlugin<R> {
-    Builder::new("mcp")
-        .setup(|app, _api| {
-            // Initialize connection registry
-            let mut registry = ConnectionRegistry::new();
-            registry.set_app_handle(app.app_handle().clone());
-            app.manage(registry);
-            println!("MCP plugin initialized with connection registry and event system");
-            Ok(())
-        })
-        .invoke_handler(tauri::generate_handler![
-            commands::health_check,
-            commands::get_connection_statuses,
-            commands::plugin_connect_server,
-            commands::plugin_disconnect_server,
-            commands::plugin_list_tools,
-            commands::plugin_execute_tool
-        ])
-        .build()
-}
\ No newline at end of file
+pub fn init<R: Runtime>() -> TauriPlugin<R, Option<PluginConfig>> {
+    Builder::new().build()
+}
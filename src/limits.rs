@@ -0,0 +1,484 @@
+/// Rate limits and concurrency quotas for tool calls
+///
+/// A runaway frontend loop can hammer a server with tool calls. Limits cap
+/// the calls per second (a token bucket) and the calls in flight, for a
+/// whole server and for single tools:
+///
+/// ```json
+/// "limits": {
+///   "ratePerSec": 5,
+///   "burst": 10,
+///   "maxConcurrent": 2,
+///   "tools": { "search_*": { "ratePerSec": 1 } },
+///   "mode": "queue",
+///   "queueTimeoutMs": 10000
+/// }
+/// ```
+///
+/// Limits can be set plugin-wide and per server. Both apply, each with its
+/// own buckets, so a server definition coming from the frontend can only
+/// tighten the plugin-wide limits. Over a limit, a call either fails with
+/// `RATE_LIMITED` or waits for its turn, up to `queueTimeoutMs`.
+use crate::error::MCPClientError;
+use crate::policy::glob_match;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+fn default_queue_timeout_ms() -> u64 {
+    30_000
+}
+
+/// What happens to a call over a limit
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum LimitMode {
+    /// Fail right away with `RATE_LIMITED`
+    #[default]
+    Reject,
+    /// Wait until the call fits, up to `queueTimeoutMs`
+    Queue,
+}
+
+/// Rate and concurrency limit; unset fields don't limit
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Limit {
+    /// Calls per second, refilled continuously
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_per_sec: Option<f64>,
+    /// Calls that can be made at once after a quiet period (default: the
+    /// rate, at least 1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub burst: Option<u32>,
+    /// Calls in flight at the same time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent: Option<u32>,
+}
+
+impl Limit {
+    pub fn rate(rate_per_sec: f64) -> Self {
+        Self {
+            rate_per_sec: Some(rate_per_sec),
+            ..Self::default()
+        }
+    }
+
+    pub fn concurrent(max_concurrent: u32) -> Self {
+        Self {
+            max_concurrent: Some(max_concurrent),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = Some(burst);
+        self
+    }
+
+    pub fn with_max_concurrent(mut self, max_concurrent: u32) -> Self {
+        self.max_concurrent = Some(max_concurrent);
+        self
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.rate_per_sec.is_none() && self.max_concurrent.is_none()
+    }
+
+    /// Reject rates that can't refill a bucket
+    pub fn validate(&self, field: &str) -> Result<(), MCPClientError> {
+        match self.rate_per_sec {
+            Some(rate) if !(rate.is_finite() && rate > 0.0) => Err(MCPClientError::configuration_error(
+                &format!("{}.ratePerSec", field),
+                &format!("Rate must be a positive number, got {}", rate),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn capacity(&self) -> Option<f64> {
+        let rate = self.rate_per_sec.filter(|rate| *rate > 0.0)?;
+        Some(self.burst.map(f64::from).unwrap_or(rate.ceil()).max(1.0))
+    }
+}
+
+/// Limits for a server and its tools
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimits {
+    /// Limit shared by all tool calls of the server
+    #[serde(flatten)]
+    pub server: Limit,
+    /// Limits of single tools, by name or glob. A tool uses the first
+    /// matching entry, in name order.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tools: BTreeMap<String, Limit>,
+    #[serde(default)]
+    pub mode: LimitMode,
+    /// How long a queued call waits before it fails with `RATE_LIMITED`
+    #[serde(default = "default_queue_timeout_ms")]
+    pub queue_timeout_ms: u64,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            server: Limit::default(),
+            tools: BTreeMap::new(),
+            mode: LimitMode::Reject,
+            queue_timeout_ms: default_queue_timeout_ms(),
+        }
+    }
+}
+
+impl RateLimits {
+    /// Limits for the whole server
+    pub fn server(limit: Limit) -> Self {
+        Self {
+            server: limit,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_tool(mut self, pattern: &str, limit: Limit) -> Self {
+        self.tools.insert(pattern.to_string(), limit);
+        self
+    }
+
+    /// Queue calls over a limit instead of rejecting them
+    pub fn queued(mut self, queue_timeout: Duration) -> Self {
+        self.mode = LimitMode::Queue;
+        self.queue_timeout_ms = queue_timeout.as_millis() as u64;
+        self
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.server.is_unlimited() && self.tools.values().all(Limit::is_unlimited)
+    }
+
+    /// Check every limit, naming the broken one under `field`
+    pub fn validate(&self, field: &str) -> Result<(), MCPClientError> {
+        self.server.validate(field)?;
+        for (pattern, limit) in &self.tools {
+            limit.validate(&format!("{}.tools.{}", field, pattern))?;
+        }
+        Ok(())
+    }
+
+    fn tool_limit(&self, tool_name: &str) -> Option<(&String, &Limit)> {
+        self.tools
+            .iter()
+            .find(|(pattern, _)| glob_match(pattern, tool_name))
+    }
+}
+
+/// Current use of one limit, as reported in the connection status
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct LimitUsage {
+    /// Calls running now
+    pub in_flight: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent: Option<u32>,
+    /// Calls that could start right away under the rate limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens_available: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_per_sec: Option<f64>,
+    /// Calls let through since the server connected
+    pub calls: u64,
+    /// Calls rejected with `RATE_LIMITED`
+    pub limited: u64,
+    /// Calls waiting in the queue
+    pub queued: u32,
+}
+
+/// Usage of a server's limits
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RateLimitUsage {
+    #[serde(flatten)]
+    pub server: LimitUsage,
+    /// Usage of the tool limits, by pattern
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tools: BTreeMap<String, LimitUsage>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token bucket and concurrency slots of one limit
+struct Gate {
+    limit: Limit,
+    bucket: Option<Mutex<Bucket>>,
+    slots: Option<Arc<Semaphore>>,
+    in_flight: AtomicU32,
+    queued: AtomicU32,
+    calls: AtomicU64,
+    limited: AtomicU64,
+}
+
+impl Gate {
+    fn new(limit: Limit) -> Arc<Self> {
+        Arc::new(Self {
+            bucket: limit.capacity().map(|capacity| {
+                Mutex::new(Bucket {
+                    tokens: capacity,
+                    updated: Instant::now(),
+                })
+            }),
+            slots: limit
+                .max_concurrent
+                .map(|max| Arc::new(Semaphore::new(max as usize))),
+            limit,
+            in_flight: AtomicU32::new(0),
+            queued: AtomicU32::new(0),
+            calls: AtomicU64::new(0),
+            limited: AtomicU64::new(0),
+        })
+    }
+
+    /// Add the tokens earned since the last update
+    fn refill(&self, bucket: &mut Bucket) {
+        let (Some(rate), Some(capacity)) = (self.limit.rate_per_sec, self.limit.capacity()) else {
+            return;
+        };
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.updated = now;
+    }
+
+    fn usage(&self) -> LimitUsage {
+        let tokens_available = self.bucket.as_ref().map(|bucket| {
+            let mut bucket = bucket.lock().unwrap_or_else(|e| e.into_inner());
+            self.refill(&mut bucket);
+            bucket.tokens.floor()
+        });
+        LimitUsage {
+            in_flight: self.in_flight.load(Ordering::SeqCst),
+            max_concurrent: self.limit.max_concurrent,
+            tokens_available,
+            rate_per_sec: self.limit.rate_per_sec,
+            calls: self.calls.load(Ordering::SeqCst),
+            limited: self.limited.load(Ordering::SeqCst),
+            queued: self.queued.load(Ordering::SeqCst),
+        }
+    }
+}
+
+/// Take a token from every gate, or from none of them. Otherwise returns
+/// the first gate without a token and how long until it has one.
+fn take_tokens(gates: &[(String, Arc<Gate>)]) -> Result<(), (usize, Duration)> {
+    let mut buckets = Vec::new();
+    for (index, (_, gate)) in gates.iter().enumerate() {
+        let (Some(bucket), Some(rate)) = (gate.bucket.as_ref(), gate.limit.rate_per_sec) else {
+            continue;
+        };
+        let mut bucket = bucket.lock().unwrap_or_else(|e| e.into_inner());
+        gate.refill(&mut bucket);
+        if bucket.tokens < 1.0 {
+            return Err((index, Duration::from_secs_f64((1.0 - bucket.tokens) / rate)));
+        }
+        buckets.push(bucket);
+    }
+    for mut bucket in buckets {
+        bucket.tokens -= 1.0;
+    }
+    Ok(())
+}
+
+/// A slot of one gate, released when dropped
+struct Slot {
+    gate: Arc<Gate>,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.gate.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Permission to make a call; frees its concurrency slots when dropped
+#[must_use]
+pub struct LimitPermit {
+    _slots: Vec<Slot>,
+}
+
+/// One set of limits with its own gates
+struct Layer {
+    limits: RateLimits,
+    server: Arc<Gate>,
+    /// Gates of tool limits, by pattern
+    tools: Mutex<HashMap<String, Arc<Gate>>>,
+}
+
+impl Layer {
+    fn gates(&self, tool_name: &str) -> Vec<(String, Arc<Gate>)> {
+        let mut gates = Vec::new();
+        if !self.limits.server.is_unlimited() {
+            gates.push(("server".to_string(), self.server.clone()));
+        }
+        if let Some((pattern, limit)) = self.limits.tool_limit(tool_name) {
+            let mut tools = self.tools.lock().unwrap_or_else(|e| e.into_inner());
+            let gate = tools
+                .entry(pattern.clone())
+                .or_insert_with(|| Gate::new(limit.clone()))
+                .clone();
+            gates.push((format!("tool '{}'", tool_name), gate));
+        }
+        gates
+    }
+}
+
+/// Limits of one server, shared by all of its calls
+pub struct ServerLimiter {
+    server_id: String,
+    layers: Vec<Layer>,
+}
+
+impl ServerLimiter {
+    /// Limiter enforcing every one of `limits`
+    pub fn new(server_id: &str, limits: Vec<RateLimits>) -> Self {
+        Self {
+            server_id: server_id.to_string(),
+            layers: limits
+                .into_iter()
+                .filter(|limits| !limits.is_default())
+                .map(|limits| Layer {
+                    server: Gate::new(limits.server.clone()),
+                    tools: Mutex::new(HashMap::new()),
+                    limits,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    fn limited(&self, gate: &Gate, what: &str, retry_after: Option<Duration>) -> MCPClientError {
+        gate.limited.fetch_add(1, Ordering::SeqCst);
+        warn_log!(server_id = %self.server_id, limit = what, "Tool call rate limited");
+        MCPClientError::rate_limited(&self.server_id, what, retry_after)
+    }
+
+    fn enter(gate: &Arc<Gate>, permit: Option<OwnedSemaphorePermit>) -> Slot {
+        gate.in_flight.fetch_add(1, Ordering::SeqCst);
+        Slot {
+            gate: gate.clone(),
+            _permit: permit,
+        }
+    }
+
+    /// Let a call through, waiting for its turn where limits queue.
+    /// Concurrency slots are taken first and given back if the call is
+    /// rejected; rate tokens are only taken once every gate has one.
+    pub async fn acquire(&self, tool_name: &str) -> Result<LimitPermit, MCPClientError> {
+        let mut slots = Vec::new();
+        let mut gates = Vec::new();
+        let mut queueing = Vec::new();
+        for layer in &self.layers {
+            let mode = layer.limits.mode;
+            let deadline = Instant::now() + Duration::from_millis(layer.limits.queue_timeout_ms);
+            for (what, gate) in layer.gates(tool_name) {
+                let permit = match gate.slots {
+                    None => None,
+                    Some(ref slots) => match slots.clone().try_acquire_owned() {
+                        Ok(permit) => Some(permit),
+                        Err(_) if mode == LimitMode::Reject => {
+                            return Err(self.limited(&gate, &format!("{} concurrency", what), None));
+                        }
+                        Err(_) => {
+                            gate.queued.fetch_add(1, Ordering::SeqCst);
+                            let acquired = tokio::time::timeout_at(deadline.into(), slots.clone().acquire_owned()).await;
+                            gate.queued.fetch_sub(1, Ordering::SeqCst);
+                            match acquired {
+                                Ok(Ok(permit)) => Some(permit),
+                                _ => return Err(self.limited(&gate, &format!("{} concurrency", what), None)),
+                            }
+                        }
+                    },
+                };
+                slots.push(Self::enter(&gate, permit));
+                gates.push((what, gate));
+                queueing.push((mode, deadline));
+            }
+        }
+
+        loop {
+            match take_tokens(&gates) {
+                Ok(()) => break,
+                Err((index, wait)) => {
+                    let (what, gate) = &gates[index];
+                    let (mode, deadline) = queueing[index];
+                    if mode == LimitMode::Reject || Instant::now() + wait > deadline {
+                        return Err(self.limited(gate, &format!("{} rate", what), Some(wait)));
+                    }
+                    gate.queued.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(wait).await;
+                    gate.queued.fetch_sub(1, Ordering::SeqCst);
+                }
+            }
+        }
+        for (_, gate) in &gates {
+            gate.calls.fetch_add(1, Ordering::SeqCst);
+        }
+        Ok(LimitPermit { _slots: slots })
+    }
+
+    /// Let a call through only if it fits right away, for callers that can't wait
+    pub fn try_acquire(&self, tool_name: &str) -> Result<LimitPermit, MCPClientError> {
+        let mut slots = Vec::new();
+        let mut gates = Vec::new();
+        for layer in &self.layers {
+            for (what, gate) in layer.gates(tool_name) {
+                let permit = match gate.slots {
+                    None => None,
+                    Some(ref slots) => Some(
+                        slots
+                            .clone()
+                            .try_acquire_owned()
+                            .map_err(|_| self.limited(&gate, &format!("{} concurrency", what), None))?,
+                    ),
+                };
+                slots.push(Self::enter(&gate, permit));
+                gates.push((what, gate));
+            }
+        }
+
+        take_tokens(&gates).map_err(|(index, wait)| {
+            let (what, gate) = &gates[index];
+            self.limited(gate, &format!("{} rate", what), Some(wait))
+        })?;
+        for (_, gate) in &gates {
+            gate.calls.fetch_add(1, Ordering::SeqCst);
+        }
+        Ok(LimitPermit { _slots: slots })
+    }
+
+    /// Usage of the server limit, the server's own if it sets one, and of
+    /// the tool limits used so far
+    pub fn usage(&self) -> Option<RateLimitUsage> {
+        if self.is_unlimited() {
+            return None;
+        }
+        let mut usage = RateLimitUsage::default();
+        for layer in &self.layers {
+            if !layer.limits.server.is_unlimited() {
+                // The server's own limits come last
+                usage.server = layer.server.usage();
+            }
+            let tools = layer.tools.lock().unwrap_or_else(|e| e.into_inner());
+            for (pattern, gate) in tools.iter() {
+                usage.tools.insert(pattern.clone(), gate.usage());
+            }
+        }
+        Some(usage)
+    }
+}
@@ -0,0 +1,103 @@
+/// Ping-based liveness checks for connected servers
+///
+/// A process can be alive but deadlocked; periodic MCP `ping` requests catch
+/// that case. Any JSON-RPC response (including an error) counts as alive.
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+fn default_interval_ms() -> u64 {
+    30_000
+}
+
+fn default_timeout_ms() -> u64 {
+    5_000
+}
+
+fn default_failure_threshold() -> u32 {
+    3
+}
+
+/// Per-server liveness check settings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LivenessConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_interval_ms")]
+    pub interval_ms: u64,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Consecutive failed pings before the server is marked unresponsive
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+    /// Restart unresponsive servers using the server's restart backoff
+    #[serde(default)]
+    pub restart_unresponsive: bool,
+}
+
+impl Default for LivenessConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_ms: default_interval_ms(),
+            timeout_ms: default_timeout_ms(),
+            failure_threshold: default_failure_threshold(),
+            restart_unresponsive: false,
+        }
+    }
+}
+
+impl LivenessConfig {
+    /// Enabled checks with the default interval and threshold
+    pub fn enabled() -> Self {
+        Self {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    /// Whether this is the default (disabled) configuration
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis(self.interval_ms.max(1))
+    }
+}
+
+/// Ping latency statistics exposed on `ConnectionInfo`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct LatencyStats {
+    pub last_ms: Option<u64>,
+    pub min_ms: Option<u64>,
+    pub max_ms: Option<u64>,
+    pub avg_ms: f64,
+    /// Number of successful pings
+    pub samples: u64,
+    /// Number of failed pings since the connection was made
+    pub failures: u64,
+    pub consecutive_failures: u32,
+    /// Unix timestamp of the last ping attempt
+    pub last_ping_at: Option<u64>,
+}
+
+impl LatencyStats {
+    /// Record a successful ping
+    pub fn record_success(&mut self, latency_ms: u64, timestamp: u64) {
+        self.samples += 1;
+        self.avg_ms += (latency_ms as f64 - self.avg_ms) / self.samples as f64;
+        self.last_ms = Some(latency_ms);
+        self.min_ms = Some(self.min_ms.map_or(latency_ms, |min| min.min(latency_ms)));
+        self.max_ms = Some(self.max_ms.map_or(latency_ms, |max| max.max(latency_ms)));
+        self.consecutive_failures = 0;
+        self.last_ping_at = Some(timestamp);
+    }
+
+    /// Record a failed or timed out ping
+    pub fn record_failure(&mut self, timestamp: u64) {
+        self.failures += 1;
+        self.consecutive_failures += 1;
+        self.last_ping_at = Some(timestamp);
+    }
+}
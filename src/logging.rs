@@ -0,0 +1,93 @@
+/// Plugin diagnostics through `tracing`
+///
+/// Events and spans are emitted under the `tauri_plugin_mcp_client` target
+/// and carry `server_id`, `method` and `request_id` fields; the app's
+/// subscriber decides where they end up. `LogLevel` additionally caps what
+/// the plugin emits. Message payloads (tool arguments, results, server
+/// output) are only logged when payload logging is turned on.
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+/// Most verbose level the plugin emits
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Off = 0,
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    #[default]
+    Debug = 4,
+    Trace = 5,
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Debug as u8);
+static PAYLOADS: AtomicBool = AtomicBool::new(false);
+
+/// Set the verbosity for the whole plugin
+pub fn set_level(level: LogLevel) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Whether events at `level` are emitted
+pub fn enabled(level: LogLevel) -> bool {
+    level != LogLevel::Off && level as u8 <= LEVEL.load(Ordering::Relaxed)
+}
+
+/// Include message payloads in debug events. Payloads can contain user
+/// data, so this is off by default.
+pub fn set_log_payloads(enabled: bool) {
+    PAYLOADS.store(enabled, Ordering::Relaxed);
+}
+
+pub fn payloads_enabled() -> bool {
+    PAYLOADS.load(Ordering::Relaxed)
+}
+
+/// Emit a message payload when payload logging is on. The payload is only
+/// built (and redacted) when it is actually logged.
+pub(crate) fn log_payload(server_id: &str, direction: &'static str, payload: impl FnOnce() -> String) {
+    if payloads_enabled() && enabled(LogLevel::Debug) {
+        tracing::debug!(server_id, direction, payload = %payload(), "MCP message payload");
+    }
+}
+
+macro_rules! error_log {
+    ($($arg:tt)*) => {
+        if $crate::logging::enabled($crate::logging::LogLevel::Error) {
+            tracing::error!($($arg)*);
+        }
+    };
+}
+
+macro_rules! warn_log {
+    ($($arg:tt)*) => {
+        if $crate::logging::enabled($crate::logging::LogLevel::Warn) {
+            tracing::warn!($($arg)*);
+        }
+    };
+}
+
+macro_rules! info_log {
+    ($($arg:tt)*) => {
+        if $crate::logging::enabled($crate::logging::LogLevel::Info) {
+            tracing::info!($($arg)*);
+        }
+    };
+}
+
+macro_rules! debug_log {
+    ($($arg:tt)*) => {
+        if $crate::logging::enabled($crate::logging::LogLevel::Debug) {
+            tracing::debug!($($arg)*);
+        }
+    };
+}
+
+macro_rules! trace_log {
+    ($($arg:tt)*) => {
+        if $crate::logging::enabled($crate::logging::LogLevel::Trace) {
+            tracing::trace!($($arg)*);
+        }
+    };
+}
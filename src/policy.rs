@@ -0,0 +1,324 @@
+/// Rust-side policy for which servers may be spawned
+///
+/// The frontend can ask `plugin_connect_server` to run any command, so the
+/// plugin checks every spawn against a policy set on the builder or in the
+/// `spawnPolicy` section of the plugin configuration:
+///
+/// ```json
+/// "spawnPolicy": {
+///   "commands": ["npx", "/opt/mcp/*"],
+///   "args": ["-y", "@modelcontextprotocol/server-*"],
+///   "envKeys": ["GITHUB_*"]
+/// }
+/// ```
+///
+/// Capability scopes narrow this further per window (see `SpawnScope`).
+/// Patterns are exact strings or globs with `*` (any run of characters) and
+/// `?` (one character). In command patterns with a path, wildcards don't
+/// match path separators.
+use crate::config::ServerConfig;
+use crate::error::MCPClientError;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path};
+use std::sync::Arc;
+
+/// Whether `text` matches the glob `pattern`
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text position it resumes from
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                // Let the last `*` swallow one more character
+                Some((after, resume)) => {
+                    p = after;
+                    t = resume + 1;
+                    star = Some((after, resume + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Whether a command matches a pattern. A bare name such as `npx` only
+/// matches a bare command, which is looked up on the `PATH`. A pattern with
+/// a path only matches an absolute command without `..`, one path component
+/// per wildcard, so `/opt/mcp/*` doesn't match `/opt/mcp/../../bin/sh`.
+pub fn command_matches(pattern: &str, command: &str) -> bool {
+    let is_bare = |text: &str| !text.contains(std::path::is_separator);
+    if is_bare(pattern) {
+        return is_bare(command) && glob_match(pattern, command);
+    }
+    if !Path::new(command).is_absolute() || has_parent_dir(command) {
+        return false;
+    }
+    let pattern: Vec<&str> = pattern.split(std::path::is_separator).collect();
+    let command: Vec<&str> = command.split(std::path::is_separator).collect();
+    pattern.len() == command.len()
+        && pattern
+            .iter()
+            .zip(&command)
+            .all(|(pattern, component)| glob_match(pattern, component))
+}
+
+/// Whether a deny pattern catches a command. Bare names also catch the
+/// command run through a path, so `bash` blocks `/bin/bash`.
+fn command_caught(pattern: &str, command: &str) -> bool {
+    let file_name = Path::new(command).file_name().and_then(|name| name.to_str());
+    command_matches(pattern, command)
+        || (!pattern.contains(std::path::is_separator) && file_name.is_some_and(|name| glob_match(pattern, name)))
+}
+
+/// Whether a command steps out of a directory with `..`
+fn has_parent_dir(command: &str) -> bool {
+    Path::new(command)
+        .components()
+        .any(|component| component == Component::ParentDir)
+}
+
+/// Environment variables that change which program runs or what an
+/// interpreter loads before the server's own code
+pub const DANGEROUS_ENV_KEYS: &[&str] = &[
+    "PATH",
+    "LD_*",
+    "DYLD_*",
+    "NODE_OPTIONS",
+    "NODE_PATH",
+    "PYTHONSTARTUP",
+    "PYTHONPATH",
+    "PYTHONHOME",
+    "PERL5OPT",
+    "PERL5LIB",
+    "RUBYOPT",
+    "RUBYLIB",
+    "BASH_ENV",
+    "ENV",
+    "JAVA_TOOL_OPTIONS",
+    "_JAVA_OPTIONS",
+];
+
+/// Whether an environment variable is one of `DANGEROUS_ENV_KEYS`
+pub fn is_dangerous_env_key(key: &str) -> bool {
+    let key = key.to_ascii_uppercase();
+    DANGEROUS_ENV_KEYS.iter().any(|pattern| glob_match(pattern, &key))
+}
+
+fn all_match<'a>(patterns: &[String], mut values: impl Iterator<Item = &'a str>) -> Result<(), &'a str> {
+    match values.find(|value| !patterns.iter().any(|pattern| glob_match(pattern, value))) {
+        Some(value) => Err(value),
+        None => Ok(()),
+    }
+}
+
+/// What a spawn request would run
+#[derive(Debug, Clone)]
+pub struct SpawnRequest<'a> {
+    pub command: &'a str,
+    pub args: &'a [String],
+    /// Environment variables set by the server definition, sorted
+    pub env_keys: Vec<&'a str>,
+    pub cwd: Option<&'a str>,
+}
+
+impl<'a> SpawnRequest<'a> {
+    pub fn from_config(config: &'a ServerConfig) -> Self {
+        let mut env_keys: Vec<&str> = config.env.keys().map(String::as_str).collect();
+        env_keys.sort_unstable();
+        Self {
+            command: &config.command,
+            args: &config.args,
+            env_keys,
+            cwd: config.cwd.as_deref(),
+        }
+    }
+}
+
+/// Limits on the servers the plugin may spawn. Unset lists allow anything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpawnPolicy {
+    /// Commands servers may be started with
+    #[serde(default)]
+    pub commands: Option<Vec<String>>,
+    /// Patterns every argument must match
+    #[serde(default)]
+    pub args: Option<Vec<String>>,
+    /// Environment variables a server definition may set
+    #[serde(default)]
+    pub env_keys: Option<Vec<String>>,
+}
+
+impl SpawnPolicy {
+    /// Policy allowing only these commands
+    pub fn commands<I, S>(commands: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            commands: Some(commands.into_iter().map(Into::into).collect()),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_args<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args = Some(patterns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn with_env_keys<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.env_keys = Some(patterns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn is_unrestricted(&self) -> bool {
+        self.commands.is_none() && self.args.is_none() && self.env_keys.is_none()
+    }
+
+    /// Check a spawn against the policy
+    pub fn check(&self, request: &SpawnRequest) -> Result<(), MCPClientError> {
+        if let Some(ref commands) = self.commands {
+            if !commands.iter().any(|pattern| command_matches(pattern, request.command)) {
+                return Err(MCPClientError::command_not_allowed(request.command));
+            }
+        }
+        if let Some(ref patterns) = self.args {
+            all_match(patterns, request.args.iter().map(String::as_str))
+                .map_err(MCPClientError::argument_not_allowed)?;
+        }
+        if let Some(ref patterns) = self.env_keys {
+            all_match(patterns, request.env_keys.iter().copied())
+                .map_err(MCPClientError::env_not_allowed)?;
+        }
+        Ok(())
+    }
+}
+
+/// Spawn rule of a capability scope entry with a `command` (see `McpScope`)
+///
+/// ```json
+/// {
+///   "identifier": "mcp-client:allow-plugin-connect-server",
+///   "allow": [{ "command": "npx", "args": ["-y", "@modelcontextprotocol/*"] }],
+///   "deny": [{ "command": "bash" }]
+/// }
+/// ```
+///
+/// An allow entry permits a spawn when the command matches and every
+/// argument and env key matches its patterns, if it lists any. A deny entry
+/// blocks a spawn when the command matches and, if it lists patterns, any
+/// argument, env key or working directory matches them. With allow entries
+/// a spawn must be permitted by one of them; a matching deny entry always
+/// blocks it.
+///
+/// Some settings are opt-in. Env keys in `DANGEROUS_ENV_KEYS`, such as
+/// `LD_PRELOAD`, are blocked unless the permitting allow entry names them
+/// exactly in `envKeys`. A spawn with a working directory needs an allow
+/// entry with matching `cwd` patterns, which match like command patterns.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpawnScope {
+    pub command: String,
+    #[serde(default)]
+    pub args: Option<Vec<String>>,
+    #[serde(default)]
+    pub env_keys: Option<Vec<String>>,
+    #[serde(default)]
+    pub cwd: Option<Vec<String>>,
+}
+
+impl SpawnScope {
+    /// Whether this entry, as an allow entry, permits the spawn
+    pub fn permits(&self, request: &SpawnRequest) -> bool {
+        command_matches(&self.command, request.command)
+            && self
+                .args
+                .as_ref()
+                .is_none_or(|patterns| all_match(patterns, request.args.iter().map(String::as_str)).is_ok())
+            && self
+                .env_keys
+                .as_ref()
+                .is_none_or(|patterns| all_match(patterns, request.env_keys.iter().copied()).is_ok())
+            && request
+                .env_keys
+                .iter()
+                .all(|key| !is_dangerous_env_key(key) || self.names_env_key(key))
+            && request.cwd.is_none_or(|cwd| {
+                !has_parent_dir(cwd)
+                    && self.cwd
+                        .as_ref()
+                        .is_some_and(|patterns| patterns.iter().any(|pattern| command_matches(pattern, cwd)))
+            })
+    }
+
+    /// Whether `envKeys` lists the key itself rather than a pattern matching it
+    fn names_env_key(&self, key: &str) -> bool {
+        self.env_keys
+            .as_ref()
+            .is_some_and(|patterns| patterns.iter().any(|pattern| pattern == key))
+    }
+
+    /// Whether this entry, as a deny entry, blocks the spawn
+    pub fn forbids(&self, request: &SpawnRequest) -> bool {
+        if !command_caught(&self.command, request.command) {
+            return false;
+        }
+        if self.args.is_none() && self.env_keys.is_none() && self.cwd.is_none() {
+            return true;
+        }
+        let any_match = |patterns: &Option<Vec<String>>, values: &[&str]| {
+            patterns.as_ref().is_some_and(|patterns| {
+                values
+                    .iter()
+                    .any(|value| patterns.iter().any(|pattern| glob_match(pattern, value)))
+            })
+        };
+        let args: Vec<&str> = request.args.iter().map(String::as_str).collect();
+        let cwd: Vec<&str> = request.cwd.into_iter().collect();
+        any_match(&self.args, &args) || any_match(&self.env_keys, &request.env_keys) || any_match(&self.cwd, &cwd)
+    }
+
+    /// Check a spawn against the allow and deny entries of a scope
+    pub fn check(allow: &[Arc<SpawnScope>], deny: &[Arc<SpawnScope>], request: &SpawnRequest) -> Result<(), MCPClientError> {
+        let restricted = !allow.is_empty() || !deny.is_empty();
+        if (restricted && has_parent_dir(request.command)) || deny.iter().any(|entry| entry.forbids(request)) {
+            return Err(MCPClientError::spawn_denied_by_scope(request.command));
+        }
+        let unnamed = request
+            .env_keys
+            .iter()
+            .find(|key| is_dangerous_env_key(key) && !allow.iter().any(|entry| entry.names_env_key(key)));
+        if let Some(key) = unnamed {
+            return Err(MCPClientError::spawn_denied_by_scope(request.command).with_details(&format!(
+                "Environment variable '{}' can change what the server runs and must be named in an allow entry's envKeys",
+                key
+            )));
+        }
+        if !allow.is_empty() && !allow.iter().any(|entry| entry.permits(request)) {
+            return Err(MCPClientError::spawn_denied_by_scope(request.command));
+        }
+        Ok(())
+    }
+}
@@ -1,12 +1,18 @@
 use crate::error::{ErrorCategory, MCPClientError};
-use once_cell::sync::Lazy;
+use crate::handlers::ClientHandlers;
+use crate::registry;
+use crate::replay::ReplaySession;
+use crate::secrets::SecretRedactor;
+use crate::shutdown::{ShutdownConfig, ShutdownStep};
+use crate::state::ConnectionState;
+use crate::traffic::{TrafficDirection, TrafficRecorder};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::io::{BufRead, BufReader, Write};
 use std::process::Child;
-use std::sync::atomic::{AtomicU32, Ordering};
-use std::sync::mpsc::{channel, Receiver};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, sync_channel, Receiver, Sender, SyncSender, TrySendError};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock};
 use std::time::{Duration, Instant};
 // Removed AppHandle import since we now use system Node.js directly
 
@@ -25,7 +31,7 @@ fn check_nodejs_availability() -> Result<String, MCPClientError> {
             if output.status.success() {
                 let version = String::from_utf8_lossy(&output.stdout);
                 let version_str = version.trim().to_string();
-                eprintln!("DEBUG: Found Node.js version: {}", version_str);
+                debug_log!(version = %version_str, "Found Node.js");
                 Ok(version_str)
             } else {
                 Err(MCPClientError::new(
@@ -57,15 +63,168 @@ fn check_nodejs_availability() -> Result<String, MCPClientError> {
     }
 }
 
+/// Extra options applied when spawning a server process
+///
+/// Deliberately not `Debug`: the environment may hold resolved secrets.
+#[derive(Clone, Default)]
+pub struct SpawnOptions {
+    /// Environment variables added to the inherited environment
+    pub env: HashMap<String, String>,
+    /// Working directory for the server
+    pub cwd: Option<std::path::PathBuf>,
+    /// Sandbox the process runs in
+    pub sandbox: crate::sandbox::SandboxConfig,
+}
+
+/// Output stream the server's responses are read from: a process's stdout,
+/// or the pipe of an in-process transport such as replay. It is read on a
+/// thread of its own.
+pub(crate) trait ServerOutput: std::io::Read + Send {}
+impl<T: std::io::Read + Send> ServerOutput for T {}
+
+/// Reading end of an in-memory pipe. Reads block until data is written and
+/// end once the writer is dropped.
+pub(crate) struct PipeReader {
+    receiver: Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl std::io::Read for PipeReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.position == self.chunk.len() {
+            match self.receiver.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk;
+                    self.position = 0;
+                }
+                Err(_) => return Ok(0),
+            }
+        }
+        let len = buf.len().min(self.chunk.len() - self.position);
+        buf[..len].copy_from_slice(&self.chunk[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+/// Writing end of an in-memory pipe. Writes fail once the reader is dropped.
+pub(crate) struct PipeWriter {
+    sender: Sender<Vec<u8>>,
+}
+
+impl Write for PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if !buf.is_empty() {
+            self.sender.send(buf.to_vec()).map_err(|_| {
+                std::io::Error::new(std::io::ErrorKind::BrokenPipe, "pipe reader closed")
+            })?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// In-memory pipe connecting an in-process server to its connection
+fn pipe() -> (PipeReader, PipeWriter) {
+    let (sender, receiver) = channel();
+    (
+        PipeReader {
+            receiver,
+            chunk: Vec::new(),
+            position: 0,
+        },
+        PipeWriter { sender },
+    )
+}
+
+/// Input stream the server's requests are written to, `None` once closed
+type SharedStdin = Arc<Mutex<Option<Box<dyn Write + Send>>>>;
+
+/// MCP protocol version requested in `initialize`
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// Protocol versions a server may answer `initialize` with, newest first
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// Number of trailing stderr lines kept for exit reports
+pub const STDERR_TAIL_LINES: usize = 20;
+
+/// How often the exit watcher polls a running process
+const EXIT_WATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Threads answering one server's requests at the same time
+const SERVER_REQUEST_WORKERS: usize = 4;
+
+/// Server requests waiting for a free worker before more are refused
+const SERVER_REQUEST_QUEUE: usize = 16;
+
+/// How a server process ended
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProcessExit {
+    pub code: Option<i32>,
+    pub signal: Option<i32>,
+    pub success: bool,
+    pub description: String,
+    /// Last lines the process wrote to stderr (redacted)
+    pub stderr_tail: Vec<String>,
+}
+
+impl ProcessExit {
+    pub fn from_status(status: &std::process::ExitStatus, stderr_tail: Vec<String>) -> Self {
+        #[cfg(unix)]
+        let signal = {
+            use std::os::unix::process::ExitStatusExt;
+            status.signal()
+        };
+        #[cfg(not(unix))]
+        let signal = None;
+
+        Self {
+            code: status.code(),
+            signal,
+            success: status.success(),
+            description: describe_exit_status(status),
+            stderr_tail,
+        }
+    }
+
+    /// Description including the last stderr line, used as the disconnect reason
+    pub fn reason(&self) -> String {
+        match self.stderr_tail.last() {
+            Some(line) => format!("Process {}: {}", self.description, line),
+            None => format!("Process {}", self.description),
+        }
+    }
+}
+
 /// Single MCP server process manager
 pub struct MCPProcess {
     server_id: String,
-    process: Option<Child>,
-    stdin: Option<std::process::ChildStdin>,
-    stdout: Option<BufReader<std::process::ChildStdout>>,
+    /// Shared with the exit watcher thread
+    process: Option<Arc<Mutex<Child>>>,
+    /// Shared with the threads answering server requests
+    stdin: SharedStdin,
+    /// Set once the process is started and its output is being read
+    channel: Option<ServerChannel>,
     stderr_receiver: Option<Receiver<String>>,
-    message_counter: AtomicU32,
+    message_counter: Arc<AtomicU32>,
     pending_requests: Mutex<HashMap<u32, PendingRequest>>,
+    redactor: SecretRedactor,
+    stderr_tail: Arc<Mutex<VecDeque<String>>>,
+    /// Set when the process is stopped on purpose so the watcher stays quiet
+    watcher_stop: Arc<AtomicBool>,
+    /// Exit code of an in-process transport that stopped on its own
+    in_process_exit: Option<Arc<Mutex<Option<i32>>>>,
+    shutdown: ShutdownConfig,
+    handlers: ClientHandlers,
+    request_timeout_ms: u64,
+    traffic: Option<TrafficRecorder>,
+    /// `result` of the server's `initialize` response
+    initialize_result: Option<serde_json::Value>,
 }
 
 impl MCPProcess {
@@ -73,14 +232,85 @@ impl MCPProcess {
         Self {
             server_id,
             process: None,
-            stdin: None,
-            stdout: None,
+            stdin: Arc::new(Mutex::new(None)),
+            channel: None,
             stderr_receiver: None,
-            message_counter: AtomicU32::new(0),
+            message_counter: Arc::new(AtomicU32::new(0)),
             pending_requests: Mutex::new(HashMap::new()),
+            redactor: SecretRedactor::new(),
+            stderr_tail: Arc::new(Mutex::new(VecDeque::new())),
+            watcher_stop: Arc::new(AtomicBool::new(false)),
+            in_process_exit: None,
+            shutdown: ShutdownConfig::default(),
+            handlers: ClientHandlers::default(),
+            request_timeout_ms: 5000,
+            traffic: None,
+            initialize_result: None,
         }
     }
 
+    /// Set the grace periods used when the process is stopped
+    pub fn set_shutdown_config(&mut self, shutdown: ShutdownConfig) {
+        self.shutdown = shutdown;
+    }
+
+    pub fn shutdown_config(&self) -> &ShutdownConfig {
+        &self.shutdown
+    }
+
+    /// Set the handlers answering requests sent by the server
+    pub fn set_handlers(&mut self, handlers: ClientHandlers) {
+        self.handlers = handlers;
+    }
+
+    /// Set the timeout for the `initialize` response
+    pub fn set_request_timeout(&mut self, timeout_ms: u64) {
+        self.request_timeout_ms = timeout_ms;
+    }
+
+    /// `result` of the server's `initialize` response, if it answered in time
+    pub fn initialize_result(&self) -> Option<&serde_json::Value> {
+        self.initialize_result.as_ref()
+    }
+
+    /// Record every message sent and received in `traffic`
+    pub fn set_traffic_recorder(&mut self, traffic: TrafficRecorder) {
+        self.traffic = Some(traffic);
+    }
+
+    fn lock_stdin(&self) -> MutexGuard<'_, Option<Box<dyn Write + Send>>> {
+        self.stdin.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_child(&self) -> Option<MutexGuard<'_, Child>> {
+        self.process
+            .as_ref()
+            .map(|child| child.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// OS process id of the running server
+    pub fn pid(&self) -> Option<u32> {
+        self.lock_child().map(|child| child.id())
+    }
+
+    /// Last lines written to stderr, oldest first
+    pub fn stderr_tail(&self) -> Vec<String> {
+        self.stderr_tail
+            .lock()
+            .map(|tail| tail.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Set the redactor used to hide secret values in logs and debug output
+    pub fn set_redactor(&mut self, redactor: SecretRedactor) {
+        self.redactor = redactor;
+    }
+
+    /// Get the redactor for this process's secrets
+    pub fn redactor(&self) -> &SecretRedactor {
+        &self.redactor
+    }
+
     /// Generate the next unique message ID for JSON-RPC requests
     pub fn next_message_id(&self) -> u32 {
         self.message_counter.fetch_add(1, Ordering::SeqCst)
@@ -106,25 +336,22 @@ impl MCPProcess {
         }
     }
 
-    /// Test if we can read anything from stdout (diagnostic function)
+    /// Test if the server's output is still being read (diagnostic function)
     pub fn test_stdout_availability(&mut self) -> Result<String, String> {
-        eprintln!(
-            "DEBUG: Testing stdout availability for server {}",
-            self.server_id
-        );
+        debug_log!(server_id = %self.server_id, "Testing stdout availability");
 
-        if self.stdout.is_none() {
+        let Some(ref channel) = self.channel else {
             return Err("No stdout available".to_string());
-        }
+        };
 
         // Check if process is still running
-        if let Some(child) = &mut self.process {
+        if let Some(mut child) = self.lock_child() {
             match child.try_wait() {
                 Ok(Some(status)) => {
                     return Err(format!("Process has exited with status: {:?}", status));
                 }
                 Ok(None) => {
-                    eprintln!("DEBUG: Process is still running");
+                    debug_log!(server_id = %self.server_id, "Process is still running");
                 }
                 Err(e) => {
                     return Err(format!("Error checking process status: {}", e));
@@ -132,32 +359,7 @@ impl MCPProcess {
             }
         }
 
-        // Try to read with a very short timeout to see if anything is available
-        let start_time = std::time::Instant::now();
-        let timeout = Duration::from_millis(100); // Very short timeout
-        let stdout = self.stdout.as_mut().unwrap();
-
-        while start_time.elapsed() < timeout {
-            let mut line = String::new();
-            match stdout.read_line(&mut line) {
-                Ok(0) => {
-                    return Err("Process closed stdout".to_string());
-                }
-                Ok(bytes_read) => {
-                    return Ok(format!("Read {} bytes: '{}'", bytes_read, line.trim()));
-                }
-                Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                    // Would block, continue waiting
-                    std::thread::sleep(Duration::from_millis(5));
-                    continue;
-                }
-                Err(e) => {
-                    return Err(format!("Error reading: {}", e));
-                }
-            }
-        }
-
-        Ok("No data available within timeout".to_string())
+        channel.responses.status()
     }
 
     /// Get comprehensive debug information about this process
@@ -165,7 +367,7 @@ impl MCPProcess {
         let mut debug_info = serde_json::Map::new();
 
         // Test basic process health
-        if let Some(child) = &mut self.process {
+        if let Some(mut child) = self.lock_child() {
             match child.try_wait() {
                 Ok(Some(status)) => {
                     debug_info.insert(
@@ -235,24 +437,43 @@ impl MCPProcess {
             );
         }
 
+        // Current CPU time, memory, threads and open files of the process tree
+        if let Some(sample) = self.pid().and_then(crate::resources::sample_process_tree) {
+            debug_info.insert("resources".to_string(), serde_json::json!(sample));
+        }
+
         // Check pipe states
         debug_info.insert(
             "pipe_status".to_string(),
             serde_json::json!({
-                "stdin_available": self.stdin.is_some(),
-                "stdout_available": self.stdout.is_some(),
+                "stdin_available": self.lock_stdin().is_some(),
+                "stdout_available": self.channel.as_ref().is_some_and(|channel| channel.responses.is_open()),
                 "stderr_receiver_available": self.stderr_receiver.is_some()
             }),
         );
 
-        serde_json::Value::Object(debug_info)
+        self.redactor
+            .redact_value(&serde_json::Value::Object(debug_info))
     }
 
     pub async fn start(&mut self, command: &str, args: &[String]) -> Result<(), MCPClientError> {
-        eprintln!(
-            "DEBUG: Starting MCP process for server {} with command: '{}' args: {:?}",
-            self.server_id, command, args
+        self.start_with_options(command, args, &SpawnOptions::default()).await
+    }
+
+    /// Start the process with extra spawn options (environment, working directory)
+    pub async fn start_with_options(
+        &mut self,
+        command: &str,
+        args: &[String],
+        options: &SpawnOptions,
+    ) -> Result<(), MCPClientError> {
+        debug_log!(
+            server_id = %self.server_id,
+            command,
+            arg_count = args.len(),
+            "Starting MCP process"
         );
+        crate::logging::log_payload(&self.server_id, "args", || self.redactor.redact(&format!("{:?}", args)));
 
         // Check Node.js availability for Node.js-based commands
         if command == "node" || command == "npx" {
@@ -262,14 +483,23 @@ impl MCPProcess {
         }
 
         // Spawn MCP server process with stdio pipes for MCP communication
-        let mut cmd = std::process::Command::new(command);
-        cmd.args(args)
+        let mut cmd = crate::sandbox::command(&options.sandbox, command, args, options.cwd.as_deref())?;
+        cmd.envs(&options.env)
             .stdin(std::process::Stdio::piped())
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped());
 
+        // Run the server in its own process group so wrappers like npx can be torn
+        // down together with the processes they spawn. PR_SET_PDEATHSIG is not used:
+        // it fires when the spawning *thread* exits, which async runtimes do routinely.
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+
         let mut child = cmd.spawn().map_err(|e| {
-            eprintln!("DEBUG: Failed to spawn MCP server process: {}", e);
+            warn_log!(server_id = %self.server_id, command, error = %e, "Failed to spawn MCP server process");
 
             // Create specific error based on command type and error details
             let error_str = e.to_string().to_lowercase();
@@ -319,11 +549,12 @@ impl MCPProcess {
 
         // Capture stderr for debugging and error reporting
         if let Some(stderr) = child.stderr.take() {
-            eprintln!("DEBUG: Process has stderr available for capture");
             let (sender, receiver) = channel();
             self.stderr_receiver = Some(receiver);
 
             let server_id_clone = self.server_id.clone();
+            let redactor = self.redactor.clone();
+            let tail = self.stderr_tail.clone();
             std::thread::spawn(move || {
                 use std::io::{BufRead, BufReader};
                 let reader = BufReader::new(stderr);
@@ -332,23 +563,24 @@ impl MCPProcess {
                 for line in reader.lines() {
                     match line {
                         Ok(line_content) => {
-                            eprintln!("DEBUG: MCP stderr [{}]: {}", server_id_clone, line_content);
+                            let line_content = redactor.redact(&line_content);
+                            crate::logging::log_payload(&server_id_clone, "stderr", || line_content.clone());
                             stderr_lines.push(line_content.clone());
+                            if let Ok(mut tail) = tail.lock() {
+                                if tail.len() == STDERR_TAIL_LINES {
+                                    tail.pop_front();
+                                }
+                                tail.push_back(line_content.clone());
+                            }
 
                             // Send individual lines to channel (non-blocking)
                             if sender.send(line_content).is_err() {
-                                eprintln!(
-                                    "DEBUG: Stderr channel closed for server {}",
-                                    server_id_clone
-                                );
+                                trace_log!(server_id = %server_id_clone, "Stderr channel closed");
                                 break;
                             }
                         }
                         Err(e) => {
-                            eprintln!(
-                                "DEBUG: Error reading stderr from MCP process {}: {}",
-                                server_id_clone, e
-                            );
+                            debug_log!(server_id = %server_id_clone, error = %e, "Error reading stderr");
                             break;
                         }
                     }
@@ -360,60 +592,107 @@ impl MCPProcess {
                     let _ = sender.send(format!("STDERR_COMPLETE:{}", combined_stderr));
                 }
 
-                eprintln!(
-                    "DEBUG: Stderr reader thread ended for server {}",
-                    server_id_clone
-                );
+                trace_log!(server_id = %server_id_clone, "Stderr reader thread ended");
             });
         }
 
         // Take stdin for writing and stdout for reading
-        self.stdin = child.stdin.take();
-        if let Some(stdout) = child.stdout.take() {
-            eprintln!(
-                "DEBUG: Successfully captured stdout for server {}",
-                self.server_id
-            );
-            self.stdout = Some(BufReader::new(stdout));
-        } else {
-            eprintln!(
-                "DEBUG: WARNING - No stdout available for server {}",
-                self.server_id
-            );
+        *self.lock_stdin() = child
+            .stdin
+            .take()
+            .map(|stdin| Box::new(stdin) as Box<dyn Write + Send>);
+        match child.stdout.take() {
+            Some(stdout) => self.open_channel(Box::new(stdout)),
+            None => warn_log!(server_id = %self.server_id, "No stdout available"),
         }
-
-        // Check if stdin is available
-        if self.stdin.is_some() {
-            eprintln!(
-                "DEBUG: Successfully captured stdin for server {}",
-                self.server_id
-            );
-        } else {
-            eprintln!(
-                "DEBUG: WARNING - No stdin available for server {}",
-                self.server_id
-            );
+        if self.lock_stdin().is_none() {
+            warn_log!(server_id = %self.server_id, "No stdin available");
         }
 
-        self.process = Some(child);
+        self.watcher_stop.store(false, Ordering::SeqCst);
+        self.process = Some(Arc::new(Mutex::new(child)));
 
-        eprintln!(
-            "DEBUG: MCP process started for server {} - stdin: {}, stdout: {}",
-            self.server_id,
-            if self.stdin.is_some() {
-                "available"
-            } else {
-                "missing"
-            },
-            if self.stdout.is_some() {
-                "available"
-            } else {
-                "missing"
-            }
+        debug_log!(server_id = %self.server_id, pid = self.pid(), "MCP process started");
+        Ok(())
+    }
+
+    /// Play back a recorded session instead of spawning a process. The
+    /// replay server runs on a background thread and stops when the
+    /// connection is stopped.
+    pub fn start_replay(&mut self, session: ReplaySession) -> Result<(), MCPClientError> {
+        debug_log!(
+            server_id = %self.server_id,
+            entries = session.len(),
+            "Starting replay transport"
         );
+        self.start_in_process("replay", move |input, output| {
+            session.serve(input, output).map(|()| None)
+        })
+    }
+
+    /// Serve the connection from a mock server running in this process
+    #[cfg(feature = "testing")]
+    pub fn start_mock(&mut self, server: crate::testing::MockServer) -> Result<(), MCPClientError> {
+        debug_log!(server_id = %self.server_id, "Starting mock transport");
+        self.start_in_process("mock", move |input, output| server.serve(input, output))
+    }
+
+    /// Connect to a server running on a background thread over in-memory
+    /// pipes. `serve` returns the exit code of a simulated crash, or `None`
+    /// once the client closes its side.
+    fn start_in_process<F>(&mut self, transport: &'static str, serve: F) -> Result<(), MCPClientError>
+    where
+        F: FnOnce(BufReader<PipeReader>, PipeWriter) -> std::io::Result<Option<i32>>
+            + Send
+            + 'static,
+    {
+        let (server_input, client_output) = pipe();
+        let (client_input, server_output) = pipe();
+
+        let exit = Arc::new(Mutex::new(None));
+        let exit_code = exit.clone();
+        let server_id = self.server_id.clone();
+        std::thread::spawn(move || match serve(BufReader::new(server_input), server_output) {
+            Ok(None) => trace_log!(server_id = %server_id, transport, "In-process transport closed"),
+            Ok(Some(code)) => {
+                debug_log!(server_id = %server_id, transport, code, "In-process server exited");
+                if let Ok(mut exit) = exit_code.lock() {
+                    *exit = Some(code);
+                }
+            }
+            Err(e) => debug_log!(server_id = %server_id, transport, error = %e, "In-process transport ended"),
+        });
+
+        *self.lock_stdin() = Some(Box::new(client_output));
+        self.open_channel(Box::new(client_input));
+        self.watcher_stop.store(false, Ordering::SeqCst);
+        self.in_process_exit = Some(exit);
         Ok(())
     }
 
+    /// Start reading the server's output on a background thread
+    fn open_channel(&mut self, output: Box<dyn ServerOutput>) {
+        let channel = ServerChannel {
+            server_id: self.server_id.clone(),
+            stdin: self.stdin.clone(),
+            responses: Arc::new(ResponseRouter::default()),
+            message_counter: self.message_counter.clone(),
+            redactor: self.redactor.clone(),
+            traffic: self.traffic.clone(),
+            protocol_version: Arc::new(OnceLock::new()),
+        };
+        let reader = channel.clone();
+        let handlers = self.handlers.clone();
+        std::thread::spawn(move || reader.read_messages(BufReader::new(output), &handlers));
+        self.channel = Some(channel);
+    }
+
+    /// Channel for sending requests to the running server. It can be used
+    /// without holding on to the process, by several requests at once.
+    pub(crate) fn channel(&self) -> Result<ServerChannel, MCPClientError> {
+        self.channel.clone().ok_or_else(no_stdout)
+    }
+
     /// Collect any available stderr output
     pub fn collect_stderr(&mut self, timeout_ms: u64) -> Option<String> {
         if let Some(ref receiver) = self.stderr_receiver {
@@ -455,10 +734,7 @@ impl MCPProcess {
     }
 
     pub fn send_initialize(&mut self) -> Result<(), MCPClientError> {
-        eprintln!(
-            "DEBUG: Starting MCP initialization for server {}",
-            self.server_id
-        );
+        debug_log!(server_id = %self.server_id, "Starting MCP initialization");
 
         let message_id = self.next_message_id();
         self.track_request(message_id, "initialize");
@@ -467,8 +743,8 @@ impl MCPProcess {
             "id": message_id,
             "method": "initialize",
             "params": {
-                "protocolVersion": "2024-11-05",
-                "capabilities": {},
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": self.handlers.capabilities(),
                 "clientInfo": {
                     "name": "tauri-plugin-mcp-client",
                     "version": "1.0.0"
@@ -476,24 +752,33 @@ impl MCPProcess {
             }
         });
 
-        eprintln!(
-            "DEBUG: Sending initialize message to server {}",
-            self.server_id
-        );
         self.send_message_sync(init_message)?;
-        eprintln!("DEBUG: Initialize message sent successfully");
 
         // Read the initialize response
-        eprintln!("DEBUG: Waiting for initialize response...");
-        match self.read_response(message_id as u64, 5000) {
+        match self.read_response(message_id as u64, self.request_timeout_ms) {
             Ok(response) => {
-                eprintln!("DEBUG: Got initialize response: {}", response);
+                let server_info = response.pointer("/result/serverInfo");
+                debug_log!(server_id = %self.server_id, server_info = ?server_info, "Got initialize response");
+                self.initialize_result = response.get("result").cloned();
+
+                // The server may answer with an older version it supports
+                if let Some(version) = response.pointer("/result/protocolVersion").and_then(|v| v.as_str()) {
+                    if !SUPPORTED_PROTOCOL_VERSIONS.contains(&version) {
+                        return Err(MCPClientError::unsupported_protocol_version(version));
+                    }
+                    if version != PROTOCOL_VERSION {
+                        debug_log!(server_id = %self.server_id, protocol_version = version, "Server uses an older protocol version");
+                    }
+                    if let Some(ref channel) = self.channel {
+                        let _ = channel.protocol_version.set(version.to_string());
+                    }
+                }
             }
             Err(e) => {
-                eprintln!("DEBUG: Failed to read initialize response: {}", e);
+                warn_log!(server_id = %self.server_id, error = %e, "Failed to read initialize response");
                 // Collect any stderr that might explain the issue
                 if let Some(stderr) = self.collect_stderr(1000) {
-                    eprintln!("DEBUG: Stderr during initialize: {}", stderr);
+                    crate::logging::log_payload(&self.server_id, "stderr", || stderr);
                 }
                 // Don't fail the connection, some servers might not respond immediately
             }
@@ -505,255 +790,93 @@ impl MCPProcess {
             "method": "notifications/initialized"
         });
 
-        eprintln!(
-            "DEBUG: Sending initialized notification to server {}",
-            self.server_id
-        );
         self.send_message_sync(initialized_notification)?;
-        eprintln!("DEBUG: Initialized notification sent successfully");
 
-        eprintln!(
-            "DEBUG: MCP initialization completed for server {}",
-            self.server_id
-        );
+        debug_log!(server_id = %self.server_id, "MCP initialization completed");
         Ok(())
     }
 
     pub fn send_message_sync(
-        &mut self,
+        &self,
         message: serde_json::Value,
     ) -> Result<(), MCPClientError> {
-        let stdin = self.stdin.as_mut().ok_or_else(|| {
-            MCPClientError::new(
-                ErrorCategory::Connection,
-                "NO_STDIN",
-                "MCP process not started or stdin not available",
-            )
-            .with_details("Cannot send message to MCP server without stdin pipe")
-            .with_suggestions(vec![
-                "Ensure the MCP server process is running",
-                "Check that the server was started correctly",
-                "Try reconnecting to the server",
-            ])
-        })?;
-
-        let message_str = serde_json::to_string(&message).map_err(|e| {
-            MCPClientError::new(
-                ErrorCategory::Protocol,
-                "JSON_SERIALIZE_FAILED",
-                "Failed to serialize JSON-RPC message",
-            )
-            .with_details(&e.to_string())
-            .with_suggestions(vec![
-                "Check message format is valid JSON",
-                "Verify message structure follows JSON-RPC spec",
-            ])
-        })?;
-
-        eprintln!(
-            "DEBUG: Sending to MCP server {}: {}",
-            self.server_id, message_str
-        );
-
-        writeln!(stdin, "{}", message_str).map_err(|e| {
-            MCPClientError::new(
-                ErrorCategory::Connection,
-                "WRITE_FAILED",
-                "Failed to write message to MCP process",
-            )
-            .with_details(&e.to_string())
-            .with_suggestions(vec![
-                "Check if the MCP server process is still running",
-                "Verify the process stdin pipe is not broken",
-                "Try reconnecting to the server",
-            ])
-        })?;
-
-        stdin.flush().map_err(|e| {
-            MCPClientError::new(
-                ErrorCategory::Connection,
-                "FLUSH_FAILED",
-                "Failed to flush stdin buffer",
-            )
-            .with_details(&e.to_string())
-            .with_suggestions(vec![
-                "Check if the MCP server process is still running",
-                "Try reconnecting to the server",
-            ])
-        })?;
-
-        Ok(())
+        match self.channel {
+            Some(ref channel) => channel.send(&message),
+            None => write_message(&self.server_id, &self.stdin, &self.redactor, self.traffic.as_ref(), &message),
+        }
     }
 
+    /// Wait for the response to a request sent with `send_message_sync`
     pub fn read_response(
-        &mut self,
+        &self,
         expected_id: u64,
         timeout_ms: u64,
     ) -> Result<serde_json::Value, MCPClientError> {
-        let stdout = self.stdout.as_mut().ok_or_else(|| {
-            MCPClientError::new(
-                ErrorCategory::Connection,
-                "NO_STDOUT",
-                "MCP process stdout not available",
-            )
-            .with_details("Cannot read response from MCP server without stdout pipe")
-            .with_suggestions(vec![
-                "Ensure the MCP server process is running",
-                "Check that the server was started correctly",
-                "Try reconnecting to the server",
-            ])
-        })?;
-
-        // Try to read a response with timeout
-        let start_time = std::time::Instant::now();
-        let timeout = Duration::from_millis(timeout_ms);
-        let mut all_output = Vec::new();
-
-        eprintln!(
-            "DEBUG: Starting to read response for ID {} with {}ms timeout",
-            expected_id, timeout_ms
-        );
+        self.channel()?.read_response(expected_id, timeout_ms)
+    }
 
-        while start_time.elapsed() < timeout {
-            // Try to read a line (non-blocking would be better, but this is simpler for now)
-            let mut line = String::new();
-            match stdout.read_line(&mut line) {
-                Ok(0) => {
-                    eprintln!(
-                        "DEBUG: MCP process closed stdout - collected {} lines total",
-                        all_output.len()
-                    );
-                    if !all_output.is_empty() {
-                        eprintln!(
-                            "DEBUG: All stdout output received before close: {:?}",
-                            all_output
-                        );
-                    }
-                    return Err(MCPClientError::new(
-                        ErrorCategory::Connection,
-                        "STDOUT_CLOSED",
-                        "MCP process closed stdout unexpectedly",
-                    )
-                    .with_details("The server terminated the connection")
-                    .with_suggestions(vec![
-                        "Check server logs for errors",
-                        "Verify server configuration is correct",
-                        "Try reconnecting to the server",
-                    ]));
-                }
-                Ok(bytes_read) => {
-                    eprintln!(
-                        "DEBUG: Read {} bytes from stdout: '{}'",
-                        bytes_read,
-                        line.trim()
-                    );
-                    let line = line.trim();
-                    if line.is_empty() {
-                        eprintln!("DEBUG: Skipping empty line");
-                        continue;
-                    }
+    /// Send an MCP `ping` and return the round-trip time.
+    /// Any response counts, including a JSON-RPC error from servers without ping support.
+    pub fn ping(&self, timeout_ms: u64) -> Result<Duration, MCPClientError> {
+        self.channel()?.ping(timeout_ms)
+    }
 
-                    // Store all output for debugging
-                    all_output.push(line.to_string());
+    /// Exit status of the process if it has exited
+    pub fn exit_status(&mut self) -> Result<Option<std::process::ExitStatus>, std::io::Error> {
+        match self.lock_child() {
+            Some(mut child) => child.try_wait(),
+            None => Ok(in_process_status(self.in_process_exit.as_ref())),
+        }
+    }
 
-                    eprintln!(
-                        "DEBUG: Received from MCP server {} (line {}): {}",
-                        self.server_id,
-                        all_output.len(),
-                        line
-                    );
+    /// Watch the process in a background thread and call `on_exit` as soon as it
+    /// exits on its own. Stopping the process through `stop` ends the watcher quietly.
+    pub fn watch_exit<F>(&self, on_exit: F)
+    where
+        F: FnOnce(ProcessExit) + Send + 'static,
+    {
+        let try_wait: Box<dyn Fn() -> std::io::Result<Option<std::process::ExitStatus>> + Send> =
+            match (self.process.clone(), self.in_process_exit.clone()) {
+                (Some(child), _) => Box::new(move || child.lock().unwrap_or_else(|e| e.into_inner()).try_wait()),
+                (None, Some(exit)) => Box::new(move || Ok(in_process_status(Some(&exit)))),
+                (None, None) => return,
+            };
+        let stop = self.watcher_stop.clone();
+        let tail = self.stderr_tail.clone();
+        let server_id = self.server_id.clone();
 
-                    // Try to parse as JSON
-                    match serde_json::from_str::<serde_json::Value>(line) {
-                        Ok(json) => {
-                            eprintln!("DEBUG: Successfully parsed JSON: {}", json);
-                            // Check if this is the response we're looking for
-                            if let Some(response_id) = json.get("id") {
-                                eprintln!("DEBUG: JSON has ID field: {}", response_id);
-                                if response_id.as_u64() == Some(expected_id) {
-                                    eprintln!(
-                                        "DEBUG: Found matching response for ID {}",
-                                        expected_id
-                                    );
-                                    return Ok(json);
-                                } else {
-                                    eprintln!(
-                                        "DEBUG: Got response for different ID: {} (expected {})",
-                                        response_id, expected_id
-                                    );
-                                    continue;
-                                }
-                            } else {
-                                eprintln!(
-                                    "DEBUG: Got JSON without ID (probably a notification): {}",
-                                    line
-                                );
-                                continue;
-                            }
-                        }
-                        Err(e) => {
-                            eprintln!(
-                                "DEBUG: Failed to parse JSON response: {} - line was: '{}'",
-                                e, line
-                            );
-                            eprintln!("DEBUG: Raw bytes: {:?}", line.as_bytes());
-                            continue;
-                        }
+        std::thread::spawn(move || loop {
+            if stop.load(Ordering::SeqCst) {
+                return;
+            }
+            match try_wait() {
+                Ok(None) => std::thread::sleep(EXIT_WATCH_INTERVAL),
+                Ok(Some(status)) => {
+                    // Give the stderr reader a moment to pick up the final lines
+                    std::thread::sleep(Duration::from_millis(50));
+                    if stop.load(Ordering::SeqCst) {
+                        return;
                     }
+                    let stderr_tail = tail
+                        .lock()
+                        .map(|tail| tail.iter().cloned().collect())
+                        .unwrap_or_default();
+                    let exit = ProcessExit::from_status(&status, stderr_tail);
+                    debug_log!(server_id = %server_id, exit = %exit.description, "Exit watcher noticed process exit");
+                    on_exit(exit);
+                    return;
                 }
                 Err(e) => {
-                    eprintln!(
-                        "DEBUG: Error reading from stdout: {} - collected {} lines so far",
-                        e,
-                        all_output.len()
-                    );
-                    if !all_output.is_empty() {
-                        eprintln!("DEBUG: All stdout output before error: {:?}", all_output);
-                    }
-                    return Err(MCPClientError::new(
-                        ErrorCategory::Connection,
-                        "READ_FAILED",
-                        "Failed to read from MCP process stdout",
-                    )
-                    .with_details(&e.to_string())
-                    .with_suggestions(vec![
-                        "Check if the MCP server process is still running",
-                        "Verify the process stdout pipe is not broken",
-                        "Try reconnecting to the server",
-                    ]));
+                    debug_log!(server_id = %server_id, error = %e, "Exit watcher failed to check status");
+                    return;
                 }
             }
-        }
-
-        // Small delay to prevent busy waiting when we loop again
-        if start_time.elapsed() < timeout {
-            std::thread::sleep(Duration::from_millis(10));
-        }
-
-        eprintln!(
-            "DEBUG: Timeout reached after {}ms - collected {} lines total",
-            timeout_ms,
-            all_output.len()
-        );
-        if !all_output.is_empty() {
-            eprintln!(
-                "DEBUG: All stdout output during timeout period: {:?}",
-                all_output
-            );
-        }
-
-        Err(
-            MCPClientError::connection_timeout("MCP server", timeout_ms).with_details(&format!(
-                "Expected response with ID {} but received {} lines with no match",
-                expected_id,
-                all_output.len()
-            )),
-        )
+        });
     }
 
     /// Check if the process is still running
     pub fn check_process_status(&mut self) -> Result<bool, std::io::Error> {
-        if let Some(child) = &mut self.process {
+        if let Some(mut child) = self.lock_child() {
             match child.try_wait() {
                 Ok(Some(_status)) => Ok(false), // Process has exited
                 Ok(None) => Ok(true), // Process is still running
@@ -764,14 +887,29 @@ impl MCPProcess {
         }
     }
 
-    pub fn stop(&mut self) {
-        if let Some(mut process) = self.process.take() {
-            let _ = process.kill();
-            let _ = process.wait();
+    /// Shut the process down gracefully: close stdin, wait, SIGTERM, wait, SIGKILL.
+    /// Returns the step that ended the process, or `None` if there was no process.
+    pub fn stop(&mut self) -> Option<ShutdownStep> {
+        self.watcher_stop.store(true, Ordering::SeqCst);
+        let step = self.process.take().map(|process| {
+            let mut child = process.lock().unwrap_or_else(|e| e.into_inner());
+            shutdown_child(&mut child, &mut self.lock_stdin(), &self.shutdown)
+        });
+        *self.lock_stdin() = None;
+        // Wake requests still waiting, in case the output is never closed
+        if let Some(channel) = self.channel.take() {
+            channel.responses.close(
+                MCPClientError::new(ErrorCategory::Connection, "PROCESS_STOPPED", "MCP process was stopped")
+                    .with_details("The connection was closed while the request was waiting for a response")
+                    .with_suggestions(vec!["Try reconnecting to the server"]),
+            );
+        }
+        self.in_process_exit = None;
+        match step {
+            Some(step) => debug_log!(server_id = %self.server_id, step = %step, "Stopped MCP process"),
+            None => debug_log!(server_id = %self.server_id, "Stopped MCP process"),
         }
-        self.stdin = None;
-        self.stdout = None;
-        eprintln!("DEBUG: Stopped MCP process for server {}", self.server_id);
+        step
     }
 }
 
@@ -781,376 +919,638 @@ impl Drop for MCPProcess {
     }
 }
 
-/// Global registry of MCP processes
-pub static MCP_PROCESSES: Lazy<Arc<Mutex<HashMap<String, MCPProcess>>>> =
-    Lazy::new(|| Arc::new(Mutex::new(HashMap::new())));
+fn no_stdout() -> MCPClientError {
+    MCPClientError::new(
+        ErrorCategory::Connection,
+        "NO_STDOUT",
+        "MCP process stdout not available",
+    )
+    .with_details("Cannot read response from MCP server without stdout pipe")
+    .with_suggestions(vec![
+        "Ensure the MCP server process is running",
+        "Check that the server was started correctly",
+        "Try reconnecting to the server",
+    ])
+}
 
-/// Start an MCP process for a specific server
-pub async fn start_mcp_process(
-    server_id: String,
-    command: String,
-    args: Vec<String>,
-) -> Result<(), MCPClientError> {
-    eprintln!(
-        "DEBUG: start_mcp_process called for server {} with command: {} {:?}",
-        server_id, command, args
-    );
+/// Responses read from a server, kept until the requests waiting for them
+/// pick them up
+#[derive(Default)]
+struct ResponseRouter {
+    state: Mutex<RouterState>,
+    changed: Condvar,
+}
 
-    // Stop existing process if any (do this in separate scope to release mutex)
-    {
-        let mut processes = MCP_PROCESSES.lock().unwrap();
-        if let Some(mut existing) = processes.remove(&server_id) {
-            eprintln!("DEBUG: Stopping existing process for server {}", server_id);
-            existing.stop();
-        }
+#[derive(Default)]
+struct RouterState {
+    /// Sent requests by id, with their response once it has arrived
+    waiting: HashMap<u64, Option<serde_json::Value>>,
+    /// Why nothing more can be read from the server
+    closed: Option<MCPClientError>,
+    lines_read: u64,
+}
+
+impl ResponseRouter {
+    fn lock(&self) -> MutexGuard<'_, RouterState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Keep the response to `id` once it arrives
+    fn expect(&self, id: u64) {
+        self.lock().waiting.entry(id).or_insert(None);
+    }
+
+    fn forget(&self, id: u64) {
+        self.lock().waiting.remove(&id);
+    }
+
+    fn line_read(&self) {
+        self.lock().lines_read += 1;
     }
 
-    // Create and start new process
-    let mut process = MCPProcess::new(server_id.clone());
+    /// Hand a response to its request. Returns false if no request waits for it.
+    fn deliver(&self, id: u64, response: serde_json::Value) -> bool {
+        let mut state = self.lock();
+        match state.waiting.get_mut(&id) {
+            Some(slot) => {
+                *slot = Some(response);
+                self.changed.notify_all();
+                true
+            }
+            None => false,
+        }
+    }
 
-    // Try to start the process
-    if let Err(mut start_error) = process.start(&command, &args).await {
-        // Collect any stderr that might explain the failure
-        if let Some(stderr) = process.collect_stderr(1000) {
-            start_error = start_error.with_details(&format!("Process stderr: {}", stderr));
+    /// Fail every waiting and future request with `error`
+    fn close(&self, error: MCPClientError) {
+        let mut state = self.lock();
+        if state.closed.is_none() {
+            state.closed = Some(error);
         }
-        return Err(start_error);
+        self.changed.notify_all();
     }
 
-    // Initialize the MCP connection
-    eprintln!(
-        "DEBUG: Initializing MCP connection for server {}",
-        server_id
-    );
-    if let Err(mut init_error) = process.send_initialize() {
-        // Wait a bit for any stderr to be captured
-        std::thread::sleep(Duration::from_millis(500));
+    fn is_open(&self) -> bool {
+        self.lock().closed.is_none()
+    }
 
-        // Collect stderr that might explain the initialization failure
-        if let Some(stderr) = process.collect_stderr(2000) {
-            init_error = init_error.with_details(&format!("Process stderr: {}", stderr));
+    fn status(&self) -> Result<String, String> {
+        let state = self.lock();
+        match state.closed {
+            Some(ref error) => Err(error.message.clone()),
+            None => Ok(format!("Read {} lines, {} requests waiting", state.lines_read, state.waiting.len())),
         }
-        return Err(init_error);
     }
 
-    // Insert into processes map
-    {
-        let mut processes = MCP_PROCESSES.lock().unwrap();
-        processes.insert(server_id.clone(), process);
+    /// Wait for the response to `id`, up to `timeout_ms`
+    fn wait(&self, id: u64, timeout_ms: u64) -> Result<serde_json::Value, MCPClientError> {
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+        let mut state = self.lock();
+        let lines_before = state.lines_read;
+        state.waiting.entry(id).or_insert(None);
+        loop {
+            if let Some(response) = state.waiting.get_mut(&id).and_then(Option::take) {
+                state.waiting.remove(&id);
+                return Ok(response);
+            }
+            if let Some(ref error) = state.closed {
+                let error = error.clone();
+                state.waiting.remove(&id);
+                return Err(error);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                state.waiting.remove(&id);
+                return Err(MCPClientError::connection_timeout("MCP server", timeout_ms).with_details(&format!(
+                    "Expected response with ID {} but received {} lines with no match",
+                    id,
+                    state.lines_read - lines_before
+                )));
+            }
+            state = self
+                .changed
+                .wait_timeout(state, deadline - now)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
     }
+}
 
-    eprintln!(
-        "DEBUG: MCP process successfully started and initialized for server {}",
-        server_id
-    );
-    Ok(())
+/// Writes to a running server and routes its responses back. A reader
+/// thread reads everything the server writes, so several requests can wait
+/// on the same server at once without holding any lock.
+#[derive(Clone)]
+pub(crate) struct ServerChannel {
+    server_id: String,
+    stdin: SharedStdin,
+    responses: Arc<ResponseRouter>,
+    message_counter: Arc<AtomicU32>,
+    redactor: SecretRedactor,
+    traffic: Option<TrafficRecorder>,
+    /// Version the server answered `initialize` with
+    protocol_version: Arc<OnceLock<String>>,
 }
 
-/// Stop an MCP process for a specific server
-pub fn stop_mcp_process(server_id: &str) {
-    eprintln!("DEBUG: stop_mcp_process called for server {}", server_id);
-    let mut processes = MCP_PROCESSES.lock().unwrap();
-    if let Some(mut process) = processes.remove(server_id) {
-        process.stop();
+impl ServerChannel {
+    pub(crate) fn next_message_id(&self) -> u32 {
+        self.message_counter.fetch_add(1, Ordering::SeqCst)
     }
-}
 
-/// List tools from a specific MCP server
-pub fn list_mcp_tools(server_id: &str) -> Result<serde_json::Value, MCPClientError> {
-    eprintln!("DEBUG: list_mcp_tools called for server {}", server_id);
+    /// Send a message; the response to a request is kept for `read_response`
+    pub(crate) fn send(&self, message: &serde_json::Value) -> Result<(), MCPClientError> {
+        let request_id = message
+            .get("id")
+            .and_then(|id| id.as_u64())
+            .filter(|_| message.get("method").is_some());
+        if let Some(id) = request_id {
+            self.responses.expect(id);
+        }
+        let sent = write_message(&self.server_id, &self.stdin, &self.redactor, self.traffic.as_ref(), message);
+        if let (Err(_), Some(id)) = (&sent, request_id) {
+            self.responses.forget(id);
+        }
+        sent
+    }
 
-    let mut processes = MCP_PROCESSES.lock().unwrap();
-    if let Some(process) = processes.get_mut(server_id) {
-        // Check if the process is still running
-        if let Some(child) = &mut process.process {
-            match child.try_wait() {
-                Ok(Some(status)) => {
-                    eprintln!(
-                        "DEBUG: MCP process for server {} has exited with status: {:?}",
-                        server_id, status
+    /// Wait for the response to the request with `expected_id`
+    pub(crate) fn read_response(&self, expected_id: u64, timeout_ms: u64) -> Result<serde_json::Value, MCPClientError> {
+        trace_log!(
+            server_id = %self.server_id,
+            request_id = expected_id,
+            timeout_ms,
+            "Waiting for response"
+        );
+        let response = self.responses.wait(expected_id, timeout_ms);
+        match response {
+            Ok(ref json) => debug_log!(
+                server_id = %self.server_id,
+                request_id = expected_id,
+                is_error = json.get("error").is_some(),
+                "Received response"
+            ),
+            Err(ref e) => debug_log!(
+                server_id = %self.server_id,
+                request_id = expected_id,
+                timeout_ms,
+                error = %e,
+                "No response"
+            ),
+        }
+        response
+    }
+
+    /// Send an MCP `ping` and return the round-trip time
+    pub(crate) fn ping(&self, timeout_ms: u64) -> Result<Duration, MCPClientError> {
+        let message_id = self.next_message_id();
+        let ping_message = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": message_id,
+            "method": "ping"
+        });
+
+        let start_time = Instant::now();
+        self.send(&ping_message)?;
+        self.read_response(message_id as u64, timeout_ms)?;
+        Ok(start_time.elapsed())
+    }
+
+    /// Read the server's output until it is closed
+    fn read_messages(&self, mut output: BufReader<Box<dyn ServerOutput>>, handlers: &ClientHandlers) {
+        let mut workers = RequestWorkers::new(self.clone(), handlers.clone());
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match output.read_line(&mut line) {
+                Ok(0) => {
+                    debug_log!(server_id = %self.server_id, "MCP process closed stdout");
+                    self.responses.close(
+                        MCPClientError::new(
+                            ErrorCategory::Connection,
+                            "STDOUT_CLOSED",
+                            "MCP process closed stdout unexpectedly",
+                        )
+                        .with_details("The server terminated the connection")
+                        .with_suggestions(vec![
+                            "Check server logs for errors",
+                            "Verify server configuration is correct",
+                            "Try reconnecting to the server",
+                        ]),
                     );
-                    return Err(MCPClientError::new(
-                        ErrorCategory::Connection,
-                        "PROCESS_EXITED",
-                        &format!("MCP process for server {} has exited", server_id),
-                    )
-                    .with_details(&format!("Process exit status: {:?}", status))
-                    .with_suggestions(vec![
-                        "Check server logs for errors",
-                        "Verify server configuration is correct",
-                        "Try reconnecting to the server",
-                    ]));
+                    return;
                 }
-                Ok(None) => {
-                    eprintln!(
-                        "DEBUG: MCP process for server {} is still running",
-                        server_id
-                    );
+                Ok(bytes_read) => {
+                    let line = line.trim();
+                    if line.is_empty() {
+                        continue;
+                    }
+
+                    // Count lines for the timeout diagnostics
+                    self.responses.line_read();
+                    trace_log!(server_id = %self.server_id, bytes = bytes_read, "Received line");
+                    crate::logging::log_payload(&self.server_id, "received", || self.redactor.redact(line));
+
+                    match serde_json::from_str::<serde_json::Value>(line) {
+                        Ok(json) => self.receive(json, &mut workers),
+                        Err(e) => warn_log!(server_id = %self.server_id, error = %e, "Ignoring non-JSON output"),
+                    }
                 }
                 Err(e) => {
-                    eprintln!(
-                        "DEBUG: Error checking process status for server {}: {}",
-                        server_id, e
+                    debug_log!(server_id = %self.server_id, error = %e, "Error reading from stdout");
+                    self.responses.close(
+                        MCPClientError::new(
+                            ErrorCategory::Connection,
+                            "READ_FAILED",
+                            "Failed to read from MCP process stdout",
+                        )
+                        .with_details(&e.to_string())
+                        .with_suggestions(vec![
+                            "Check if the MCP server process is still running",
+                            "Verify the process stdout pipe is not broken",
+                            "Try reconnecting to the server",
+                        ]),
                     );
-                    return Err(MCPClientError::new(
-                        ErrorCategory::System,
-                        "STATUS_CHECK_FAILED",
-                        "Error checking MCP process status",
-                    )
-                    .with_details(&e.to_string())
-                    .with_suggestions(vec![
-                        "Try reconnecting to the server",
-                        "Restart the application if the issue persists",
-                    ]));
+                    return;
                 }
             }
         }
+    }
 
-        let message_id = process.next_message_id();
-        let list_tools_message = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": message_id,
-            "method": "tools/list",
-            "params": {}
-        });
-
-        // Send the message
-        if let Err(e) = process.send_message_sync(list_tools_message) {
-            return Err(e);
+    /// Route one message from the server
+    fn receive(&self, json: serde_json::Value, workers: &mut RequestWorkers) {
+        if let Some(ref traffic) = self.traffic {
+            traffic.record(TrafficDirection::Received, &self.redactor.redact_value(&json));
         }
 
-        // Read the response
-        match process.read_response(message_id as u64, 5000) {
-            // 5 second timeout
-            Ok(response) => {
-                eprintln!(
-                    "DEBUG: Got tools response for server {}: {}",
-                    server_id, response
-                );
+        // Servers may send their own requests at any time
+        if json.get("method").is_some() && json.get("id").is_some() {
+            workers.answer(json);
+            return;
+        }
 
-                // Extract the result from the JSON-RPC response
-                if let Some(result) = response.get("result") {
-                    Ok(result.clone())
-                } else if let Some(error) = response.get("error") {
-                    Err(MCPClientError::protocol_error(&format!(
-                        "MCP server returned error: {}",
-                        error
-                    )))
-                } else {
-                    Err(MCPClientError::protocol_error(
-                        "Invalid JSON-RPC response: missing result and error",
-                    ))
+        match json.get("id").cloned() {
+            Some(response_id) => {
+                let delivered = response_id
+                    .as_u64()
+                    .is_some_and(|id| self.responses.deliver(id, json));
+                if !delivered {
+                    debug_log!(
+                        server_id = %self.server_id,
+                        request_id = %response_id,
+                        "Skipping response no request is waiting for"
+                    );
                 }
             }
-            Err(e) => {
-                return Err(e);
-            }
+            None => debug_log!(
+                server_id = %self.server_id,
+                method = json.get("method").and_then(|m| m.as_str()),
+                "Received notification"
+            ),
         }
-    } else {
-        return Err(MCPClientError::new(
-            ErrorCategory::Connection,
-            "NO_PROCESS",
-            &format!("No active MCP process found for server {}", server_id),
-        )
-        .with_suggestions(vec![
-            "Ensure the server is connected",
-            "Try connecting to the server first",
-            "Check that the server ID is correct",
-        ]));
     }
 }
 
-/// Execute a tool on a specific MCP server
-pub fn execute_mcp_tool(
-    server_id: &str,
-    tool_name: &str,
-    arguments: serde_json::Value,
-) -> Result<(serde_json::Value, u64), MCPClientError> {
-    eprintln!(
-        "DEBUG: execute_mcp_tool called for server {} tool {} with args: {}",
-        server_id, tool_name, arguments
-    );
+/// Answers the requests a server sends with the registered handlers, on up
+/// to `SERVER_REQUEST_WORKERS` threads started as needed. Handlers run off
+/// the reader thread, so one waiting on the user or a model doesn't hold up
+/// reading from the server, and a server flooding the client with requests
+/// gets them refused once the queue is full.
+struct RequestWorkers {
+    channel: ServerChannel,
+    handlers: ClientHandlers,
+    sender: SyncSender<serde_json::Value>,
+    receiver: Arc<Mutex<Receiver<serde_json::Value>>>,
+    started: usize,
+    idle: Arc<AtomicUsize>,
+}
 
-    let start_time = std::time::Instant::now();
-    let mut processes = MCP_PROCESSES.lock().unwrap();
-    if let Some(process) = processes.get_mut(server_id) {
-        // Check if the process is still running
-        if let Some(child) = &mut process.process {
-            match child.try_wait() {
-                Ok(Some(status)) => {
-                    eprintln!(
-                        "DEBUG: MCP process for server {} has exited with status: {:?}",
-                        server_id, status
-                    );
-                    return Err(MCPClientError::new(
-                        ErrorCategory::Connection,
-                        "PROCESS_EXITED",
-                        &format!("MCP process for server {} has exited", server_id),
-                    )
-                    .with_details(&format!("Process exit status: {:?}", status))
-                    .with_suggestions(vec![
-                        "Check server logs for errors",
-                        "Verify server configuration is correct",
-                        "Try reconnecting to the server",
-                    ]));
-                }
-                Ok(None) => {
-                    eprintln!(
-                        "DEBUG: MCP process for server {} is still running",
-                        server_id
-                    );
-                }
-                Err(e) => {
-                    eprintln!(
-                        "DEBUG: Error checking process status for server {}: {}",
-                        server_id, e
-                    );
-                    return Err(MCPClientError::new(
-                        ErrorCategory::System,
-                        "STATUS_CHECK_FAILED",
-                        "Error checking MCP process status",
-                    )
-                    .with_details(&e.to_string())
-                    .with_suggestions(vec![
-                        "Try reconnecting to the server",
-                        "Restart the application if the issue persists",
-                    ]));
-                }
-            }
+impl RequestWorkers {
+    fn new(channel: ServerChannel, handlers: ClientHandlers) -> Self {
+        let (sender, receiver) = sync_channel(SERVER_REQUEST_QUEUE);
+        Self {
+            channel,
+            handlers,
+            sender,
+            receiver: Arc::new(Mutex::new(receiver)),
+            started: 0,
+            idle: Arc::new(AtomicUsize::new(0)),
         }
+    }
 
-        let message_id = process.next_message_id();
-        let call_tool_message = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": message_id,
-            "method": "tools/call",
-            "params": {
-                "name": tool_name,
-                "arguments": arguments
+    /// Queue a request for the next free worker, or refuse it if too many are waiting
+    fn answer(&mut self, request: serde_json::Value) {
+        debug_log!(
+            server_id = %self.channel.server_id,
+            method = request.get("method").and_then(|m| m.as_str()),
+            request_id = %request.get("id").unwrap_or(&serde_json::Value::Null),
+            "Answering server request"
+        );
+        if self.idle.load(Ordering::SeqCst) == 0 && self.started < SERVER_REQUEST_WORKERS {
+            self.start_worker();
+        }
+        let request = match self.sender.try_send(request) {
+            Ok(()) => return,
+            Err(TrySendError::Full(request)) | Err(TrySendError::Disconnected(request)) => request,
+        };
+        warn_log!(server_id = %self.channel.server_id, "Refusing server request, too many are waiting");
+        let response = crate::handlers::busy_response(&request);
+        if let Err(e) = self.channel.send(&response) {
+            debug_log!(server_id = %self.channel.server_id, error = %e, "Failed to answer server request");
+        }
+    }
+
+    /// Start a worker that runs until the reader, and with it the sender, is gone
+    fn start_worker(&mut self) {
+        self.started += 1;
+        let channel = self.channel.clone();
+        let handlers = self.handlers.clone();
+        let receiver = self.receiver.clone();
+        let idle = self.idle.clone();
+        std::thread::spawn(move || loop {
+            idle.fetch_add(1, Ordering::SeqCst);
+            let request = receiver.lock().unwrap_or_else(|e| e.into_inner()).recv();
+            idle.fetch_sub(1, Ordering::SeqCst);
+            let Ok(request) = request else {
+                return;
+            };
+            let response = match channel.protocol_version.get() {
+                Some(version) => handlers.for_protocol(version).respond(&channel.server_id, &request),
+                None => handlers.respond(&channel.server_id, &request),
+            };
+            if let Err(e) = channel.send(&response) {
+                debug_log!(server_id = %channel.server_id, error = %e, "Failed to answer server request");
             }
         });
+    }
+}
 
-        eprintln!("DEBUG: Sending tool call message: {}", call_tool_message);
-
-        // Send the message
-        if let Err(e) = process.send_message_sync(call_tool_message) {
-            return Err(e);
-        }
-
-        // Read the response
-        match process.read_response(message_id as u64, 10000) {
-            // 10 second timeout for tool execution
-            Ok(response) => {
-                let duration_ms = start_time.elapsed().as_millis() as u64;
-                eprintln!(
-                    "DEBUG: Got tool response for server {} in {}ms: {}",
-                    server_id, duration_ms, response
-                );
+/// Write one JSON-RPC message to the server's stdin
+fn write_message(
+    server_id: &str,
+    stdin: &SharedStdin,
+    redactor: &SecretRedactor,
+    traffic: Option<&TrafficRecorder>,
+    message: &serde_json::Value,
+) -> Result<(), MCPClientError> {
+    let mut stdin = stdin.lock().unwrap_or_else(|e| e.into_inner());
+    let stdin = stdin.as_mut().ok_or_else(|| {
+        MCPClientError::new(
+            ErrorCategory::Connection,
+            "NO_STDIN",
+            "MCP process not started or stdin not available",
+        )
+        .with_details("Cannot send message to MCP server without stdin pipe")
+        .with_suggestions(vec![
+            "Ensure the MCP server process is running",
+            "Check that the server was started correctly",
+            "Try reconnecting to the server",
+        ])
+    })?;
+
+    let message_str = serde_json::to_string(message).map_err(|e| {
+        MCPClientError::new(
+            ErrorCategory::Protocol,
+            "JSON_SERIALIZE_FAILED",
+            "Failed to serialize JSON-RPC message",
+        )
+        .with_details(&e.to_string())
+        .with_suggestions(vec![
+            "Check message format is valid JSON",
+            "Verify message structure follows JSON-RPC spec",
+        ])
+    })?;
+
+    debug_log!(
+        server_id = %server_id,
+        method = message.get("method").and_then(|m| m.as_str()),
+        request_id = %message.get("id").unwrap_or(&serde_json::Value::Null),
+        bytes = message_str.len(),
+        "Sending message"
+    );
+    crate::logging::log_payload(server_id, "sent", || redactor.redact(&message_str));
 
-                // Extract the result from the JSON-RPC response
-                if let Some(result) = response.get("result") {
-                    Ok((result.clone(), duration_ms))
-                } else if let Some(error) = response.get("error") {
-                    Err(MCPClientError::new(
-                        ErrorCategory::Protocol,
-                        "TOOL_EXECUTION_ERROR",
-                        &format!("Tool '{}' execution failed", tool_name),
-                    )
-                    .with_details(&format!("MCP server returned error: {}", error))
-                    .with_suggestions(vec![
-                        "Check the tool parameters are correct",
-                        "Verify the tool exists on this server",
-                        "Review server logs for more details",
-                    ]))
-                } else {
-                    Err(MCPClientError::protocol_error(
-                        "Invalid JSON-RPC response: missing result and error",
-                    ))
-                }
-            }
-            Err(e) => {
-                return Err(e);
-            }
-        }
-    } else {
-        return Err(MCPClientError::new(
+    writeln!(stdin, "{}", message_str).map_err(|e| {
+        MCPClientError::new(
             ErrorCategory::Connection,
-            "NO_PROCESS",
-            &format!("No active MCP process found for server {}", server_id),
+            "WRITE_FAILED",
+            "Failed to write message to MCP process",
         )
+        .with_details(&e.to_string())
         .with_suggestions(vec![
-            "Ensure the server is connected",
-            "Try connecting to the server first",
-            "Check that the server ID is correct",
-        ]));
+            "Check if the MCP server process is still running",
+            "Verify the process stdin pipe is not broken",
+            "Try reconnecting to the server",
+        ])
+    })?;
+
+    stdin.flush().map_err(|e| {
+        MCPClientError::new(
+            ErrorCategory::Connection,
+            "FLUSH_FAILED",
+            "Failed to flush stdin buffer",
+        )
+        .with_details(&e.to_string())
+        .with_suggestions(vec![
+            "Check if the MCP server process is still running",
+            "Try reconnecting to the server",
+        ])
+    })?;
+
+    if let Some(traffic) = traffic {
+        traffic.record(TrafficDirection::Sent, &redactor.redact_value(message));
     }
+
+    Ok(())
 }
 
-/// Check if a server has an active MCP process
-pub fn is_mcp_process_running(server_id: &str) -> bool {
-    let processes = MCP_PROCESSES.lock().unwrap();
-    processes.contains_key(server_id)
+/// Run the shutdown sequence on a child process, reap it and clean up
+/// anything left in its process group
+fn shutdown_child(
+    child: &mut Child,
+    stdin: &mut Option<Box<dyn Write + Send>>,
+    config: &ShutdownConfig,
+) -> ShutdownStep {
+    let step = stop_leader(child, stdin, config);
+    #[cfg(unix)]
+    terminate_process_group(child.id(), config.terminate_timeout());
+    step
 }
 
-/// Get connection status for all servers
-pub fn get_all_server_connection_statuses() -> HashMap<String, bool> {
-    let mut statuses = HashMap::new();
-    let mut processes = MCP_PROCESSES.lock().unwrap();
-
-    // Clone the keys to avoid borrowing issues
-    let server_ids: Vec<String> = processes.keys().cloned().collect();
-
-    for server_id in server_ids {
-        if let Some(process) = processes.get_mut(&server_id) {
-            // Check if the process is still running
-            let is_running = if let Some(child) = &mut process.process {
-                match child.try_wait() {
-                    Ok(Some(_)) => {
-                        // Process has exited, remove it from registry
-                        eprintln!("DEBUG: Removing dead process for server {}", server_id);
-                        false
-                    }
-                    Ok(None) => true, // Still running
-                    Err(_) => false,  // Error checking, assume dead
-                }
-            } else {
-                false // No process
-            };
+fn stop_leader(
+    child: &mut Child,
+    stdin: &mut Option<Box<dyn Write + Send>>,
+    config: &ShutdownConfig,
+) -> ShutdownStep {
+    if let Ok(Some(_)) = child.try_wait() {
+        return ShutdownStep::AlreadyExited;
+    }
 
-            if is_running {
-                statuses.insert(server_id.clone(), true);
-            } else {
-                // Remove dead process from registry
-                drop(processes.get_mut(&server_id)); // Release the reference
-                let mut processes_mut = MCP_PROCESSES.lock().unwrap();
-                if let Some(mut dead_process) = processes_mut.remove(&server_id) {
-                    dead_process.stop();
-                }
-                drop(processes_mut); // Release the lock
-                statuses.insert(server_id, false);
-                // Re-acquire lock for next iteration
-                processes = MCP_PROCESSES.lock().unwrap();
-            }
+    // Closing stdin tells a well-behaved server to exit
+    *stdin = None;
+    if wait_for_exit(child, config.stdin_timeout()) {
+        return ShutdownStep::StdinClosed;
+    }
+
+    #[cfg(unix)]
+    {
+        if signal_process_group(child.id(), libc::SIGTERM)
+            && wait_for_exit(child, config.terminate_timeout())
+        {
+            return ShutdownStep::Terminated;
         }
+        signal_process_group(child.id(), libc::SIGKILL);
     }
 
-    statuses
+    let _ = child.kill();
+    let _ = child.wait();
+    ShutdownStep::Killed
 }
 
-/// Cleanup all MCP processes on application shutdown
-pub fn cleanup_all_mcp_processes() {
-    eprintln!("DEBUG: Cleaning up all MCP processes...");
-    let mut processes = MCP_PROCESSES.lock().unwrap();
-    let server_ids: Vec<String> = processes.keys().cloned().collect();
-
-    for server_id in server_ids {
-        eprintln!("DEBUG: Stopping MCP process for server {}", server_id);
-        if let Some(mut process) = processes.remove(&server_id) {
-            process.stop();
+/// Send a signal to the server's process group, falling back to the process itself
+#[cfg(unix)]
+fn signal_process_group(pid: u32, signal: libc::c_int) -> bool {
+    let pid = pid as libc::pid_t;
+    // SAFETY: kill(2) has no memory safety requirements; the group id is the
+    // server's pid because it was spawned with `process_group(0)`
+    unsafe { libc::kill(-pid, signal) == 0 || libc::kill(pid, signal) == 0 }
+}
+
+/// Terminate processes the server left behind in its group (e.g. node children
+/// of an npx wrapper): SIGTERM, wait up to `timeout`, then SIGKILL
+#[cfg(unix)]
+fn terminate_process_group(pid: u32, timeout: Duration) {
+    let pgid = -(pid as libc::pid_t);
+    // SAFETY: signal 0 only checks whether any process in the group exists
+    let group_alive = || unsafe { libc::kill(pgid, 0) == 0 };
+    if !group_alive() {
+        return;
+    }
+
+    debug_log!(pgid = pid, "Terminating leftover processes in group");
+    // SAFETY: see above
+    unsafe { libc::kill(pgid, libc::SIGTERM) };
+    let start_time = Instant::now();
+    while group_alive() && start_time.elapsed() < timeout {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    if group_alive() {
+        // SAFETY: see above
+        unsafe { libc::kill(pgid, libc::SIGKILL) };
+    }
+}
+
+/// Poll the child until it exits or `timeout` passes; true if it exited
+fn wait_for_exit(child: &mut Child, timeout: Duration) -> bool {
+    let start_time = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => return true,
+            Ok(None) => {}
+            Err(_) => return false,
+        }
+        if start_time.elapsed() >= timeout {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Exit status of an in-process transport that stopped with an exit code
+fn in_process_status(exit: Option<&Arc<Mutex<Option<i32>>>>) -> Option<std::process::ExitStatus> {
+    let code = (*exit?.lock().ok()?)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        Some(std::process::ExitStatus::from_raw((code & 0xff) << 8))
+    }
+    #[cfg(not(unix))]
+    {
+        use std::os::windows::process::ExitStatusExt;
+        Some(std::process::ExitStatus::from_raw(code as u32))
+    }
+}
+
+/// Human readable description of how a process exited
+pub fn describe_exit_status(status: &std::process::ExitStatus) -> String {
+    if let Some(code) = status.code() {
+        return format!("exited with code {}", code);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return format!("killed by signal {}", signal);
         }
     }
+    format!("exited with status {:?}", status)
+}
+
+// The free functions below are a thin facade over the plugin's `ConnectionRegistry`,
+// so Rust callers and the Tauri commands share the same connection state.
+
+/// Start an MCP process for a specific server
+pub async fn start_mcp_process(
+    server_id: String,
+    command: String,
+    args: Vec<String>,
+) -> Result<(), MCPClientError> {
+    debug_log!(server_id = %server_id, command = %command, "start_mcp_process called");
+    let registry = registry::shared_registry();
+    registry.connect(server_id, command, args).await
+}
 
-    eprintln!("DEBUG: All MCP processes cleaned up");
+/// Stop an MCP process for a specific server
+pub fn stop_mcp_process(server_id: &str) {
+    debug_log!(server_id, "stop_mcp_process called");
+    if let Err(e) = registry::shared_registry().disconnect(server_id) {
+        warn_log!(server_id, error = %e, "Failed to stop MCP process");
+    }
+}
+
+/// List tools from a specific MCP server
+pub fn list_mcp_tools(server_id: &str) -> Result<serde_json::Value, MCPClientError> {
+    debug_log!(server_id, "list_mcp_tools called");
+    registry::shared_registry().list_tools(server_id)
+}
+
+/// Execute a tool on a specific MCP server
+pub fn execute_mcp_tool(
+    server_id: &str,
+    tool_name: &str,
+    arguments: serde_json::Value,
+) -> Result<(serde_json::Value, u64), MCPClientError> {
+    debug_log!(server_id, tool_name, "execute_mcp_tool called");
+    registry::shared_registry().execute_tool(server_id, tool_name, arguments)
+}
+
+/// Check if a server has an active MCP process, i.e. its connection is in
+/// a state a process belongs to
+pub fn is_mcp_process_running(server_id: &str) -> bool {
+    registry::shared_registry()
+        .connection_statuses()
+        .iter()
+        .any(|info| info.server_id == server_id && info.status.is_active())
+}
+
+/// Get connection status for all servers with an active MCP process, the
+/// same servers `is_mcp_process_running` reports. The value tells whether
+/// the server is ready for requests.
+pub fn get_all_server_connection_statuses() -> HashMap<String, bool> {
+    registry::shared_registry()
+        .connection_statuses()
+        .into_iter()
+        .filter(|info| info.status.is_active())
+        .map(|info| {
+            let connected = info.status == ConnectionState::Ready;
+            (info.server_id, connected)
+        })
+        .collect()
+}
+
+/// Cleanup all MCP processes on application shutdown
+pub fn cleanup_all_mcp_processes() {
+    debug_log!("Cleaning up all MCP processes");
+    registry::shared_registry().disconnect_all(crate::DEFAULT_EXIT_TIMEOUT, "Application exiting");
+    debug_log!("All MCP processes cleaned up");
 }
 
 #[derive(Debug, Serialize, Deserialize)]
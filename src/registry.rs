@@ -1,21 +1,56 @@
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use once_cell::sync::Lazy;
+use std::time::{Duration, Instant};
 use serde::{Deserialize, Serialize};
-use crate::process::MCPProcess;
-use crate::error::MCPClientError;
+use crate::approval::{self, ApprovalDecision, ApprovalManager, ApprovalMode, ApprovalRequest, ApprovalResolved};
+use crate::audit::{AuditLog, ToolCallRecord};
+use crate::config::{ServerConfig, TransportKind};
+use crate::process::{MCPProcess, ProcessExit, ServerChannel, SpawnOptions};
+use crate::replay::{self, ReplaySession};
+use crate::error::{ErrorCategory, MCPClientError};
+use crate::handlers::ClientHandlers;
+use crate::limits::{RateLimitUsage, ServerLimiter};
+use crate::resources::{ResourceMonitorConfig, ResourceSampler, ResourceStats, ResourceThresholdEvent, ThresholdAction};
+use crate::sandbox::SandboxConfig;
+use crate::scope::ScopeRules;
+use crate::state::ConnectionState;
+use crate::liveness::{LatencyStats, LivenessConfig};
+use crate::policy::SpawnRequest;
+use crate::secrets::{self, SecretProvider};
+use crate::settings::PluginConfig;
+use crate::shutdown::ShutdownStep;
+use crate::traffic::{TrafficConfig, TrafficEntry, TrafficRecorder};
 use tauri::{AppHandle, Emitter, Runtime};
+use tracing::Instrument;
 
 /// Event types for real-time MCP connection updates
 pub const EVENT_CONNECTION_CHANGED: &str = "mcp://connection-changed";
 pub const EVENT_SERVER_CONNECTED: &str = "mcp://server-connected";
 pub const EVENT_SERVER_DISCONNECTED: &str = "mcp://server-disconnected";
 pub const EVENT_PROCESS_ERROR: &str = "mcp://process-error";
+pub const EVENT_SERVER_RECONNECTING: &str = "mcp://server-reconnecting";
+pub const EVENT_SERVER_UNRESPONSIVE: &str = "mcp://server-unresponsive";
+/// Emitted for every recorded message of servers with `traffic.emitEvents`
+pub const EVENT_TRAFFIC: &str = "mcp://traffic";
+/// Emitted when a tool call waits for the user's approval
+pub const EVENT_APPROVAL_REQUIRED: &str = "mcp://approval-required";
+/// Emitted when a pending approval is answered or times out
+pub const EVENT_APPROVAL_RESOLVED: &str = "mcp://approval-resolved";
+/// Emitted when a server stays over one of its resource thresholds
+pub const EVENT_RESOURCE_THRESHOLD: &str = "mcp://resource-threshold";
 
-/// Event payload for connection status changes
+/// Event payload for connection state transitions
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConnectionEvent {
     pub server_id: String,
-    pub status: String, // "connected", "disconnected", "error"
+    pub status: ConnectionState,
+    /// State the connection left; `None` for a server seen for the first time
+    pub previous_status: Option<ConnectionState>,
     pub reason: Option<String>,
     pub timestamp: u64,
     pub command: Option<String>,
@@ -28,16 +63,108 @@ pub struct ConnectionInfo {
     pub server_id: String,
     pub command: String,
     pub args: Vec<String>,
-    pub status: String,
+    pub status: ConnectionState,
     pub connected_at: Option<u64>, // Unix timestamp
+    /// Why the server last failed or went away, until it is ready again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// How the process ended, once it has exited on its own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_exit: Option<ProcessExit>,
+    /// Ping statistics when liveness checks are enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency: Option<LatencyStats>,
+    /// Use of the server's rate limits and concurrency quotas, if it has any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<RateLimitUsage>,
+    /// CPU, memory, thread and file descriptor samples when resource
+    /// monitoring is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resources: Option<ResourceStats>,
+}
+
+/// Span covering one JSON-RPC request; `request_id` is recorded once assigned
+fn request_span(server_id: &str, method: &str) -> tracing::Span {
+    tracing::debug_span!(
+        "mcp_request",
+        server_id,
+        method,
+        request_id = tracing::field::Empty
+    )
+}
+
+/// A connected server's process behind its own lock. Handles are cloned out
+/// of the process map, so talking to one server never holds the map lock.
+#[derive(Clone)]
+struct ProcessHandle {
+    /// Unique per connection, so background tasks can tell whether the
+    /// process they were started for has been replaced. Pids can't do that:
+    /// in-process transports have none.
+    generation: u64,
+    /// Fixed for the life of the process, readable without locking it
+    pid: Option<u32>,
+    process: Arc<Mutex<MCPProcess>>,
+}
+
+/// Source of `ProcessHandle::generation`
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(1);
+
+impl ProcessHandle {
+    fn new(process: MCPProcess) -> Self {
+        Self {
+            generation: NEXT_GENERATION.fetch_add(1, Ordering::Relaxed),
+            pid: process.pid(),
+            process: Arc::new(Mutex::new(process)),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MCPProcess> {
+        self.process.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn stop(&self) -> Option<ShutdownStep> {
+        self.lock().stop()
+    }
+}
+
+/// Restart bookkeeping for a single server
+#[derive(Default)]
+struct RestartState {
+    /// Identifies the running restart loop; cleared or replaced to stop it
+    token: Option<u64>,
+    attempts: u32,
+    last_restart: Option<Instant>,
 }
 
 /// Plugin-specific connection registry to track MCP server connections
 /// This runs independently from any main MCP system
+///
+/// Cloning is cheap and shares the same state, which lets background tasks
+/// (such as restart loops) hold on to the registry.
 pub struct ConnectionRegistry<R: Runtime = tauri::Wry> {
     connections: Arc<Mutex<HashMap<String, ConnectionInfo>>>,
-    processes: Arc<Mutex<HashMap<String, MCPProcess>>>,
+    processes: Arc<Mutex<HashMap<String, ProcessHandle>>>,
+    /// Spawn configuration of each connected server, reused for restarts
+    configs: Arc<Mutex<HashMap<String, ServerConfig>>>,
+    restarts: Arc<Mutex<HashMap<String, RestartState>>>,
+    restart_tokens: Arc<AtomicU64>,
     app_handle: Option<AppHandle<R>>,
+    secret_provider: Option<Arc<dyn SecretProvider>>,
+    settings: Arc<PluginConfig>,
+    handlers: ClientHandlers,
+    /// Message logs of servers with traffic recording, kept across reconnects
+    traffic: Arc<Mutex<HashMap<String, TrafficRecorder>>>,
+    /// Spawn decisions and tool calls
+    audit: AuditLog,
+    /// Tool calls waiting for approval and remembered decisions
+    approvals: ApprovalManager,
+    /// Rate limits and concurrency quotas of connected servers
+    limiters: Arc<Mutex<HashMap<String, Arc<ServerLimiter>>>>,
+    /// Tool annotations from `tools/list`, by server id and tool name
+    tool_annotations: Arc<Mutex<HashMap<String, HashMap<String, serde_json::Value>>>>,
+    /// Mock servers served over the mock transport, by server id
+    #[cfg(feature = "testing")]
+    mocks: Arc<Mutex<HashMap<String, crate::testing::MockServer>>>,
 }
 
 impl<R: Runtime> ConnectionRegistry<R> {
@@ -46,7 +173,20 @@ impl<R: Runtime> ConnectionRegistry<R> {
         Self {
             connections: Arc::new(Mutex::new(HashMap::new())),
             processes: Arc::new(Mutex::new(HashMap::new())),
+            configs: Arc::new(Mutex::new(HashMap::new())),
+            restarts: Arc::new(Mutex::new(HashMap::new())),
+            restart_tokens: Arc::new(AtomicU64::new(0)),
             app_handle: None,
+            secret_provider: None,
+            settings: Arc::new(PluginConfig::default()),
+            handlers: ClientHandlers::default(),
+            traffic: Arc::new(Mutex::new(HashMap::new())),
+            audit: AuditLog::new(),
+            approvals: ApprovalManager::new(),
+            limiters: Arc::new(Mutex::new(HashMap::new())),
+            tool_annotations: Arc::new(Mutex::new(HashMap::new())),
+            #[cfg(feature = "testing")]
+            mocks: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -55,220 +195,625 @@ impl<R: Runtime> ConnectionRegistry<R> {
         self.app_handle = Some(app_handle);
     }
 
+    /// Set the provider used to resolve secret references in server environments
+    pub fn set_secret_provider(&mut self, provider: Arc<dyn SecretProvider>) {
+        self.secret_provider = Some(provider);
+    }
+
+    /// Set the plugin-wide settings
+    pub fn set_settings(&mut self, settings: PluginConfig) {
+        self.settings = Arc::new(settings);
+    }
+
+    pub fn settings(&self) -> &PluginConfig {
+        &self.settings
+    }
+
+    /// Spawn decisions and tool calls
+    pub fn audit_log(&self) -> &AuditLog {
+        &self.audit
+    }
+
+    /// Tool calls waiting for approval and remembered decisions
+    pub fn approvals(&self) -> &ApprovalManager {
+        &self.approvals
+    }
+
+    /// Set the handlers answering requests sent by servers
+    pub fn set_handlers(&mut self, handlers: ClientHandlers) {
+        self.handlers = handlers;
+    }
+
     /// Emit a connection event if app handle is available
     fn emit_connection_event(&self, event: ConnectionEvent) {
         if let Some(ref app_handle) = self.app_handle {
-            eprintln!("DEBUG: About to emit connection event: {:?}", event);
-            if let Err(e) = app_handle.emit(EVENT_CONNECTION_CHANGED, &event) {
-                eprintln!("DEBUG: Failed to emit connection event: {}", e);
-            } else {
-                eprintln!("DEBUG: Successfully emitted connection event: {:?}", event);
+            debug_log!(
+                server_id = %event.server_id,
+                status = %event.status,
+                reason = ?event.reason,
+                "Emitting connection event"
+            );
+            if let Err(e) = app_handle.emit(&self.settings.event_name(EVENT_CONNECTION_CHANGED), &event) {
+                warn_log!(server_id = %event.server_id, error = %e, "Failed to emit connection event");
+            }
+
+            // Also emit the status-specific event
+            let specific_event = match event.status {
+                ConnectionState::Ready => Some(EVENT_SERVER_CONNECTED),
+                ConnectionState::Disconnected => Some(EVENT_SERVER_DISCONNECTED),
+                ConnectionState::Reconnecting => Some(EVENT_SERVER_RECONNECTING),
+                ConnectionState::Degraded => Some(EVENT_SERVER_UNRESPONSIVE),
+                ConnectionState::Failed => Some(EVENT_PROCESS_ERROR),
+                _ => None,
+            };
+            if let Some(name) = specific_event {
+                if let Err(e) = app_handle.emit(&self.settings.event_name(name), &event) {
+                    warn_log!(server_id = %event.server_id, event = name, error = %e, "Failed to emit event");
+                }
             }
         } else {
-            eprintln!("DEBUG: No app handle available, cannot emit event: {:?}", event);
+            trace_log!(server_id = %event.server_id, status = %event.status, "No app handle, event not emitted");
         }
     }
 
+    /// Move a server to `next` and emit the transition. `update` runs on the
+    /// entry as part of the transition. A server's first state is always
+    /// `Spawning`; transitions the state machine doesn't allow are refused.
+    fn transition(
+        &self,
+        server_id: &str,
+        next: ConnectionState,
+        reason: Option<String>,
+        update: impl FnOnce(&mut ConnectionInfo),
+    ) -> Result<(), MCPClientError> {
+        let event = {
+            let mut connections = self.connections.lock()
+                .map_err(|e| MCPClientError::system_error(&format!("Failed to lock connections: {}", e)))?;
+            let previous = match connections.get(server_id) {
+                Some(info) if !info.status.can_transition_to(next) => {
+                    debug_log!(server_id, from = %info.status, to = %next, "Refused connection state transition");
+                    return Err(MCPClientError::invalid_state_transition(server_id, Some(info.status), next));
+                }
+                Some(info) => Some(info.status),
+                None if next == ConnectionState::Spawning => None,
+                None => return Err(MCPClientError::invalid_state_transition(server_id, None, next)),
+            };
+            let info = connections.entry(server_id.to_string()).or_insert_with(|| ConnectionInfo {
+                server_id: server_id.to_string(),
+                command: String::new(),
+                args: Vec::new(),
+                status: next,
+                connected_at: None,
+                last_error: None,
+                last_exit: None,
+                latency: None,
+                usage: None,
+                resources: None,
+            });
+            info.status = next;
+            update(info);
+            ConnectionEvent {
+                server_id: server_id.to_string(),
+                status: next,
+                previous_status: previous,
+                reason,
+                timestamp: unix_timestamp(),
+                command: Some(info.command.clone()),
+                args: Some(info.args.clone()),
+            }
+        };
+        self.emit_connection_event(event);
+        Ok(())
+    }
+
+    fn connection_state(&self, server_id: &str) -> Option<ConnectionState> {
+        self.connections.lock().ok()?.get(server_id).map(|info| info.status)
+    }
+
     /// Get all current connection statuses
     pub fn get_connection_statuses(&self) -> Result<Vec<ConnectionInfo>, String> {
-        let connections = self.connections.lock()
-            .map_err(|e| format!("Failed to lock connections: {}", e))?;
-        
-        Ok(connections.values().cloned().collect())
+        let mut statuses: Vec<ConnectionInfo> = {
+            let connections = self.connections.lock()
+                .map_err(|e| format!("Failed to lock connections: {}", e))?;
+            connections.values().cloned().collect()
+        };
+        for info in &mut statuses {
+            info.usage = self.limiter(&info.server_id).and_then(|limiter| limiter.usage());
+        }
+        Ok(statuses)
     }
 
     /// Connect to an MCP server through the plugin
     pub async fn connect_server(&self, server_id: String, command: String, args: Vec<String>) -> Result<(), MCPClientError> {
-        eprintln!("DEBUG: Plugin connect_server called for {} with command: {} {:?}", server_id, command, args);
+        self.connect_server_with_config(server_id, ServerConfig::new(&command, args)).await
+    }
 
-        // Stop existing process if any (silently, without emitting events)
-        self.disconnect_server_silent(&server_id).await?;
+    /// Connect to an MCP server from a full server definition, resolving
+    /// secret references in its environment at spawn time
+    pub async fn connect_server_with_config(&self, server_id: String, config: ServerConfig) -> Result<(), MCPClientError> {
+        self.connect_server_in_scope(server_id, config, &ScopeRules::default()).await
+    }
 
-        // Create new MCPProcess
-        let mut process = MCPProcess::new(server_id.clone());
-        
-        // Start the process
-        match process.start(&command, &args).await {
-            Ok(()) => {
-                // Initialize MCP connection
-                process.send_initialize()?;
-                
-                // Store the process
-                {
-                    let mut processes = self.processes.lock()
-                        .map_err(|e| MCPClientError::system_error(&format!("Failed to lock processes: {}", e)))?;
-                    processes.insert(server_id.clone(), process);
-                }
+    /// Connect on behalf of a window: the server must match the spawn
+    /// entries of its capability scopes as well as the spawn policy
+    pub async fn connect_server_in_scope(
+        &self,
+        server_id: String,
+        config: ServerConfig,
+        rules: &ScopeRules,
+    ) -> Result<(), MCPClientError> {
+        // An explicit connect supersedes any pending automatic restart
+        self.cancel_restart(&server_id);
+        let span = tracing::debug_span!("mcp_connect", server_id = %server_id);
+        self.connect_inner(server_id, config, rules, ConnectionState::Failed).instrument(span).await
+    }
 
-                // Store connection info
-                let connection_info = ConnectionInfo {
-                    server_id: server_id.clone(),
-                    command: command.clone(),
-                    args: args.clone(),
-                    status: "connected".to_string(),
-                    connected_at: Some(std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs()),
-                };
+    /// Connect the server, moving it to `failed` if it can't be started
+    async fn connect_inner(
+        &self,
+        server_id: String,
+        mut config: ServerConfig,
+        rules: &ScopeRules,
+        failed: ConnectionState,
+    ) -> Result<(), MCPClientError> {
+        debug_log!(
+            server_id = %server_id,
+            command = %config.command,
+            env_keys = ?config.env.keys().collect::<Vec<_>>(),
+            "Connecting to server"
+        );
+
+        config.validate(&server_id)?;
+        self.settings.limits.validate("limits")?;
+        let transport = config.transport;
+        if transport == TransportKind::Stdio {
+            let request = SpawnRequest::from_config(&config);
+            let checked = self
+                .settings
+                .spawn_policy
+                .check(&request)
+                .and_then(|()| rules.check_spawn(&server_id, &request));
+            self.audit.record_spawn(&server_id, &request, checked.as_ref().copied());
+            checked?;
+        }
+        self.check_connection_limit(&server_id)?;
+        if config.restart.is_default() {
+            config.restart = self.settings.restart.clone();
+        }
+        let spawn_config = config.clone();
+        let liveness = config.liveness.clone();
+        let resources = config.resources.clone();
+        let shutdown = config.shutdown.clone();
+        let session_dir = self.settings.session_dir.as_deref();
+        let mut traffic = config.traffic.clone();
+        if let Some(ref path) = traffic.record_file {
+            traffic.record_file = Some(replay::session_path(session_dir, path)?);
+        }
+        let session = match transport {
+            TransportKind::Replay => {
+                replay::session_path(session_dir, Path::new(config.session.as_deref().unwrap_or_default()))?
+            }
+            _ => PathBuf::new(),
+        };
+        let sandbox = match transport {
+            TransportKind::Stdio => {
+                let sandbox = SandboxConfig::effective(&self.settings.sandbox, &config.sandbox)?;
+                sandbox.check()?;
+                sandbox
+            }
+            _ => SandboxConfig::default(),
+        };
+        let ServerConfig { command, args, env, cwd, .. } = config;
+
+        // Resolve secrets before touching any existing connection
+        let resolved_env = secrets::resolve_env(&env, self.secret_provider.as_deref())?;
+        let redactor = resolved_env.redactor.clone();
+        let options = SpawnOptions {
+            env: resolved_env.vars,
+            cwd: cwd.map(PathBuf::from),
+            sandbox,
+        };
+
+        // Stop existing process if any
+        self.stop_for_reconnect(&server_id).await?;
+        self.transition(&server_id, ConnectionState::Spawning, None, |info| {
+            info.command = command.clone();
+            info.args = args.clone();
+        })?;
+
+        let result = async {
+            // Create new MCPProcess
+            let mut process = MCPProcess::new(server_id.clone());
+            process.set_redactor(redactor.clone());
+            process.set_shutdown_config(shutdown);
+            process.set_handlers(self.handlers.clone());
+            process.set_request_timeout(self.settings.request_timeout_ms);
+            if traffic.enabled {
+                process.set_traffic_recorder(self.traffic_recorder(&server_id, traffic)?);
+            }
+
+            // Start the process, or play back a recording in its place
+            match transport {
+                TransportKind::Replay => ReplaySession::load(&session)
+                    .and_then(|session| process.start_replay(session)),
+                #[cfg(feature = "testing")]
+                TransportKind::Mock => self
+                    .mocks
+                    .lock()
+                    .ok()
+                    .and_then(|mocks| mocks.get(&server_id).cloned())
+                    .ok_or_else(|| {
+                        MCPClientError::configuration_error(&server_id, "No mock server registered for this id")
+                    })
+                    .and_then(|server| process.start_mock(server)),
+                _ => process.start_with_options(&command, &args, &options).await,
+            }?;
+
+            // Initialize MCP connection, unless the server was disconnected meanwhile
+            if let Err(e) = self.transition(&server_id, ConnectionState::Initializing, None, |_| {}) {
+                process.stop();
+                return Err(e);
+            }
+            process.send_initialize()?;
+
+            // Store the process
+            let handle = ProcessHandle::new(process);
+            let generation = handle.generation;
+            {
+                let mut processes = self.processes.lock()
+                    .map_err(|e| MCPClientError::system_error(&format!("Failed to lock processes: {}", e)))?;
+                processes.insert(server_id.clone(), handle);
+            }
 
-                {
-                    let mut connections = self.connections.lock()
-                        .map_err(|e| MCPClientError::system_error(&format!("Failed to lock connections: {}", e)))?;
-                    connections.insert(server_id.clone(), connection_info);
+            // A restarted server may have changed its tools
+            if let Ok(mut annotations) = self.tool_annotations.lock() {
+                annotations.remove(&server_id);
+            }
+            if let Ok(mut limiters) = self.limiters.lock() {
+                let limits = vec![self.settings.limits.clone(), spawn_config.limits.clone()];
+                limiters.insert(server_id.clone(), Arc::new(ServerLimiter::new(&server_id, limits)));
+            }
+
+            {
+                let mut configs = self.configs.lock()
+                    .map_err(|e| MCPClientError::system_error(&format!("Failed to lock configs: {}", e)))?;
+                configs.insert(server_id.clone(), spawn_config);
+            }
+
+            let ready = self.transition(&server_id, ConnectionState::Ready, None, |info| {
+                info.connected_at = Some(unix_timestamp());
+                info.last_error = None;
+                info.last_exit = None;
+                info.latency = liveness.enabled.then(LatencyStats::default);
+                info.resources = resources.enabled.then(ResourceStats::default);
+            });
+            if let Err(e) = ready {
+                self.forget_config(&server_id);
+                let process = self.processes.lock().ok().and_then(|mut processes| match processes.get(&server_id) {
+                    Some(process) if process.generation == generation => processes.remove(&server_id),
+                    _ => None,
+                });
+                if let Some(process) = process {
+                    process.stop();
                 }
+                return Err(e);
+            }
+            Ok(())
+        }
+        .await;
 
-                // Emit connection event
-                let event = ConnectionEvent {
-                    server_id: server_id.clone(),
-                    status: "connected".to_string(),
-                    reason: None,
-                    timestamp: std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs(),
-                    command: Some(command.clone()),
-                    args: Some(args.clone()),
-                };
-                self.emit_connection_event(event);
+        if let Err(e) = result {
+            let e = redactor.redact_error(e);
+            warn_log!(server_id = %server_id, error = %e, "Failed to connect to server");
+            // Keep the entry with the error, unless the connect was cut short
+            // by a disconnect
+            let connecting = self.connection_state(&server_id).is_some_and(|state| {
+                matches!(state, ConnectionState::Spawning | ConnectionState::Initializing)
+            });
+            if connecting {
+                let _ = self.transition(&server_id, failed, Some(e.message.clone()), |info| {
+                    info.last_error = Some(e.message.clone());
+                });
+            }
+            return Err(e);
+        }
 
-                eprintln!("DEBUG: Plugin successfully connected to server {}", server_id);
-                Ok(())
+        // Notice the process exiting even when nobody is calling tools
+        if let Some(process) = self.process_handle(&server_id) {
+            self.watch_process(&server_id, &process);
+            if liveness.enabled {
+                self.start_liveness_checks(&server_id, process.generation, liveness);
             }
-            Err(e) => {
-                eprintln!("DEBUG: Plugin failed to connect to server {}: {}", server_id, e);
-                Err(e)
+            if resources.enabled {
+                self.start_resource_monitor(&server_id, &process, resources);
             }
         }
+
+        info_log!(server_id = %server_id, "Connected to server");
+        Ok(())
     }
 
-    /// Disconnect from an MCP server silently (no events)
-    async fn disconnect_server_silent(&self, server_id: &str) -> Result<(), MCPClientError> {
-        eprintln!("DEBUG: Plugin disconnect_server_silent called for {}", server_id);
+    /// Connect to an in-process mock server. Restarts reuse the same mock.
+    #[cfg(feature = "testing")]
+    pub async fn connect_mock(
+        &self,
+        server_id: String,
+        server: crate::testing::MockServer,
+    ) -> Result<(), MCPClientError> {
+        self.mocks
+            .lock()
+            .map_err(|e| MCPClientError::system_error(&format!("Failed to lock mocks: {}", e)))?
+            .insert(server_id.clone(), server);
+        let config = ServerConfig {
+            transport: TransportKind::Mock,
+            ..ServerConfig::default()
+        };
+        self.connect_server_with_config(server_id, config).await
+    }
 
-        // Remove and stop the process
-        {
-            let mut processes = self.processes.lock()
-                .map_err(|e| MCPClientError::system_error(&format!("Failed to lock processes: {}", e)))?;
-            
-            if let Some(mut process) = processes.remove(server_id) {
-                process.stop();
-                eprintln!("DEBUG: Plugin silently stopped process for server {}", server_id);
-            }
+    /// Recorder for `server_id`, reusing the existing log so traffic from
+    /// before a reconnect or restart stays available
+    fn traffic_recorder(&self, server_id: &str, config: TrafficConfig) -> Result<TrafficRecorder, MCPClientError> {
+        let mut traffic = self.traffic.lock()
+            .map_err(|e| MCPClientError::system_error(&format!("Failed to lock traffic: {}", e)))?;
+        if let Some(recorder) = traffic.get(server_id) {
+            recorder.set_config(config);
+            recorder.start_session()?;
+            return Ok(recorder.clone());
         }
 
-        // Remove connection info
-        {
-            let mut connections = self.connections.lock()
-                .map_err(|e| MCPClientError::system_error(&format!("Failed to lock connections: {}", e)))?;
-            connections.remove(server_id);
+        let mut recorder = TrafficRecorder::new(server_id, config);
+        if let Some(app_handle) = self.app_handle.clone() {
+            let event_name = self.settings.event_name(EVENT_TRAFFIC);
+            recorder = recorder.with_sink(move |entry| {
+                if let Err(e) = app_handle.emit(&event_name, entry) {
+                    warn_log!(server_id = %entry.server_id, error = %e, "Failed to emit traffic event");
+                }
+            });
         }
+        recorder.start_session()?;
+        traffic.insert(server_id.to_string(), recorder.clone());
+        Ok(recorder)
+    }
 
-        // No event emission for silent disconnect
+    fn recorded_traffic(&self, server_id: &str) -> Result<TrafficRecorder, MCPClientError> {
+        let traffic = self.traffic.lock()
+            .map_err(|e| MCPClientError::system_error(&format!("Failed to lock traffic: {}", e)))?;
+        traffic.get(server_id).cloned().ok_or_else(|| {
+            MCPClientError::new(
+                ErrorCategory::Configuration,
+                "TRAFFIC_NOT_RECORDED",
+                &format!("No traffic recorded for server {}", server_id),
+            )
+            .with_suggestions(vec![
+                "Enable traffic recording in the server configuration",
+                "Reconnect the server after enabling it",
+            ])
+        })
+    }
+
+    /// Recorded JSON-RPC messages of a server, oldest first
+    pub fn traffic(&self, server_id: &str) -> Result<Vec<TrafficEntry>, MCPClientError> {
+        Ok(self.recorded_traffic(server_id)?.entries())
+    }
+
+    /// Recorded JSON-RPC messages of a server as JSON Lines
+    pub fn export_traffic(&self, server_id: &str) -> Result<String, MCPClientError> {
+        Ok(self.recorded_traffic(server_id)?.to_jsonl())
+    }
+
+    /// Drop the recorded messages of a server
+    pub fn clear_traffic(&self, server_id: &str) -> Result<(), MCPClientError> {
+        self.recorded_traffic(server_id)?.clear();
+        Ok(())
+    }
+
+    /// Refuse a new connection once `max_connections` servers are running.
+    /// Reconnecting an already running server doesn't count as a new one.
+    fn check_connection_limit(&self, server_id: &str) -> Result<(), MCPClientError> {
+        let Some(limit) = self.settings.max_connections else {
+            return Ok(());
+        };
+        let processes = self.processes.lock()
+            .map_err(|e| MCPClientError::system_error(&format!("Failed to lock processes: {}", e)))?;
+        if !processes.contains_key(server_id) && processes.len() >= limit {
+            return Err(MCPClientError::connection_limit_reached(limit));
+        }
+        Ok(())
+    }
+
+    /// Stop the server's current process before it is connected again
+    async fn stop_for_reconnect(&self, server_id: &str) -> Result<(), MCPClientError> {
+        // Remove the process, then stop it without holding the lock
+        let process = {
+            let mut processes = self.processes.lock()
+                .map_err(|e| MCPClientError::system_error(&format!("Failed to lock processes: {}", e)))?;
+            processes.remove(server_id)
+        };
+        if let Some(process) = process {
+            debug_log!(server_id, "Stopping process before reconnecting");
+            let _ = self.transition(server_id, ConnectionState::Disconnecting, None, |_| {});
+            let step = stop_process(process).await;
+            let reason = match step {
+                Some(step) => format!("Reconnecting ({})", step),
+                None => "Reconnecting".to_string(),
+            };
+            let _ = self.transition(server_id, ConnectionState::Disconnected, Some(reason), |_| {});
+        }
         Ok(())
     }
 
     /// Disconnect from an MCP server
     pub async fn disconnect_server(&self, server_id: &str) -> Result<(), MCPClientError> {
-        eprintln!("DEBUG: Plugin disconnect_server called for {}", server_id);
+        // The graceful shutdown can take a while, keep it off the async executor
+        let registry = self.clone();
+        let server_id = server_id.to_string();
+        tauri::async_runtime::spawn_blocking(move || registry.disconnect_server_blocking(&server_id))
+            .await
+            .map_err(|e| MCPClientError::system_error(&format!("Disconnect task failed: {}", e)))?
+    }
+
+    /// Disconnect from an MCP server, blocking the calling thread during shutdown
+    pub(crate) fn disconnect_server_blocking(&self, server_id: &str) -> Result<(), MCPClientError> {
+        debug_log!(server_id, "Disconnecting server");
 
-        // Remove and stop the process
-        {
+        // A user-requested disconnect is never followed by an automatic restart
+        self.cancel_restart(server_id);
+        if let Ok(mut configs) = self.configs.lock() {
+            configs.remove(server_id);
+        }
+
+        // Remove the process, then stop it without holding the lock
+        let process = {
             let mut processes = self.processes.lock()
                 .map_err(|e| MCPClientError::system_error(&format!("Failed to lock processes: {}", e)))?;
-            
-            if let Some(mut process) = processes.remove(server_id) {
-                process.stop();
-                eprintln!("DEBUG: Plugin stopped process for server {}", server_id);
+            processes.remove(server_id)
+        };
+        let step = match process {
+            Some(process) => {
+                let _ = self.transition(server_id, ConnectionState::Disconnecting, None, |_| {});
+                let step = process.stop();
+                info_log!(server_id, step = ?step, "Disconnected server");
+                step
             }
-        }
+            None => None,
+        };
 
-        // Remove connection info
-        {
-            let mut connections = self.connections.lock()
-                .map_err(|e| MCPClientError::system_error(&format!("Failed to lock connections: {}", e)))?;
-            connections.remove(server_id);
-        }
-
-        // Emit disconnection event
-        let event = ConnectionEvent {
-            server_id: server_id.to_string(),
-            status: "disconnected".to_string(),
-            reason: Some("User requested disconnection".to_string()),
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-            command: None,
-            args: None,
+        // Keep the entry, so the server still shows up as disconnected
+        let reason = match step {
+            Some(step) => format!("User requested disconnection ({})", step),
+            None => "User requested disconnection".to_string(),
         };
-        self.emit_connection_event(event);
+        let _ = self.transition(server_id, ConnectionState::Disconnected, Some(reason), |_| {});
 
         Ok(())
     }
 
+    /// Disconnect every server, e.g. when the app exits. Servers are stopped in
+    /// parallel and each shutdown sequence is shortened to fit within `deadline`.
+    pub async fn disconnect_all(&self, deadline: Duration, reason: &str) {
+        let registry = self.clone();
+        let reason = reason.to_string();
+        let result = tauri::async_runtime::spawn_blocking(move || {
+            registry.disconnect_all_blocking(deadline, &reason)
+        })
+        .await;
+        if let Err(e) = result {
+            error_log!(error = %e, "disconnect_all task failed");
+        }
+    }
+
+    /// Blocking variant of `disconnect_all`, used from the app exit hook
+    pub(crate) fn disconnect_all_blocking(&self, deadline: Duration, reason: &str) {
+        debug_log!(reason, "Disconnecting all servers");
+
+        // Nothing gets restarted from here on
+        if let Ok(mut restarts) = self.restarts.lock() {
+            restarts.clear();
+        }
+        if let Ok(mut configs) = self.configs.lock() {
+            configs.clear();
+        }
+
+        let processes: Vec<(String, ProcessHandle)> = match self.processes.lock() {
+            Ok(mut processes) => processes.drain().collect(),
+            Err(_) => Vec::new(),
+        };
+
+        for (server_id, _) in &processes {
+            let _ = self.transition(server_id, ConnectionState::Disconnecting, None, |_| {});
+        }
+        let stopped: Vec<(String, Option<ShutdownStep>)> = std::thread::scope(|scope| {
+            let handles: Vec<_> = processes
+                .into_iter()
+                .map(|(server_id, process)| {
+                    scope.spawn(move || {
+                        let mut process = process.lock();
+                        let shutdown = process.shutdown_config().within(deadline);
+                        process.set_shutdown_config(shutdown);
+                        (server_id, process.stop())
+                    })
+                })
+                .collect();
+            handles.into_iter().filter_map(|handle| handle.join().ok()).collect()
+        });
+
+        for (server_id, step) in stopped {
+            let reason = match step {
+                Some(step) => format!("{} ({})", reason, step),
+                None => reason.to_string(),
+            };
+            let _ = self.transition(&server_id, ConnectionState::Disconnected, Some(reason), |_| {});
+        }
+
+        // Entries without a process, e.g. waiting for a restart
+        let pending: Vec<String> = match self.connections.lock() {
+            Ok(connections) => connections
+                .values()
+                .filter(|info| info.status.can_transition_to(ConnectionState::Disconnected))
+                .filter(|info| info.status != ConnectionState::Failed)
+                .map(|info| info.server_id.clone())
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        for server_id in pending {
+            let _ = self.transition(&server_id, ConnectionState::Disconnected, Some(reason.to_string()), |_| {});
+        }
+    }
+
     /// Check if a server is connected through the plugin
     pub fn is_server_connected(&self, server_id: &str) -> Result<bool, String> {
         let connections = self.connections.lock()
             .map_err(|e| format!("Failed to lock connections: {}", e))?;
         
-        Ok(connections.contains_key(server_id))
+        Ok(connections
+            .get(server_id)
+            .map(|info| info.status == ConnectionState::Ready)
+            .unwrap_or(false))
     }
 
-    /// List tools from an MCP server through the plugin
-    pub async fn list_tools(&self, server_id: &str) -> Result<serde_json::Value, MCPClientError> {
-        eprintln!("DEBUG: Plugin list_tools called for server {}", server_id);
+    /// Handle of the server's process, if it has one
+    fn process_handle(&self, server_id: &str) -> Option<ProcessHandle> {
+        self.processes.lock().ok()?.get(server_id).cloned()
+    }
 
-        let mut processes = self.processes.lock()
-            .map_err(|e| MCPClientError::system_error(&format!("Failed to lock processes: {}", e)))?;
+    /// Run `f` on the channel of the server's process after making sure it
+    /// is still alive. An exited process is cleaned up (and restarted if its
+    /// policy says so). No lock is held while `f` runs, so requests to the
+    /// same server run concurrently.
+    fn with_running_process<T>(
+        &self,
+        server_id: &str,
+        context: &str,
+        f: impl FnOnce(&ServerChannel) -> Result<T, MCPClientError>,
+    ) -> Result<T, MCPClientError> {
+        let (generation, exit) = {
+            let handle = self.process_handle(server_id).ok_or_else(|| {
+                MCPClientError::new(
+                    ErrorCategory::Connection,
+                    "NO_PROCESS",
+                    &format!("No active MCP process found for server {}", server_id),
+                )
+                .with_suggestions(vec![
+                    "Ensure the server is connected",
+                    "Try connecting to the server first",
+                    "Check that the server ID is correct",
+                ])
+            })?;
+            let mut process = handle.lock();
 
-        if let Some(process) = processes.get_mut(server_id) {
-            // Check if the process is still running using the public method
-            match process.check_process_status() {
-                Ok(true) => {
-                    eprintln!(
-                        "DEBUG: Plugin MCP process for server {} is still running",
-                        server_id
-                    );
-                }
-                Ok(false) => {
-                    eprintln!(
-                        "DEBUG: Plugin MCP process for server {} has exited",
-                        server_id
-                    );
-
-                    // Emit process exit event for list_tools
-                    let event = ConnectionEvent {
-                        server_id: server_id.to_string(),
-                        status: "disconnected".to_string(),
-                        reason: Some("Process exited during tool listing".to_string()),
-                        timestamp: std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap()
-                            .as_secs(),
-                        command: None,
-                        args: None,
-                    };
-                    self.emit_connection_event(event);
-
-                    return Err(MCPClientError::new(
-                        crate::error::ErrorCategory::Connection,
-                        "PROCESS_EXITED",
-                        &format!("MCP process for server {} has exited", server_id),
-                    )
-                    .with_suggestions(vec![
-                        "Check server logs for errors",
-                        "Verify server configuration is correct",
-                        "Try reconnecting to the server",
-                    ]));
+            // Check if the process is still running
+            match process.exit_status() {
+                Ok(None) => {
+                    let channel = process.channel()?;
+                    drop(process);
+                    return f(&channel);
                 }
+                Ok(Some(status)) => (handle.generation, ProcessExit::from_status(&status, process.stderr_tail())),
                 Err(e) => {
-                    eprintln!(
-                        "DEBUG: Plugin error checking process status for server {}: {}",
-                        server_id, e
-                    );
+                    warn_log!(server_id, error = %e, "Failed to check process status");
                     return Err(MCPClientError::new(
-                        crate::error::ErrorCategory::System,
+                        ErrorCategory::System,
                         "STATUS_CHECK_FAILED",
                         "Error checking MCP process status",
                     )
@@ -279,9 +824,56 @@ impl<R: Runtime> ConnectionRegistry<R> {
                     ]));
                 }
             }
+        };
+
+        let description = exit.description.clone();
+        debug_log!(server_id, exit = %description, "MCP process has exited");
+        self.handle_process_exit(
+            server_id,
+            generation,
+            &format!("Process exited {} ({})", context, description),
+            exit,
+        );
+
+        Err(MCPClientError::new(
+            ErrorCategory::Connection,
+            "PROCESS_EXITED",
+            &format!("MCP process for server {} has exited", server_id),
+        )
+        .with_details(&format!("Process {}", description))
+        .with_suggestions(vec![
+            "Check server logs for errors",
+            "Verify server configuration is correct",
+            "Try reconnecting to the server",
+        ]))
+    }
+
+    /// Run a request on the blocking thread pool, so waiting for a server
+    /// never stalls the async executor
+    async fn run_blocking<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&Self) -> Result<T, MCPClientError> + Send + 'static,
+    ) -> Result<T, MCPClientError> {
+        let registry = self.clone();
+        tauri::async_runtime::spawn_blocking(move || f(&registry))
+            .await
+            .map_err(|e| MCPClientError::system_error(&format!("Request task failed: {}", e)))?
+    }
 
+    /// List tools from an MCP server through the plugin
+    pub async fn list_tools(&self, server_id: &str) -> Result<serde_json::Value, MCPClientError> {
+        let server_id = server_id.to_string();
+        self.run_blocking(move |registry| registry.list_tools_blocking(&server_id)).await
+    }
+
+    pub(crate) fn list_tools_blocking(&self, server_id: &str) -> Result<serde_json::Value, MCPClientError> {
+        let span = request_span(server_id, "tools/list");
+        let _entered = span.enter();
+
+        let result = self.with_running_process(server_id, "during tool listing", |channel| {
             // Create the tools/list JSON-RPC message
-            let message_id = process.next_message_id();
+            let message_id = channel.next_message_id();
+            span.record("request_id", message_id);
             let list_tools_message = serde_json::json!({
                 "jsonrpc": "2.0",
                 "id": message_id,
@@ -290,118 +882,230 @@ impl<R: Runtime> ConnectionRegistry<R> {
             });
 
             // Send the message
-            if let Err(e) = process.send_message_sync(list_tools_message) {
-                return Err(e);
+            channel.send(&list_tools_message)?;
+
+            let response = channel.read_response(message_id as u64, self.settings.request_timeout_ms)?;
+
+            // Extract the result from the JSON-RPC response
+            if let Some(result) = response.get("result") {
+                Ok(result.clone())
+            } else if let Some(error) = response.get("error") {
+                Err(MCPClientError::protocol_error(&format!(
+                    "MCP server returned error: {}",
+                    error
+                )))
+            } else {
+                Err(MCPClientError::protocol_error(
+                    "Invalid JSON-RPC response: missing result and error",
+                ))
             }
+        })?;
+        self.cache_tool_annotations(server_id, &result);
+        Ok(result)
+    }
 
-            // Read the response with 5 second timeout
-            match process.read_response(message_id as u64, 5000) {
-                Ok(response) => {
-                    eprintln!(
-                        "DEBUG: Plugin got tools response for server {}: {}",
-                        server_id, response
-                    );
-
-                    // Extract the result from the JSON-RPC response
-                    if let Some(result) = response.get("result") {
-                        Ok(result.clone())
-                    } else if let Some(error) = response.get("error") {
-                        Err(MCPClientError::protocol_error(&format!(
-                            "MCP server returned error: {}",
-                            error
-                        )))
-                    } else {
-                        Err(MCPClientError::protocol_error(
-                            "Invalid JSON-RPC response: missing result and error",
-                        ))
-                    }
-                }
-                Err(e) => {
-                    return Err(e);
+    fn cache_tool_annotations(&self, server_id: &str, result: &serde_json::Value) {
+        let Some(tools) = result.get("tools").and_then(|tools| tools.as_array()) else {
+            return;
+        };
+        if let Ok(mut annotations) = self.tool_annotations.lock() {
+            let server = annotations.entry(server_id.to_string()).or_default();
+            for tool in tools {
+                if let Some(name) = tool.get("name").and_then(|name| name.as_str()) {
+                    let value = tool.get("annotations").cloned().unwrap_or(serde_json::Value::Null);
+                    server.insert(name.to_string(), value);
                 }
             }
+        }
+    }
+
+    /// Annotations of a tool, listing the server's tools if they aren't known yet
+    fn tool_annotations(&self, server_id: &str, tool_name: &str) -> Option<serde_json::Value> {
+        let cached = |annotations: &HashMap<String, HashMap<String, serde_json::Value>>| {
+            annotations.get(server_id).map(|tools| tools.get(tool_name).cloned())
+        };
+        if let Some(known) = self.tool_annotations.lock().ok().and_then(|a| cached(&a)) {
+            return known;
+        }
+        if let Err(e) = self.list_tools_blocking(server_id) {
+            debug_log!(server_id, error = %e, "Could not list tools for their annotations");
+            return None;
+        }
+        self.tool_annotations.lock().ok().and_then(|a| cached(&a)).flatten()
+    }
+
+    /// What the approval policy says about a call: whether it may go ahead,
+    /// `None` when that needs the user's decision, and whether the tool may
+    /// be destructive
+    fn approval_precheck(&self, server_id: &str, tool_name: &str) -> (Option<bool>, bool) {
+        let policy = &self.settings.approval;
+        if policy.mode == ApprovalMode::Never {
+            return (Some(true), false);
+        }
+        let annotations = self.tool_annotations(server_id, tool_name);
+        let destructive = approval::is_destructive(annotations.as_ref());
+        if !policy.requires_approval(server_id, tool_name, annotations.as_ref()) {
+            return (Some(true), destructive);
+        }
+        (self.approvals.remembered(server_id, tool_name), destructive)
+    }
+
+    /// Hold a tool call until the user approves it, if the approval policy asks for that
+    async fn approve_tool_call(&self, server_id: &str, tool_name: &str, arguments: &serde_json::Value) -> Result<(), MCPClientError> {
+        let (allowed, destructive) = self.approval_precheck(server_id, tool_name);
+        match allowed {
+            Some(true) => return Ok(()),
+            Some(false) => {
+                debug_log!(server_id, tool_name, "Tool call rejected by remembered decision");
+                return Err(MCPClientError::tool_call_rejected(tool_name, true));
+            }
+            None => {}
+        }
+
+        let (request, decision) = self.approvals.request(server_id, tool_name, arguments.clone(), destructive)?;
+        info_log!(server_id, tool_name, approval_id = %request.id, "Tool call waiting for approval");
+        self.emit_event(EVENT_APPROVAL_REQUIRED, &request);
+
+        let timeout_ms = self.settings.approval.timeout_ms;
+        let reason = match tokio::time::timeout(Duration::from_millis(timeout_ms), decision).await {
+            // `respond_approval` reports the decision
+            Ok(Ok(true)) => return Ok(()),
+            Ok(Ok(false)) => {
+                info_log!(server_id, tool_name, "Tool call rejected");
+                return Err(MCPClientError::tool_call_rejected(tool_name, false));
+            }
+            // The request was dropped without a decision
+            Ok(Err(_)) => "cancelled",
+            Err(_) => {
+                self.approvals.cancel(&request.id);
+                "timeout"
+            }
+        };
+        self.emit_event(
+            EVENT_APPROVAL_RESOLVED,
+            &ApprovalResolved {
+                id: request.id,
+                server_id: request.server_id,
+                tool_name: request.tool_name,
+                approved: false,
+                reason: Some(reason.to_string()),
+            },
+        );
+        warn_log!(server_id, tool_name, reason, "No decision on tool call");
+        if reason == "timeout" {
+            Err(MCPClientError::approval_timeout(tool_name, timeout_ms))
         } else {
-            return Err(MCPClientError::new(
-                crate::error::ErrorCategory::Connection,
-                "NO_PROCESS",
-                &format!("No active MCP process found for server {}", server_id),
-            )
-            .with_suggestions(vec![
-                "Ensure the server is connected",
-                "Try connecting to the server first",
-                "Check that the server ID is correct",
-            ]));
+            Err(MCPClientError::tool_call_rejected(tool_name, false))
         }
     }
 
-    /// Execute a tool on an MCP server through the plugin
+    fn emit_event<T: Serialize + Clone>(&self, event: &str, payload: &T) {
+        if let Some(ref app_handle) = self.app_handle {
+            if let Err(e) = app_handle.emit(&self.settings.event_name(event), payload) {
+                warn_log!(event, error = %e, "Failed to emit event");
+            }
+        }
+    }
+
+    /// Tool calls waiting for the user's approval, oldest first
+    pub fn pending_approvals(&self) -> Vec<ApprovalRequest> {
+        self.approvals.pending()
+    }
+
+    /// Answer a pending approval request, letting the call go ahead or rejecting it
+    pub fn respond_approval(&self, id: &str, decision: ApprovalDecision) -> Result<(), MCPClientError> {
+        let request = self.approvals.respond(id, decision)?;
+        debug_log!(
+            server_id = %request.server_id,
+            tool_name = %request.tool_name,
+            approved = decision.approved,
+            remember = decision.remember,
+            "Approval answered"
+        );
+        self.emit_event(
+            EVENT_APPROVAL_RESOLVED,
+            &ApprovalResolved {
+                id: request.id,
+                server_id: request.server_id,
+                tool_name: request.tool_name,
+                approved: decision.approved,
+                reason: None,
+            },
+        );
+        Ok(())
+    }
+
+    /// Execute a tool on an MCP server through the plugin. Depending on the
+    /// approval policy, the call first waits for the user's approval.
     pub async fn execute_tool(&self, server_id: &str, tool_name: &str, arguments: serde_json::Value) -> Result<(serde_json::Value, u64), MCPClientError> {
-        eprintln!("DEBUG: Plugin execute_tool called for server {} tool {} with args: {}", server_id, tool_name, arguments);
+        self.execute_tool_for(None, server_id, tool_name, arguments).await
+    }
 
-        let start_time = std::time::Instant::now();
-        let mut processes = self.processes.lock()
-            .map_err(|e| MCPClientError::system_error(&format!("Failed to lock processes: {}", e)))?;
+    /// Execute a tool on behalf of a window, whose label goes into the audit log
+    pub async fn execute_tool_for(
+        &self,
+        window: Option<&str>,
+        server_id: &str,
+        tool_name: &str,
+        arguments: serde_json::Value,
+    ) -> Result<(serde_json::Value, u64), MCPClientError> {
+        let record = self.tool_call_record(window, server_id, tool_name, &arguments);
+        let start = Instant::now();
+        let result = async {
+            // Approved first, so a call waiting on the user doesn't hold a slot
+            self.approve_tool_call(server_id, tool_name, &arguments).await?;
+            let _permit = match self.limiter(server_id) {
+                Some(limiter) => Some(limiter.acquire(tool_name).await?),
+                None => None,
+            };
+            let (server_id, tool_name) = (server_id.to_string(), tool_name.to_string());
+            self.run_blocking(move |registry| registry.execute_tool_blocking(&server_id, &tool_name, arguments))
+                .await
+        }
+        .await;
+        self.audit
+            .record_tool_call(server_id, record, start.elapsed(), result.as_ref().map(|(result, _)| result));
+        result
+    }
 
-        if let Some(process) = processes.get_mut(server_id) {
-            // Check if the process is still running using the public method
-            match process.check_process_status() {
-                Ok(true) => {
-                    eprintln!(
-                        "DEBUG: Plugin MCP process for server {} is still running",
-                        server_id
-                    );
-                }
-                Ok(false) => {
-                    eprintln!(
-                        "DEBUG: Plugin MCP process for server {} has exited",
-                        server_id
-                    );
-
-                    // Emit process exit event for execute_tool
-                    let event = ConnectionEvent {
-                        server_id: server_id.to_string(),
-                        status: "disconnected".to_string(),
-                        reason: Some("Process exited during tool execution".to_string()),
-                        timestamp: std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap()
-                            .as_secs(),
-                        command: None,
-                        args: None,
-                    };
-                    self.emit_connection_event(event);
+    /// Limits of a connected server, unless it has none
+    fn limiter(&self, server_id: &str) -> Option<Arc<ServerLimiter>> {
+        self.limiters
+            .lock()
+            .ok()?
+            .get(server_id)
+            .filter(|limiter| !limiter.is_unlimited())
+            .cloned()
+    }
 
-                    return Err(MCPClientError::new(
-                        crate::error::ErrorCategory::Connection,
-                        "PROCESS_EXITED",
-                        &format!("MCP process for server {} has exited", server_id),
-                    )
-                    .with_suggestions(vec![
-                        "Check server logs for errors",
-                        "Verify server configuration is correct",
-                        "Try reconnecting to the server",
-                    ]));
-                }
-                Err(e) => {
-                    eprintln!(
-                        "DEBUG: Plugin error checking process status for server {}: {}",
-                        server_id, e
-                    );
-                    return Err(MCPClientError::new(
-                        crate::error::ErrorCategory::System,
-                        "STATUS_CHECK_FAILED",
-                        "Error checking MCP process status",
-                    )
-                    .with_details(&e.to_string())
-                    .with_suggestions(vec![
-                        "Try reconnecting to the server",
-                        "Restart the application if the issue persists",
-                    ]));
-                }
-            }
+    /// Audit record of a call, with arguments hashed and, if configured, redacted
+    pub(crate) fn tool_call_record(
+        &self,
+        window: Option<&str>,
+        server_id: &str,
+        tool_name: &str,
+        arguments: &serde_json::Value,
+    ) -> ToolCallRecord {
+        let redactor = self
+            .processes
+            .lock()
+            .ok()
+            .and_then(|processes| processes.get(server_id).cloned())
+            .map(|process| process.lock().redactor().clone())
+            .unwrap_or_default();
+        ToolCallRecord::new(window, tool_name, arguments, self.settings.audit.arguments, &redactor)
+    }
 
+    pub(crate) fn execute_tool_blocking(&self, server_id: &str, tool_name: &str, arguments: serde_json::Value) -> Result<(serde_json::Value, u64), MCPClientError> {
+        let span = request_span(server_id, "tools/call");
+        let _entered = span.enter();
+        debug_log!(server_id, tool_name, "Calling tool");
+
+        let start_time = std::time::Instant::now();
+        self.with_running_process(server_id, "during tool execution", |channel| {
             // Create the tools/call JSON-RPC message
-            let message_id = process.next_message_id();
+            let message_id = channel.next_message_id();
+            span.record("request_id", message_id);
             let call_tool_message = serde_json::json!({
                 "jsonrpc": "2.0",
                 "id": message_id,
@@ -412,58 +1116,648 @@ impl<R: Runtime> ConnectionRegistry<R> {
                 }
             });
 
-            eprintln!("DEBUG: Plugin sending tool call message: {}", call_tool_message);
-
             // Send the message
-            if let Err(e) = process.send_message_sync(call_tool_message) {
-                return Err(e);
+            channel.send(&call_tool_message)?;
+
+            let response = channel.read_response(message_id as u64, self.settings.tool_timeout_ms)?;
+            let duration_ms = start_time.elapsed().as_millis() as u64;
+            debug_log!(server_id, tool_name, duration_ms, "Tool call completed");
+
+            // Extract the result from the JSON-RPC response
+            if let Some(result) = response.get("result") {
+                Ok((result.clone(), duration_ms))
+            } else if let Some(error) = response.get("error") {
+                Err(MCPClientError::new(
+                    ErrorCategory::Protocol,
+                    "TOOL_EXECUTION_ERROR",
+                    &format!("Tool '{}' execution failed", tool_name),
+                )
+                .with_details(&format!("MCP server returned error: {}", error))
+                .with_suggestions(vec![
+                    "Check the tool parameters are correct",
+                    "Verify the tool exists on this server",
+                    "Review server logs for more details",
+                ]))
+            } else {
+                Err(MCPClientError::protocol_error(
+                    "Invalid JSON-RPC response: missing result and error",
+                ))
             }
+        })
+    }
 
-            // Read the response with 10 second timeout for tool execution
-            match process.read_response(message_id as u64, 10000) {
-                Ok(response) => {
-                    let duration_ms = start_time.elapsed().as_millis() as u64;
-                    eprintln!(
-                        "DEBUG: Plugin got tool response for server {} in {}ms: {}",
-                        server_id, duration_ms, response
-                    );
-
-                    // Extract the result from the JSON-RPC response
-                    if let Some(result) = response.get("result") {
-                        Ok((result.clone(), duration_ms))
-                    } else if let Some(error) = response.get("error") {
-                        Err(MCPClientError::new(
-                            crate::error::ErrorCategory::Protocol,
-                            "TOOL_EXECUTION_ERROR",
-                            &format!("Tool '{}' execution failed", tool_name),
-                        )
-                        .with_details(&format!("MCP server returned error: {}", error))
-                        .with_suggestions(vec![
-                            "Check the tool parameters are correct",
-                            "Verify the tool exists on this server",
-                            "Review server logs for more details",
-                        ]))
-                    } else {
-                        Err(MCPClientError::protocol_error(
-                            "Invalid JSON-RPC response: missing result and error",
-                        ))
+    /// List resources from an MCP server through the plugin
+    pub async fn list_resources(&self, server_id: &str) -> Result<serde_json::Value, MCPClientError> {
+        self.request(server_id, "resources/list", serde_json::json!({}), self.settings.request_timeout_ms)
+            .await
+    }
+
+    /// Read a resource from an MCP server through the plugin
+    pub async fn read_resource(&self, server_id: &str, uri: &str) -> Result<serde_json::Value, MCPClientError> {
+        self.request(
+            server_id,
+            "resources/read",
+            serde_json::json!({ "uri": uri }),
+            self.settings.request_timeout_ms,
+        )
+        .await
+    }
+
+    /// Send an arbitrary JSON-RPC request to a server and return its `result`
+    pub async fn request(
+        &self,
+        server_id: &str,
+        method: &str,
+        params: serde_json::Value,
+        timeout_ms: u64,
+    ) -> Result<serde_json::Value, MCPClientError> {
+        let (server_id, method) = (server_id.to_string(), method.to_string());
+        self.run_blocking(move |registry| registry.request_blocking(&server_id, &method, params, timeout_ms))
+            .await
+    }
+
+    pub(crate) fn request_blocking(
+        &self,
+        server_id: &str,
+        method: &str,
+        params: serde_json::Value,
+        timeout_ms: u64,
+    ) -> Result<serde_json::Value, MCPClientError> {
+        let span = request_span(server_id, method);
+        let _entered = span.enter();
+
+        let response = self.raw_request_in(&span, server_id, method, params, timeout_ms)?;
+        if let Some(result) = response.get("result") {
+            Ok(result.clone())
+        } else if let Some(error) = response.get("error") {
+            Err(MCPClientError::protocol_error(&format!(
+                "MCP server returned error for {}: {}",
+                method, error
+            )))
+        } else {
+            Err(MCPClientError::protocol_error(
+                "Invalid JSON-RPC response: missing result and error",
+            ))
+        }
+    }
+
+    /// Send a JSON-RPC request and return the whole response, so callers
+    /// can inspect error objects themselves
+    pub async fn raw_request(
+        &self,
+        server_id: &str,
+        method: &str,
+        params: serde_json::Value,
+        timeout_ms: u64,
+    ) -> Result<serde_json::Value, MCPClientError> {
+        let (server_id, method) = (server_id.to_string(), method.to_string());
+        self.run_blocking(move |registry| {
+            let span = request_span(&server_id, &method);
+            let _entered = span.enter();
+            registry.raw_request_in(&span, &server_id, &method, params, timeout_ms)
+        })
+        .await
+    }
+
+    fn raw_request_in(
+        &self,
+        span: &tracing::Span,
+        server_id: &str,
+        method: &str,
+        params: serde_json::Value,
+        timeout_ms: u64,
+    ) -> Result<serde_json::Value, MCPClientError> {
+        self.with_running_process(server_id, &format!("during {}", method), |channel| {
+            let message_id = channel.next_message_id();
+            span.record("request_id", message_id);
+            channel.send(&serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": message_id,
+                "method": method,
+                "params": params
+            }))?;
+            channel.read_response(message_id as u64, timeout_ms)
+        })
+    }
+
+    /// Send a JSON-RPC notification to a server
+    pub async fn notify(
+        &self,
+        server_id: &str,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<(), MCPClientError> {
+        let (server_id, method) = (server_id.to_string(), method.to_string());
+        self.run_blocking(move |registry| {
+            registry.with_running_process(&server_id, &format!("during {}", method), |channel| {
+                channel.send(&serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "method": method,
+                    "params": params
+                }))
+            })
+        })
+        .await
+    }
+
+    /// Response the server gave to `initialize`, if it answered in time
+    pub fn initialize_result(&self, server_id: &str) -> Option<serde_json::Value> {
+        self.process_handle(server_id)?.lock().initialize_result().cloned()
+    }
+
+    /// Start the exit watcher for a connected process
+    fn watch_process(&self, server_id: &str, process: &ProcessHandle) {
+        let generation = process.generation;
+        let registry = self.clone();
+        let server_id = server_id.to_string();
+        process.lock().watch_exit(move |exit| {
+            let reason = exit.reason();
+            registry.handle_process_exit(&server_id, generation, &reason, exit);
+        });
+    }
+
+    /// Ping the server periodically on a background thread until its process is replaced or removed
+    fn start_liveness_checks(&self, server_id: &str, generation: u64, liveness: LivenessConfig) {
+        let registry = self.clone();
+        let server_id = server_id.to_string();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(liveness.interval());
+            if !registry.check_liveness(&server_id, generation, &liveness) {
+                debug_log!(server_id = %server_id, "Liveness checks ended");
+                return;
+            }
+        });
+    }
+
+    /// Run one ping and update the server's latency stats and status.
+    /// Returns false once the checked process is gone.
+    fn check_liveness(&self, server_id: &str, generation: u64, liveness: &LivenessConfig) -> bool {
+        // Ping without holding the process map or the process itself
+        let result = match self.process_handle(server_id) {
+            Some(process) if process.generation == generation => {
+                let channel = process.lock().channel();
+                channel.and_then(|channel| channel.ping(liveness.timeout_ms))
+            }
+            _ => return false,
+        };
+
+        let timestamp = unix_timestamp();
+        let (previous_status, consecutive_failures) = {
+            let mut connections = match self.connections.lock() {
+                Ok(connections) => connections,
+                Err(_) => return false,
+            };
+            let info = match connections.get_mut(server_id) {
+                Some(info) => info,
+                None => return false,
+            };
+            let stats = info.latency.get_or_insert_with(LatencyStats::default);
+            match result {
+                Ok(latency) => stats.record_success(latency.as_millis() as u64, timestamp),
+                Err(ref e) => {
+                    debug_log!(server_id, error = %e, "Ping failed");
+                    stats.record_failure(timestamp)
+                }
+            }
+            let consecutive_failures = stats.consecutive_failures;
+            (info.status, consecutive_failures)
+        };
+
+        if result.is_ok() {
+            if previous_status == ConnectionState::Degraded {
+                let reason = "Server is responding to pings again".to_string();
+                let _ = self.transition(server_id, ConnectionState::Ready, Some(reason), |_| {});
+            }
+            return true;
+        }
+
+        if consecutive_failures < liveness.failure_threshold || previous_status != ConnectionState::Ready {
+            return true;
+        }
+
+        let reason = format!("No ping response after {} attempts", consecutive_failures);
+        let _ = self.transition(server_id, ConnectionState::Degraded, Some(reason), |_| {});
+
+        if liveness.restart_unresponsive {
+            self.restart_process(server_id, generation, "unresponsive");
+            return false;
+        }
+        true
+    }
+
+    /// Sample the server's resource usage periodically on a background
+    /// thread until its process is replaced or removed
+    fn start_resource_monitor(&self, server_id: &str, process: &ProcessHandle, config: ResourceMonitorConfig) {
+        let Some(pid) = process.pid else {
+            return;
+        };
+        let generation = process.generation;
+        if !crate::resources::supported() {
+            warn_log!(server_id, "Resource monitoring is only supported on Linux");
+            return;
+        }
+        let registry = self.clone();
+        let server_id = server_id.to_string();
+        std::thread::spawn(move || {
+            let mut sampler = ResourceSampler::new(pid);
+            loop {
+                std::thread::sleep(config.interval());
+                if !registry.check_resources(&server_id, generation, &mut sampler, &config) {
+                    debug_log!(server_id = %server_id, "Resource monitoring ended");
+                    return;
+                }
+            }
+        });
+    }
+
+    /// Take one sample, update the server's resource stats and act on
+    /// thresholds. Returns false once the sampled process is gone.
+    fn check_resources(
+        &self,
+        server_id: &str,
+        generation: u64,
+        sampler: &mut ResourceSampler,
+        config: &ResourceMonitorConfig,
+    ) -> bool {
+        match self.process_handle(server_id) {
+            Some(process) if process.generation == generation => {}
+            _ => return false,
+        }
+        let Some(sample) = sampler.sample() else {
+            return false;
+        };
+
+        let exceeded = config.thresholds.exceeded(&sample);
+        let timestamp = unix_timestamp();
+        let consecutive_breaches = {
+            let mut connections = match self.connections.lock() {
+                Ok(connections) => connections,
+                Err(_) => return false,
+            };
+            let Some(info) = connections.get_mut(server_id) else {
+                return false;
+            };
+            let stats = info.resources.get_or_insert_with(ResourceStats::default);
+            stats.record(sample.clone(), exceeded.clone(), timestamp);
+            stats.consecutive_breaches
+        };
+        trace_log!(server_id, rss_bytes = sample.rss_bytes, cpu_percent = sample.cpu_percent, "Sampled resources");
+
+        // Act once per streak of samples over a threshold
+        if consecutive_breaches != config.breach_samples.max(1) {
+            return true;
+        }
+        let summary = exceeded
+            .iter()
+            .map(|breach| format!("{} {:.0} > {:.0}", breach.resource, breach.value, breach.limit))
+            .collect::<Vec<_>>()
+            .join(", ");
+        warn_log!(server_id, exceeded = %summary, samples = consecutive_breaches, "Server is over its resource thresholds");
+        self.emit_event(
+            EVENT_RESOURCE_THRESHOLD,
+            &ResourceThresholdEvent {
+                server_id: server_id.to_string(),
+                exceeded,
+                sample,
+                action: config.action,
+                timestamp,
+            },
+        );
+
+        if config.action == ThresholdAction::Restart {
+            self.restart_process(server_id, generation, "over its resource thresholds");
+            return false;
+        }
+        true
+    }
+
+    /// Kill a hung or runaway server and hand it to the restart loop
+    fn restart_process(&self, server_id: &str, generation: u64, reason: &str) {
+        let process = {
+            let mut processes = match self.processes.lock() {
+                Ok(processes) => processes,
+                Err(_) => return,
+            };
+            match processes.get(server_id) {
+                Some(process) if process.generation == generation => {}
+                _ => return,
+            }
+            processes.remove(server_id)
+        };
+        if let Some(process) = process {
+            process.stop();
+        }
+        let error = format!("Stopped because the server was {}", reason);
+        let _ = self.transition(server_id, ConnectionState::Disconnected, Some(error.clone()), |info| {
+            info.last_error = Some(error);
+        });
+
+        let config = self.configs.lock().ok().and_then(|configs| configs.get(server_id).cloned());
+        match config {
+            Some(config) => {
+                info_log!(server_id, reason, "Restarting server");
+                if !self.schedule_restart(server_id, config) {
+                    self.forget_config(server_id);
+                }
+            }
+            None => self.forget_config(server_id),
+        }
+    }
+
+    /// Clean up after a server process exited on its own and apply its restart policy.
+    /// Does nothing if the process of `generation` has already been replaced or removed.
+    fn handle_process_exit(&self, server_id: &str, generation: u64, reason: &str, exit: ProcessExit) {
+        // Reap the dead process, without holding the map lock while it stops
+        let process = {
+            let mut processes = match self.processes.lock() {
+                Ok(processes) => processes,
+                Err(_) => return,
+            };
+            match processes.get(server_id) {
+                Some(process) if process.generation == generation => {}
+                _ => return,
+            }
+            processes.remove(server_id)
+        };
+        if let Some(process) = process {
+            process.stop();
+        }
+
+        let exited_successfully = exit.success;
+        let _ = self.transition(server_id, ConnectionState::Disconnected, Some(reason.to_string()), |info| {
+            if !exit.success {
+                info.last_error = Some(reason.to_string());
+            }
+            info.last_exit = Some(exit);
+        });
+
+        let config = self.configs.lock().ok().and_then(|configs| configs.get(server_id).cloned());
+
+        if let Some(config) = config {
+            if config.restart.should_restart(exited_successfully) && self.schedule_restart(server_id, config) {
+                return;
+            }
+        }
+
+        self.forget_config(server_id);
+    }
+
+    /// Start a background restart loop for a server unless one is already running.
+    /// Returns false when the retry budget is exhausted.
+    fn schedule_restart(&self, server_id: &str, config: ServerConfig) -> bool {
+        let token = {
+            let mut restarts = match self.restarts.lock() {
+                Ok(restarts) => restarts,
+                Err(_) => return false,
+            };
+            let state = restarts.entry(server_id.to_string()).or_default();
+            if state.token.is_some() {
+                return true;
+            }
+
+            // A server that stayed up long enough gets a fresh retry budget
+            let reset_after = std::time::Duration::from_millis(config.restart.reset_after_ms);
+            if state.last_restart.map(|t| t.elapsed() >= reset_after).unwrap_or(false) {
+                state.attempts = 0;
+            }
+            if state.attempts >= config.restart.max_retries {
+                drop(restarts);
+                self.give_up_restart(server_id, &config);
+                return false;
+            }
+
+            let token = self.restart_tokens.fetch_add(1, Ordering::SeqCst);
+            state.token = Some(token);
+            token
+        };
+
+        let registry = self.clone();
+        let server_id = server_id.to_string();
+        tauri::async_runtime::spawn(async move {
+            registry.run_restart_loop(server_id, config, token).await;
+        });
+        true
+    }
+
+    /// Retry connecting with exponential backoff until success, cancellation or the retry limit
+    async fn run_restart_loop(&self, server_id: String, config: ServerConfig, token: u64) {
+        let policy = config.restart.clone();
+
+        loop {
+            let attempt = {
+                let mut restarts = match self.restarts.lock() {
+                    Ok(restarts) => restarts,
+                    Err(_) => return,
+                };
+                match restarts.get_mut(&server_id) {
+                    Some(state) if state.token == Some(token) => {
+                        if state.attempts >= policy.max_retries {
+                            state.token = None;
+                            break;
+                        }
+                        state.attempts += 1;
+                        state.attempts
+                    }
+                    // Cancelled by an explicit connect or disconnect
+                    _ => return,
+                }
+            };
+
+            let delay = policy.backoff_for_attempt(attempt - 1);
+            let reason = format!("Restart attempt {}/{} in {}ms", attempt, policy.max_retries, delay.as_millis());
+            if let Err(e) = self.transition(&server_id, ConnectionState::Reconnecting, Some(reason), |_| {}) {
+                // Something else took over the server, e.g. an explicit connect
+                warn_log!(server_id = %server_id, error = %e, "Stopping restarts");
+                if let Ok(mut restarts) = self.restarts.lock() {
+                    if let Some(state) = restarts.get_mut(&server_id).filter(|state| state.token == Some(token)) {
+                        state.token = None;
+                    }
+                }
+                return;
+            }
+
+            tokio::time::sleep(delay).await;
+            if !self.is_restart_current(&server_id, token) {
+                return;
+            }
+
+            // A failed attempt leaves the server disconnected until the next
+            // one; its definition already passed the scope it connected with
+            let rules = ScopeRules::default();
+            match self.connect_inner(server_id.clone(), config.clone(), &rules, ConnectionState::Disconnected).await {
+                Ok(()) => {
+                    if let Ok(mut restarts) = self.restarts.lock() {
+                        if let Some(state) = restarts.get_mut(&server_id) {
+                            state.token = None;
+                            state.last_restart = Some(Instant::now());
+                        }
                     }
+                    info_log!(server_id = %server_id, attempt, "Restarted server");
+                    return;
                 }
                 Err(e) => {
-                    return Err(e);
+                    warn_log!(server_id = %server_id, attempt, error = %e, "Restart attempt failed");
+                    // Errors before spawning (spawn policy, secrets) leave the
+                    // server reconnecting; record them like failed spawns
+                    if self.connection_state(&server_id) == Some(ConnectionState::Reconnecting) {
+                        let error = e.message.clone();
+                        let disconnected = self.transition(&server_id, ConnectionState::Disconnected, Some(error.clone()), |info| {
+                            info.last_error = Some(error);
+                        });
+                        if let Err(e) = disconnected {
+                            warn_log!(server_id = %server_id, error = %e, "Failed to record restart failure");
+                        }
+                    }
                 }
             }
-        } else {
-            return Err(MCPClientError::new(
-                crate::error::ErrorCategory::Connection,
-                "NO_PROCESS",
-                &format!("No active MCP process found for server {}", server_id),
-            )
-            .with_suggestions(vec![
-                "Ensure the server is connected",
-                "Try connecting to the server first",
-                "Check that the server ID is correct",
-            ]));
+        }
+
+        self.give_up_restart(&server_id, &config);
+    }
+
+    fn is_restart_current(&self, server_id: &str, token: u64) -> bool {
+        self.restarts
+            .lock()
+            .map(|restarts| restarts.get(server_id).and_then(|s| s.token) == Some(token))
+            .unwrap_or(false)
+    }
+
+    /// Stop any pending restart and reset the retry budget
+    fn cancel_restart(&self, server_id: &str) {
+        if let Ok(mut restarts) = self.restarts.lock() {
+            restarts.remove(server_id);
+        }
+    }
+
+    fn give_up_restart(&self, server_id: &str, config: &ServerConfig) {
+        warn_log!(server_id, "Giving up restarting server");
+        self.forget_config(server_id);
+        let reason = format!("Server could not be restarted after {} attempts", config.restart.max_retries);
+        let _ = self.transition(server_id, ConnectionState::Failed, Some(reason.clone()), |info| {
+            info.last_error = Some(match info.last_error.take() {
+                Some(error) => format!("{}: {}", reason, error),
+                None => reason,
+            });
+        });
+    }
+
+    /// Drop the spawn configuration so the server is no longer restarted
+    fn forget_config(&self, server_id: &str) {
+        if let Ok(mut configs) = self.configs.lock() {
+            configs.remove(server_id);
+        }
+    }
+}
+
+/// Run the (possibly slow) graceful shutdown off the async executor
+async fn stop_process(process: ProcessHandle) -> Option<ShutdownStep> {
+    tauri::async_runtime::spawn_blocking(move || process.stop())
+        .await
+        .ok()
+        .flatten()
+}
+
+fn unix_timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Runtime-independent view of a registry, backing the free functions in `process`
+pub(crate) trait SharedRegistry: Send + Sync {
+    fn connect(&self, server_id: String, command: String, args: Vec<String>) -> BoxFuture<'_, Result<(), MCPClientError>>;
+    fn disconnect(&self, server_id: &str) -> Result<(), MCPClientError>;
+    fn list_tools(&self, server_id: &str) -> Result<serde_json::Value, MCPClientError>;
+    fn execute_tool(&self, server_id: &str, tool_name: &str, arguments: serde_json::Value) -> Result<(serde_json::Value, u64), MCPClientError>;
+    fn connection_statuses(&self) -> Vec<ConnectionInfo>;
+    fn disconnect_all(&self, deadline: Duration, reason: &str);
+}
+
+impl<R: Runtime> SharedRegistry for ConnectionRegistry<R> {
+    fn connect(&self, server_id: String, command: String, args: Vec<String>) -> BoxFuture<'_, Result<(), MCPClientError>> {
+        Box::pin(self.connect_server(server_id, command, args))
+    }
+
+    fn disconnect(&self, server_id: &str) -> Result<(), MCPClientError> {
+        self.disconnect_server_blocking(server_id)
+    }
+
+    fn list_tools(&self, server_id: &str) -> Result<serde_json::Value, MCPClientError> {
+        self.list_tools_blocking(server_id)
+    }
+
+    fn execute_tool(&self, server_id: &str, tool_name: &str, arguments: serde_json::Value) -> Result<(serde_json::Value, u64), MCPClientError> {
+        let record = self.tool_call_record(None, server_id, tool_name, &arguments);
+        let start = Instant::now();
+        let permit = self
+            .limiter(server_id)
+            .map(|limiter| limiter.try_acquire(tool_name))
+            .transpose();
+        // Blocking callers can't wait for a decision or a queue, only remembered decisions apply
+        let result = match (permit, self.approval_precheck(server_id, tool_name).0) {
+            (Err(e), _) => Err(e),
+            (Ok(_permit), Some(true)) => self.execute_tool_blocking(server_id, tool_name, arguments),
+            (Ok(_), Some(false)) => Err(MCPClientError::tool_call_rejected(tool_name, true)),
+            (Ok(_), None) => Err(MCPClientError::approval_required(tool_name)),
+        };
+        self.audit
+            .record_tool_call(server_id, record, start.elapsed(), result.as_ref().map(|(result, _)| result));
+        result
+    }
+
+    fn connection_statuses(&self) -> Vec<ConnectionInfo> {
+        self.get_connection_statuses().unwrap_or_default()
+    }
+
+    fn disconnect_all(&self, deadline: Duration, reason: &str) {
+        self.disconnect_all_blocking(deadline, reason)
+    }
+}
+
+/// Registry managed by the plugin; a standalone one is created on first use
+/// when the free functions are called outside a Tauri app
+static SHARED_REGISTRY: Lazy<RwLock<Option<Arc<dyn SharedRegistry>>>> =
+    Lazy::new(|| RwLock::new(None));
+
+/// Make `registry` the one used by the free functions in `process`
+pub(crate) fn install_shared_registry<R: Runtime>(registry: ConnectionRegistry<R>) {
+    if let Ok(mut shared) = SHARED_REGISTRY.write() {
+        *shared = Some(Arc::new(registry));
+    }
+}
+
+pub(crate) fn shared_registry() -> Arc<dyn SharedRegistry> {
+    if let Some(registry) = SHARED_REGISTRY.read().ok().and_then(|shared| shared.clone()) {
+        return registry;
+    }
+    let mut shared = SHARED_REGISTRY.write().unwrap_or_else(|e| e.into_inner());
+    shared
+        .get_or_insert_with(|| Arc::new(ConnectionRegistry::<tauri::Wry>::new()))
+        .clone()
+}
+
+impl<R: Runtime> Clone for ConnectionRegistry<R> {
+    fn clone(&self) -> Self {
+        Self {
+            connections: self.connections.clone(),
+            processes: self.processes.clone(),
+            configs: self.configs.clone(),
+            restarts: self.restarts.clone(),
+            restart_tokens: self.restart_tokens.clone(),
+            app_handle: self.app_handle.clone(),
+            secret_provider: self.secret_provider.clone(),
+            settings: self.settings.clone(),
+            handlers: self.handlers.clone(),
+            traffic: self.traffic.clone(),
+            audit: self.audit.clone(),
+            approvals: self.approvals.clone(),
+            limiters: self.limiters.clone(),
+            tool_annotations: self.tool_annotations.clone(),
+            #[cfg(feature = "testing")]
+            mocks: self.mocks.clone(),
         }
     }
 }
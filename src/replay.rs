@@ -0,0 +1,199 @@
+/// Replay of recorded sessions as a fake server
+///
+/// A session is the JSON Lines export of the traffic recorder (see
+/// `TrafficConfig::record_file`). Each message the client sends is matched
+/// against the next recorded message sent with the same method, and the
+/// messages the server sent in reply are played back with their ids mapped
+/// to the live request ids. Requests with no recording left get a JSON-RPC
+/// error, so a test fails fast instead of waiting for a timeout.
+use crate::error::{ErrorCategory, MCPClientError};
+use crate::traffic::{TrafficDirection, TrafficEntry};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::{Component, Path, PathBuf};
+
+/// JSON-RPC error code returned for requests missing from the recording
+pub const NOT_RECORDED: i64 = -32603;
+
+/// Resolve a record or replay file inside `session_dir`. Relative paths are
+/// taken from the directory; paths leaving it through `..` or a symlink are
+/// refused, as are all paths when no directory is configured.
+pub fn session_path(session_dir: Option<&Path>, path: &Path) -> Result<PathBuf, MCPClientError> {
+    let denied = |reason: &str| MCPClientError::session_path_not_allowed(&path.display().to_string(), reason);
+    let dir = session_dir.ok_or_else(|| denied("No session directory is configured"))?;
+    if path.components().any(|component| component == Component::ParentDir) {
+        return Err(denied("The path must not contain '..'"));
+    }
+    let resolved = dir.join(path);
+    if !resolved.starts_with(dir) {
+        return Err(denied("The path is outside the session directory"));
+    }
+
+    // The deepest part that exists must still be inside once symlinks are followed
+    let existing = resolved
+        .ancestors()
+        .take_while(|ancestor| ancestor.starts_with(dir))
+        .find(|ancestor| ancestor.symlink_metadata().is_ok());
+    if let Some(existing) = existing {
+        let inside = match (existing.canonicalize(), dir.canonicalize()) {
+            (Ok(existing), Ok(dir)) => existing.starts_with(dir),
+            _ => false,
+        };
+        if !inside {
+            return Err(denied("The path is outside the session directory"));
+        }
+    }
+    Ok(resolved)
+}
+
+/// A recorded session that can be served over the replay transport
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplaySession {
+    entries: Vec<TrafficEntry>,
+}
+
+impl ReplaySession {
+    pub fn from_entries(entries: Vec<TrafficEntry>) -> Self {
+        Self { entries }
+    }
+
+    /// Parse a session from traffic JSON Lines; blank lines are skipped
+    pub fn from_jsonl(contents: &str) -> Result<Self, MCPClientError> {
+        let entries = contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line).map_err(|e| {
+                    MCPClientError::configuration_error(
+                        "session",
+                        &format!("Invalid recorded message on line {}: {}", index + 1, e),
+                    )
+                })
+            })
+            .collect::<Result<Vec<TrafficEntry>, _>>()?;
+        Ok(Self { entries })
+    }
+
+    /// Load a session file written by a recording connection
+    pub fn load(path: &Path) -> Result<Self, MCPClientError> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            MCPClientError::new(
+                ErrorCategory::Configuration,
+                "SESSION_READ_FAILED",
+                &format!("Failed to read recorded session {}", path.display()),
+            )
+            .with_details(&e.to_string())
+            .with_suggestions(vec![
+                "Check the session path is correct",
+                "Record the session with traffic.recordFile first",
+            ])
+        })?;
+        Self::from_jsonl(&contents)
+    }
+
+    pub fn entries(&self) -> &[TrafficEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Answer the client's messages from `input` on `output` until the
+    /// client closes its side
+    pub fn serve<I: BufRead, O: Write>(&self, mut input: I, mut output: O) -> std::io::Result<()> {
+        let mut player = Player::new(&self.entries);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if input.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+            let Ok(message) = serde_json::from_str::<Value>(line.trim()) else {
+                continue;
+            };
+            for reply in player.reply(&message) {
+                writeln!(output, "{}", reply)?;
+            }
+            output.flush()?;
+        }
+    }
+}
+
+/// Playback position in a session
+struct Player<'a> {
+    entries: &'a [TrafficEntry],
+    cursor: usize,
+    /// Live request id for each recorded request id
+    ids: HashMap<String, Value>,
+}
+
+impl<'a> Player<'a> {
+    fn new(entries: &'a [TrafficEntry]) -> Self {
+        Self {
+            entries,
+            cursor: 0,
+            ids: HashMap::new(),
+        }
+    }
+
+    /// Messages the server sent in reply to `message` in the recording
+    fn reply(&mut self, message: &Value) -> Vec<Value> {
+        let method = message.get("method").and_then(|m| m.as_str());
+        let live_id = message.get("id").cloned();
+
+        let matched = self.entries[self.cursor..].iter().position(|entry| {
+            entry.direction == TrafficDirection::Sent
+                && entry.method.as_deref() == method
+                // Answers to server requests must answer the same request
+                && (method.is_some() || entry.request_id == live_id)
+        });
+        let Some(offset) = matched else {
+            return match (method, live_id) {
+                (Some(method), Some(id)) => vec![serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {
+                        "code": NOT_RECORDED,
+                        "message": format!("No recorded response for {}", method)
+                    }
+                })],
+                _ => Vec::new(),
+            };
+        };
+
+        let recorded = &self.entries[self.cursor + offset];
+        if let (Some(recorded_id), Some(method), Some(live_id)) = (&recorded.request_id, method, live_id) {
+            trace_log!(method, recorded_id = %recorded_id, live_id = %live_id, "Replaying request");
+            self.ids.insert(recorded_id.to_string(), live_id);
+        }
+        self.cursor += offset + 1;
+
+        let mut replies = Vec::new();
+        while let Some(entry) = self.entries.get(self.cursor) {
+            if entry.direction != TrafficDirection::Received {
+                break;
+            }
+            self.cursor += 1;
+            let mut reply = entry.message.clone();
+            // Responses carry the id of the live request they answer
+            if entry.method.is_none() {
+                if let Some(live_id) = entry
+                    .request_id
+                    .as_ref()
+                    .and_then(|id| self.ids.remove(&id.to_string()))
+                {
+                    reply["id"] = live_id;
+                }
+            }
+            replies.push(reply);
+        }
+        replies
+    }
+}
@@ -0,0 +1,303 @@
+/// Resource usage monitoring of server processes
+///
+/// A leaking or spinning server slowly takes the whole machine down with
+/// it. With monitoring enabled the registry samples the CPU, memory,
+/// threads and open file descriptors of the server process and all its
+/// descendants from `/proc` (Linux only):
+///
+/// ```json
+/// "resources": {
+///   "enabled": true,
+///   "intervalMs": 10000,
+///   "maxRssMb": 1024,
+///   "maxCpuPercent": 90,
+///   "breachSamples": 3,
+///   "action": "restart"
+/// }
+/// ```
+///
+/// A server over a threshold for `breachSamples` samples in a row emits
+/// `mcp://resource-threshold`, and with `"action": "restart"` is killed and
+/// restarted using its restart backoff settings.
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+fn default_interval_ms() -> u64 {
+    10_000
+}
+
+fn default_breach_samples() -> u32 {
+    3
+}
+
+/// What happens when a server stays over a threshold
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThresholdAction {
+    /// Emit `mcp://resource-threshold` and log a warning
+    #[default]
+    Warn,
+    /// Also kill the server and restart it
+    Restart,
+}
+
+/// Limits on a server's resource usage; unset fields aren't checked
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceThresholds {
+    /// Resident memory of the process tree in MiB
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_rss_mb: Option<u64>,
+    /// CPU use since the previous sample, where 100 is one full core
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cpu_percent: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_threads: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_open_fds: Option<u32>,
+}
+
+impl ResourceThresholds {
+    /// Thresholds `sample` is over
+    pub fn exceeded(&self, sample: &ResourceSample) -> Vec<ThresholdBreach> {
+        let checks = [
+            ("rss", sample.rss_bytes as f64, self.max_rss_mb.map(|mb| (mb * 1024 * 1024) as f64)),
+            ("cpu", sample.cpu_percent, self.max_cpu_percent),
+            ("threads", sample.threads as f64, self.max_threads.map(f64::from)),
+            ("open_fds", sample.open_fds as f64, self.max_open_fds.map(f64::from)),
+        ];
+        checks
+            .into_iter()
+            .filter_map(|(resource, value, limit)| {
+                limit.filter(|&limit| value > limit).map(|limit| ThresholdBreach {
+                    resource: resource.to_string(),
+                    value,
+                    limit,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Per-server resource monitoring settings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceMonitorConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_interval_ms")]
+    pub interval_ms: u64,
+    #[serde(flatten)]
+    pub thresholds: ResourceThresholds,
+    /// Consecutive samples over a threshold before `action` is taken
+    #[serde(default = "default_breach_samples")]
+    pub breach_samples: u32,
+    #[serde(default)]
+    pub action: ThresholdAction,
+}
+
+impl Default for ResourceMonitorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_ms: default_interval_ms(),
+            thresholds: ResourceThresholds::default(),
+            breach_samples: default_breach_samples(),
+            action: ThresholdAction::default(),
+        }
+    }
+}
+
+impl ResourceMonitorConfig {
+    /// Enabled sampling with the default interval and no thresholds
+    pub fn enabled() -> Self {
+        Self {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval_ms = interval.as_millis() as u64;
+        self
+    }
+
+    pub fn with_thresholds(mut self, thresholds: ResourceThresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Restart the server once it stays over a threshold
+    pub fn restarting(mut self) -> Self {
+        self.action = ThresholdAction::Restart;
+        self
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis(self.interval_ms.max(1))
+    }
+}
+
+/// Usage of a server process and its descendants at one point in time
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ResourceSample {
+    /// Processes in the tree, the server included
+    pub processes: u32,
+    /// CPU use since the previous sample (0 for the first), where 100 is one core
+    pub cpu_percent: f64,
+    /// Total CPU time of the live processes
+    pub cpu_time_ms: u64,
+    pub rss_bytes: u64,
+    pub threads: u32,
+    pub open_fds: u32,
+}
+
+/// A threshold a sample is over
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ThresholdBreach {
+    /// `rss`, `cpu`, `threads` or `open_fds`
+    pub resource: String,
+    pub value: f64,
+    pub limit: f64,
+}
+
+/// Resource statistics exposed on `ConnectionInfo`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ResourceStats {
+    pub last: Option<ResourceSample>,
+    pub peak_rss_bytes: u64,
+    pub samples: u64,
+    /// Unix timestamp of the last sample
+    pub last_sample_at: Option<u64>,
+    /// Thresholds the last sample was over
+    pub exceeded: Vec<ThresholdBreach>,
+    /// Samples in a row that were over a threshold
+    pub consecutive_breaches: u32,
+}
+
+impl ResourceStats {
+    /// Record a sample and the thresholds it is over
+    pub fn record(&mut self, sample: ResourceSample, exceeded: Vec<ThresholdBreach>, timestamp: u64) {
+        self.samples += 1;
+        self.peak_rss_bytes = self.peak_rss_bytes.max(sample.rss_bytes);
+        self.last = Some(sample);
+        self.last_sample_at = Some(timestamp);
+        self.consecutive_breaches = if exceeded.is_empty() { 0 } else { self.consecutive_breaches + 1 };
+        self.exceeded = exceeded;
+    }
+}
+
+/// Payload of `mcp://resource-threshold`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceThresholdEvent {
+    pub server_id: String,
+    pub exceeded: Vec<ThresholdBreach>,
+    pub sample: ResourceSample,
+    pub action: ThresholdAction,
+    pub timestamp: u64,
+}
+
+/// Whether process trees can be sampled on this platform
+pub fn supported() -> bool {
+    cfg!(target_os = "linux")
+}
+
+/// Samples a process tree, tracking CPU time between samples
+pub struct ResourceSampler {
+    pid: u32,
+    previous: Option<(u64, Instant)>,
+}
+
+impl ResourceSampler {
+    pub fn new(pid: u32) -> Self {
+        Self { pid, previous: None }
+    }
+
+    /// Sample the tree now, or `None` once the process is gone
+    pub fn sample(&mut self) -> Option<ResourceSample> {
+        let mut sample = sample_process_tree(self.pid)?;
+        let now = Instant::now();
+        if let Some((cpu_time_ms, at)) = self.previous {
+            let elapsed_ms = now.duration_since(at).as_secs_f64() * 1000.0;
+            if elapsed_ms > 0.0 {
+                // Descendants that exited take their CPU time with them
+                sample.cpu_percent = sample.cpu_time_ms.saturating_sub(cpu_time_ms) as f64 / elapsed_ms * 100.0;
+            }
+        }
+        self.previous = Some((sample.cpu_time_ms, now));
+        Some(sample)
+    }
+}
+
+/// One-off sample of `pid` and its descendants, without CPU percentage
+#[cfg(target_os = "linux")]
+pub fn sample_process_tree(pid: u32) -> Option<ResourceSample> {
+    use std::collections::HashMap;
+
+    let stats: HashMap<u32, ProcStat> = std::fs::read_dir("/proc")
+        .ok()?
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(|pid| Some((pid, ProcStat::read(pid)?)))
+        .collect();
+    if !stats.contains_key(&pid) {
+        return None;
+    }
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for (&child, stat) in &stats {
+        children.entry(stat.ppid).or_default().push(child);
+    }
+
+    let ticks_per_sec = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as u64;
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(1) as u64;
+    let mut sample = ResourceSample::default();
+    let mut pending = vec![pid];
+    while let Some(current) = pending.pop() {
+        let Some(stat) = stats.get(&current) else {
+            continue;
+        };
+        sample.processes += 1;
+        sample.cpu_time_ms += stat.cpu_ticks * 1000 / ticks_per_sec;
+        sample.rss_bytes += stat.rss_pages * page_size;
+        sample.threads += stat.threads;
+        sample.open_fds += std::fs::read_dir(format!("/proc/{}/fd", current))
+            .map(|fds| fds.count() as u32)
+            .unwrap_or(0);
+        pending.extend(children.get(&current).into_iter().flatten());
+    }
+    Some(sample)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn sample_process_tree(_pid: u32) -> Option<ResourceSample> {
+    None
+}
+
+/// Fields of `/proc/<pid>/stat` the sampler uses
+#[cfg(target_os = "linux")]
+struct ProcStat {
+    ppid: u32,
+    cpu_ticks: u64,
+    threads: u32,
+    rss_pages: u64,
+}
+
+#[cfg(target_os = "linux")]
+impl ProcStat {
+    fn read(pid: u32) -> Option<Self> {
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+        // The command name can contain spaces and parentheses; fields
+        // resume after the last `)`, starting with the state (field 3)
+        let fields: Vec<&str> = stat.get(stat.rfind(')')? + 1..)?.split_whitespace().collect();
+        let field = |number: usize| fields.get(number - 3)?.parse::<u64>().ok();
+        Some(Self {
+            ppid: field(4)? as u32,
+            cpu_ticks: field(14)? + field(15)?,
+            threads: field(20)? as u32,
+            rss_pages: field(24)?,
+        })
+    }
+}
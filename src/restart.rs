@@ -0,0 +1,107 @@
+/// Automatic restart policy for server processes that exit unexpectedly
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// When a server should be restarted after its process exits
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartMode {
+    /// Never restart automatically
+    #[default]
+    Never,
+    /// Restart only when the process exits with an error code or a signal
+    OnFailure,
+    /// Restart whenever the process exits
+    Always,
+}
+
+fn default_max_retries() -> u32 {
+    5
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    500
+}
+
+fn default_max_backoff_ms() -> u64 {
+    30_000
+}
+
+fn default_multiplier() -> f64 {
+    2.0
+}
+
+fn default_reset_after_ms() -> u64 {
+    60_000
+}
+
+/// Per-server restart policy with exponential backoff
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RestartPolicy {
+    #[serde(default)]
+    pub mode: RestartMode,
+    /// Attempts before giving up; the counter resets once a server stays up for `reset_after_ms`
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    #[serde(default = "default_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+    #[serde(default = "default_multiplier")]
+    pub multiplier: f64,
+    #[serde(default = "default_reset_after_ms")]
+    pub reset_after_ms: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            mode: RestartMode::Never,
+            max_retries: default_max_retries(),
+            initial_backoff_ms: default_initial_backoff_ms(),
+            max_backoff_ms: default_max_backoff_ms(),
+            multiplier: default_multiplier(),
+            reset_after_ms: default_reset_after_ms(),
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Policy restarting failed servers with the default backoff
+    pub fn on_failure() -> Self {
+        Self {
+            mode: RestartMode::OnFailure,
+            ..Default::default()
+        }
+    }
+
+    /// Policy restarting servers whenever they exit with the default backoff
+    pub fn always() -> Self {
+        Self {
+            mode: RestartMode::Always,
+            ..Default::default()
+        }
+    }
+
+    /// Whether this is the default (never restart) policy
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether a process that exited (successfully or not) should be restarted
+    pub fn should_restart(&self, exited_successfully: bool) -> bool {
+        match self.mode {
+            RestartMode::Never => false,
+            RestartMode::OnFailure => !exited_successfully,
+            RestartMode::Always => true,
+        }
+    }
+
+    /// Delay before the given restart attempt (0-based), capped at `max_backoff_ms`
+    pub fn backoff_for_attempt(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.max(1.0).powi(attempt.min(63) as i32);
+        let delay_ms = (self.initial_backoff_ms as f64 * factor).min(self.max_backoff_ms as f64);
+        Duration::from_millis(delay_ms as u64)
+    }
+}
@@ -0,0 +1,634 @@
+/// Sandboxing of server processes
+///
+/// Third-party servers otherwise run with all the privileges of the app. A
+/// server can be given a sandbox profile (Linux only):
+///
+/// - `restricted`: `no_new_privs` and a seccomp filter that blocks syscalls
+///   servers have no use for (ptrace, mounts, kernel modules, new
+///   namespaces, ...) and, unless `network` is set, IP sockets.
+/// - `isolated`: the same filter, inside bubblewrap namespaces. The server
+///   only sees system directories and its declared paths, and without
+///   `network` it gets an empty network namespace. Needs `bwrap` on the
+///   `PATH`; connecting fails with `SANDBOX_UNAVAILABLE` otherwise.
+///
+/// Resource limits (rlimits) apply with any profile, on any Unix:
+///
+/// ```json
+/// "sandbox": {
+///   "profile": "isolated",
+///   "memoryMb": 1024,
+///   "cpuSecs": 600,
+///   "maxOpenFiles": 256,
+///   "readPaths": ["~/.nvm"],
+///   "writePaths": ["~/Documents/notes"],
+///   "network": false
+/// }
+/// ```
+///
+/// A plugin-wide sandbox applies to servers without one of their own. For
+/// those with one it is a floor: the stricter profile and the lower limits
+/// win, and declared paths and network access must also be allowed
+/// plugin-wide.
+use crate::error::MCPClientError;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+/// How strongly a server is confined, weakest first
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum SandboxProfile {
+    /// No confinement besides the resource limits
+    #[default]
+    Off,
+    /// `no_new_privs` and a seccomp filter
+    Restricted,
+    /// The restricted profile inside bubblewrap namespaces
+    Isolated,
+}
+
+/// Sandbox of a server process
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxConfig {
+    #[serde(default)]
+    pub profile: SandboxProfile,
+    /// Address space limit in MiB (`RLIMIT_AS`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_mb: Option<u64>,
+    /// CPU time limit in seconds (`RLIMIT_CPU`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_secs: Option<u64>,
+    /// Open file descriptor limit (`RLIMIT_NOFILE`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_open_files: Option<u64>,
+    /// Paths the server may read besides system directories. Absolute or
+    /// starting with `~/`; only enforced by the isolated profile.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub read_paths: Vec<String>,
+    /// Paths the server may read and write
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub write_paths: Vec<String>,
+    /// Allow network access
+    #[serde(default)]
+    pub network: bool,
+}
+
+impl SandboxConfig {
+    pub fn restricted() -> Self {
+        Self {
+            profile: SandboxProfile::Restricted,
+            ..Self::default()
+        }
+    }
+
+    pub fn isolated() -> Self {
+        Self {
+            profile: SandboxProfile::Isolated,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_network(mut self) -> Self {
+        self.network = true;
+        self
+    }
+
+    pub fn with_read_path(mut self, path: impl Into<String>) -> Self {
+        self.read_paths.push(path.into());
+        self
+    }
+
+    pub fn with_write_path(mut self, path: impl Into<String>) -> Self {
+        self.write_paths.push(path.into());
+        self
+    }
+
+    pub fn with_memory_mb(mut self, memory_mb: u64) -> Self {
+        self.memory_mb = Some(memory_mb);
+        self
+    }
+
+    pub fn with_cpu_secs(mut self, cpu_secs: u64) -> Self {
+        self.cpu_secs = Some(cpu_secs);
+        self
+    }
+
+    pub fn with_max_open_files(mut self, max_open_files: u64) -> Self {
+        self.max_open_files = Some(max_open_files);
+        self
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether spawning is affected at all
+    pub fn is_enabled(&self) -> bool {
+        self.profile != SandboxProfile::Off
+            || self.memory_mb.is_some()
+            || self.cpu_secs.is_some()
+            || self.max_open_files.is_some()
+    }
+
+    /// Sandbox of a server given the plugin-wide one, which the server's
+    /// own can only tighten
+    pub fn effective(plugin: &Self, server: &Self) -> Result<Self, MCPClientError> {
+        if server.is_default() {
+            return Ok(plugin.clone());
+        }
+        let mut effective = server.clone();
+        effective.profile = plugin.profile.max(server.profile);
+        effective.memory_mb = lower(plugin.memory_mb, server.memory_mb);
+        effective.cpu_secs = lower(plugin.cpu_secs, server.cpu_secs);
+        effective.max_open_files = lower(plugin.max_open_files, server.max_open_files);
+        if plugin.profile == SandboxProfile::Off {
+            return Ok(effective);
+        }
+
+        effective.network = server.network && plugin.network;
+        let plugin_write = expand_paths(&plugin.write_paths)?;
+        let plugin_read: Vec<PathBuf> = expand_paths(&plugin.read_paths)?
+            .into_iter()
+            .chain(plugin_write.iter().cloned())
+            .collect();
+        for (declared, allowed) in [(&server.write_paths, &plugin_write), (&server.read_paths, &plugin_read)] {
+            for path in declared {
+                let expanded = expand_path(path)?;
+                if !allowed.iter().any(|allowed| expanded.starts_with(allowed)) {
+                    return Err(MCPClientError::sandbox_path_not_allowed(path));
+                }
+            }
+        }
+        Ok(effective)
+    }
+
+    /// Check the sandbox can be set up on this system, before anything is
+    /// spawned
+    pub fn check(&self) -> Result<(), MCPClientError> {
+        let read = expand_paths(&self.read_paths)?;
+        let write = expand_paths(&self.write_paths)?;
+        if self.profile == SandboxProfile::Isolated {
+            if let Some(missing) = write.iter().chain(&read).find(|path| !path.exists()) {
+                return Err(MCPClientError::configuration_error(
+                    "sandbox",
+                    &format!("Declared path '{}' does not exist", missing.display()),
+                ));
+            }
+        }
+        if cfg!(not(unix)) && self.is_enabled() {
+            return Err(MCPClientError::sandbox_unavailable("Sandboxing is only supported on Unix"));
+        }
+        if self.profile >= SandboxProfile::Restricted && !seccomp_supported() {
+            return Err(MCPClientError::sandbox_unavailable(
+                "Seccomp filters are only supported on Linux on x86_64 and aarch64",
+            ));
+        }
+        if self.profile == SandboxProfile::Isolated && bubblewrap_path().is_none() {
+            return Err(MCPClientError::sandbox_unavailable("bubblewrap (bwrap) was not found on the PATH"));
+        }
+        Ok(())
+    }
+}
+
+fn lower(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Expand a leading `~/`, require the path to be absolute and without
+/// `..`, and resolve symlinks in the part of it that exists, so paths can
+/// be compared by their components
+pub fn expand_path(path: &str) -> Result<PathBuf, MCPClientError> {
+    let expanded = match path.strip_prefix("~/") {
+        Some(rest) => std::env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join(rest))
+            .ok_or_else(|| MCPClientError::configuration_error("sandbox", "HOME is not set to expand '~'"))?,
+        None => PathBuf::from(path),
+    };
+    if !expanded.is_absolute() {
+        return Err(MCPClientError::configuration_error(
+            "sandbox",
+            &format!("Sandbox path '{}' must be absolute or start with '~/'", path),
+        ));
+    }
+    if expanded.components().any(|component| component == Component::ParentDir) {
+        return Err(MCPClientError::configuration_error(
+            "sandbox",
+            &format!("Sandbox path '{}' must not contain '..'", path),
+        ));
+    }
+
+    // Keep the missing part as declared, below the resolved existing one
+    let mut missing = Vec::new();
+    let mut existing = expanded.as_path();
+    loop {
+        if let Ok(resolved) = existing.canonicalize() {
+            return Ok(missing.iter().rev().fold(resolved, |resolved, name| resolved.join(name)));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                existing = parent;
+            }
+            _ => return Ok(expanded),
+        }
+    }
+}
+
+fn expand_paths(paths: &[String]) -> Result<Vec<PathBuf>, MCPClientError> {
+    paths.iter().map(|path| expand_path(path)).collect()
+}
+
+/// `bwrap` found on the `PATH`, used by the isolated profile
+pub fn bubblewrap_path() -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join("bwrap"))
+        .find(|candidate| candidate.is_file())
+}
+
+fn seccomp_supported() -> bool {
+    cfg!(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))
+}
+
+/// Command that runs `program` inside the sandbox. The working directory
+/// is set too, inside the namespaces for the isolated profile.
+pub fn command(
+    config: &SandboxConfig,
+    program: &str,
+    args: &[String],
+    cwd: Option<&Path>,
+) -> Result<std::process::Command, MCPClientError> {
+    if let (SandboxProfile::Isolated, Some(cwd)) = (config.profile, cwd) {
+        check_isolated_cwd(config, cwd)?;
+    }
+    config.check()?;
+    #[cfg(unix)]
+    let confinement = imp::Confinement::new(config)?;
+    let mut cmd = match config.profile {
+        SandboxProfile::Isolated => {
+            let mut cmd = std::process::Command::new(bubblewrap_path().unwrap_or_else(|| "bwrap".into()));
+            cmd.args(bubblewrap_args(config, cwd)?);
+            #[cfg(unix)]
+            if let Some(fd) = confinement.filter_fd() {
+                cmd.arg("--seccomp").arg(fd.to_string());
+            }
+            cmd.arg("--").arg(program).args(args);
+            cmd
+        }
+        _ => {
+            let mut cmd = std::process::Command::new(program);
+            cmd.args(args);
+            cmd
+        }
+    };
+    if let Some(cwd) = cwd {
+        cmd.current_dir(cwd);
+    }
+    #[cfg(unix)]
+    confinement.apply(&mut cmd);
+    Ok(cmd)
+}
+
+/// Only declared paths are mounted in the isolated profile, so the working
+/// directory has to be one of them or below one
+fn check_isolated_cwd(config: &SandboxConfig, cwd: &Path) -> Result<(), MCPClientError> {
+    let absolute = if cwd.is_absolute() {
+        cwd.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .map_err(|e| MCPClientError::system_error(&format!("Failed to read the current directory: {}", e)))?
+            .join(cwd)
+    };
+    let resolved = expand_path(&absolute.to_string_lossy())?;
+    let declared = expand_paths(&config.read_paths)?
+        .into_iter()
+        .chain(expand_paths(&config.write_paths)?)
+        .collect::<Vec<_>>();
+    if declared.iter().any(|path| resolved.starts_with(path)) {
+        return Ok(());
+    }
+    Err(MCPClientError::configuration_error(
+        "sandbox",
+        &format!(
+            "Working directory '{}' is not mounted in the isolated sandbox; add it, or a parent directory, to readPaths or writePaths",
+            cwd.display()
+        ),
+    ))
+}
+
+/// bubblewrap options up to the `--` before the server command. The seccomp
+/// filter is passed separately, as `--seccomp <fd>`.
+fn bubblewrap_args(config: &SandboxConfig, cwd: Option<&Path>) -> Result<Vec<String>, MCPClientError> {
+    let mut args: Vec<String> = [
+        "--unshare-user-try",
+        "--unshare-ipc",
+        "--unshare-pid",
+        "--unshare-uts",
+        "--unshare-cgroup-try",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+    if !config.network {
+        args.push("--unshare-net".to_string());
+    }
+    for dir in ["/usr", "/bin", "/sbin", "/lib", "/lib32", "/lib64", "/etc"] {
+        args.extend(["--ro-bind-try".to_string(), dir.to_string(), dir.to_string()]);
+    }
+    args.extend(["--proc", "/proc", "--dev", "/dev", "--tmpfs", "/tmp"].map(String::from));
+    for path in expand_paths(&config.read_paths)? {
+        let path = path.to_string_lossy().into_owned();
+        args.extend(["--ro-bind".to_string(), path.clone(), path]);
+    }
+    for path in expand_paths(&config.write_paths)? {
+        let path = path.to_string_lossy().into_owned();
+        args.extend(["--bind".to_string(), path.clone(), path]);
+    }
+    if let Some(cwd) = cwd {
+        args.extend(["--chdir".to_string(), cwd.to_string_lossy().into_owned()]);
+    }
+    Ok(args)
+}
+
+#[cfg(unix)]
+mod imp {
+    #[cfg(target_os = "linux")]
+    use super::SandboxProfile;
+    use super::SandboxConfig;
+    use crate::error::MCPClientError;
+    use std::os::unix::process::CommandExt;
+
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    type Resource = libc::__rlimit_resource_t;
+    #[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+    type Resource = libc::c_int;
+
+    /// Rlimits, `no_new_privs` and seccomp filter, applied between fork
+    /// and exec
+    pub(super) struct Confinement {
+        rlimits: Vec<(Resource, u64)>,
+        /// Filter installed by the child itself (restricted profile)
+        #[cfg(target_os = "linux")]
+        filter: Option<Vec<libc::sock_filter>>,
+        /// Filter handed to bwrap, which sets `no_new_privs` and installs it
+        /// itself once the namespaces are set up (isolated profile)
+        #[cfg(target_os = "linux")]
+        filter_file: Option<std::fs::File>,
+    }
+
+    impl Confinement {
+        pub(super) fn new(config: &SandboxConfig) -> Result<Self, MCPClientError> {
+            let rlimits = [
+                (libc::RLIMIT_AS, config.memory_mb.map(|mb| mb.saturating_mul(1024 * 1024))),
+                (libc::RLIMIT_CPU, config.cpu_secs),
+                (libc::RLIMIT_NOFILE, config.max_open_files),
+            ]
+            .into_iter()
+            .filter_map(|(resource, limit)| limit.map(|limit| (resource, limit)))
+            .collect();
+            Ok(Self {
+                rlimits,
+                #[cfg(target_os = "linux")]
+                filter: (config.profile == SandboxProfile::Restricted).then(|| seccomp::filter(config.network)),
+                #[cfg(target_os = "linux")]
+                filter_file: match config.profile {
+                    SandboxProfile::Isolated => Some(seccomp::filter_file(&seccomp::filter(config.network))?),
+                    _ => None,
+                },
+            })
+        }
+
+        pub(super) fn filter_fd(&self) -> Option<std::os::unix::io::RawFd> {
+            #[cfg(target_os = "linux")]
+            {
+                use std::os::unix::io::AsRawFd;
+                self.filter_file.as_ref().map(|file| file.as_raw_fd())
+            }
+            #[cfg(not(target_os = "linux"))]
+            None
+        }
+
+        pub(super) fn apply(self, cmd: &mut std::process::Command) {
+            let filter_fd = self.filter_fd();
+            if self.rlimits.is_empty() && filter_fd.is_none() && !self.has_filter() {
+                return;
+            }
+            // Only async-signal-safe calls in here: it runs in the forked child.
+            // The closure owns the filter file, keeping it open until the spawn.
+            unsafe {
+                cmd.pre_exec(move || {
+                    for &(resource, limit) in &self.rlimits {
+                        set_rlimit(resource, limit)?;
+                    }
+                    if let Some(fd) = filter_fd {
+                        if libc::fcntl(fd, libc::F_SETFD, 0) != 0 {
+                            return Err(std::io::Error::last_os_error());
+                        }
+                    }
+                    #[cfg(target_os = "linux")]
+                    if let Some(ref filter) = self.filter {
+                        seccomp::install(filter)?;
+                    }
+                    Ok(())
+                });
+            }
+        }
+
+        fn has_filter(&self) -> bool {
+            #[cfg(target_os = "linux")]
+            return self.filter.is_some();
+            #[cfg(not(target_os = "linux"))]
+            false
+        }
+    }
+
+    /// Lower both the soft and the hard limit, so the server can't raise
+    /// them again
+    fn set_rlimit(resource: Resource, limit: u64) -> std::io::Result<()> {
+        let mut current = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+        if unsafe { libc::getrlimit(resource, &mut current) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let limit = (limit as libc::rlim_t).min(current.rlim_max);
+        let new = libc::rlimit { rlim_cur: limit, rlim_max: limit };
+        if unsafe { libc::setrlimit(resource, &new) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    pub(super) mod seccomp {
+        use crate::error::MCPClientError;
+        use libc::sock_filter;
+
+        #[cfg(target_arch = "x86_64")]
+        const AUDIT_ARCH: u32 = 0xC000_003E;
+        #[cfg(target_arch = "aarch64")]
+        const AUDIT_ARCH: u32 = 0xC000_00B7;
+        #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+        const AUDIT_ARCH: u32 = 0;
+
+        /// Offsets into `struct seccomp_data`; the low half of the first
+        /// argument on little-endian targets
+        const NR_OFFSET: u32 = 0;
+        const ARCH_OFFSET: u32 = 4;
+        const ARG0_OFFSET: u32 = 16;
+
+        const CLONE_NAMESPACES: u32 = (libc::CLONE_NEWNS
+            | libc::CLONE_NEWUSER
+            | libc::CLONE_NEWNET
+            | libc::CLONE_NEWPID
+            | libc::CLONE_NEWIPC
+            | libc::CLONE_NEWUTS
+            | libc::CLONE_NEWCGROUP) as u32;
+
+        /// Syscalls that fail, with the errno returned
+        #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+        fn denied() -> Vec<(libc::c_long, libc::c_int)> {
+            let mut denied = vec![
+                (libc::SYS_ptrace, libc::EPERM),
+                (libc::SYS_process_vm_readv, libc::EPERM),
+                (libc::SYS_process_vm_writev, libc::EPERM),
+                (libc::SYS_mount, libc::EPERM),
+                (libc::SYS_umount2, libc::EPERM),
+                (libc::SYS_pivot_root, libc::EPERM),
+                (libc::SYS_chroot, libc::EPERM),
+                (libc::SYS_unshare, libc::EPERM),
+                (libc::SYS_setns, libc::EPERM),
+                (libc::SYS_kexec_load, libc::EPERM),
+                (libc::SYS_kexec_file_load, libc::EPERM),
+                (libc::SYS_init_module, libc::EPERM),
+                (libc::SYS_finit_module, libc::EPERM),
+                (libc::SYS_delete_module, libc::EPERM),
+                (libc::SYS_reboot, libc::EPERM),
+                (libc::SYS_swapon, libc::EPERM),
+                (libc::SYS_swapoff, libc::EPERM),
+                (libc::SYS_acct, libc::EPERM),
+                (libc::SYS_bpf, libc::EPERM),
+                (libc::SYS_perf_event_open, libc::EPERM),
+                (libc::SYS_userfaultfd, libc::EPERM),
+                (libc::SYS_open_by_handle_at, libc::EPERM),
+                (libc::SYS_keyctl, libc::EPERM),
+                (libc::SYS_add_key, libc::EPERM),
+                (libc::SYS_request_key, libc::EPERM),
+                // Runtimes such as libuv fall back to threads on ENOSYS
+                (libc::SYS_io_uring_setup, libc::ENOSYS),
+                (libc::SYS_io_uring_enter, libc::ENOSYS),
+                (libc::SYS_io_uring_register, libc::ENOSYS),
+                // clone3 passes its flags in memory the filter can't read;
+                // libc falls back to clone, whose flags are checked below
+                (libc::SYS_clone3, libc::ENOSYS),
+            ];
+            #[cfg(target_arch = "x86_64")]
+            denied.extend([(libc::SYS_iopl, libc::EPERM), (libc::SYS_ioperm, libc::EPERM)]);
+            denied
+        }
+
+        #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+        fn denied() -> Vec<(libc::c_long, libc::c_int)> {
+            Vec::new()
+        }
+
+        fn load(offset: u32) -> sock_filter {
+            statement(libc::BPF_LD | libc::BPF_W | libc::BPF_ABS, offset)
+        }
+
+        fn statement(code: u32, k: u32) -> sock_filter {
+            jump(code, k, 0, 0)
+        }
+
+        fn jump(code: u32, k: u32, jt: u8, jf: u8) -> sock_filter {
+            sock_filter { code: code as u16, jt, jf, k }
+        }
+
+        fn ret(action: u32) -> sock_filter {
+            statement(libc::BPF_RET | libc::BPF_K, action)
+        }
+
+        fn errno(errno: libc::c_int) -> u32 {
+            libc::SECCOMP_RET_ERRNO | (errno as u32 & libc::SECCOMP_RET_DATA)
+        }
+
+        /// The filter program: deny-list, namespace flags of `clone`, and IP
+        /// sockets unless the network is allowed
+        pub(crate) fn filter(network: bool) -> Vec<sock_filter> {
+            let jeq = libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K;
+            let mut program = vec![
+                load(ARCH_OFFSET),
+                jump(jeq, AUDIT_ARCH, 1, 0),
+                ret(libc::SECCOMP_RET_KILL_PROCESS),
+                load(NR_OFFSET),
+            ];
+            // x32 syscalls share the x86_64 audit arch
+            #[cfg(target_arch = "x86_64")]
+            program.extend([
+                jump(libc::BPF_JMP | libc::BPF_JGE | libc::BPF_K, 0x4000_0000, 0, 1),
+                ret(errno(libc::EPERM)),
+            ]);
+            for (nr, code) in denied() {
+                program.extend([jump(jeq, nr as u32, 0, 1), ret(errno(code))]);
+            }
+            program.extend([
+                jump(jeq, libc::SYS_clone as u32, 0, 4),
+                load(ARG0_OFFSET),
+                jump(libc::BPF_JMP | libc::BPF_JSET | libc::BPF_K, CLONE_NAMESPACES, 0, 1),
+                ret(errno(libc::EPERM)),
+                ret(libc::SECCOMP_RET_ALLOW),
+            ]);
+            if !network {
+                program.extend([
+                    jump(jeq, libc::SYS_socket as u32, 0, 4),
+                    load(ARG0_OFFSET),
+                    jump(jeq, libc::AF_INET as u32, 1, 0),
+                    jump(jeq, libc::AF_INET6 as u32, 0, 1),
+                    ret(errno(libc::EACCES)),
+                ]);
+            }
+            program.push(ret(libc::SECCOMP_RET_ALLOW));
+            program
+        }
+
+        /// Set `no_new_privs` and load the filter into the calling process
+        pub(crate) fn install(filter: &[sock_filter]) -> std::io::Result<()> {
+            let program = libc::sock_fprog {
+                len: filter.len() as libc::c_ushort,
+                filter: filter.as_ptr() as *mut sock_filter,
+            };
+            unsafe {
+                if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0
+                    || libc::prctl(libc::PR_SET_SECCOMP, libc::SECCOMP_MODE_FILTER, &program) != 0
+                {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        }
+
+        /// In-memory file holding the compiled filter, for `bwrap --seccomp`.
+        /// It is close-on-exec until the server's child clears the flag.
+        pub(crate) fn filter_file(filter: &[sock_filter]) -> Result<std::fs::File, MCPClientError> {
+            use std::io::{Seek, Write};
+            use std::os::unix::io::FromRawFd;
+
+            let fd = unsafe { libc::memfd_create(c"mcp-seccomp".as_ptr(), libc::MFD_CLOEXEC) };
+            if fd < 0 {
+                return Err(MCPClientError::sandbox_unavailable(&format!(
+                    "Failed to create the seccomp filter file: {}",
+                    std::io::Error::last_os_error()
+                )));
+            }
+            let mut file = unsafe { std::fs::File::from_raw_fd(fd) };
+            let bytes = unsafe {
+                std::slice::from_raw_parts(filter.as_ptr() as *const u8, std::mem::size_of_val(filter))
+            };
+            file.write_all(bytes)
+                .and_then(|_| file.rewind())
+                .map_err(|e| MCPClientError::sandbox_unavailable(&format!("Failed to write the seccomp filter: {}", e)))?;
+            Ok(file)
+        }
+    }
+}
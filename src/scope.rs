@@ -0,0 +1,264 @@
+/// Capability scopes limiting which servers, tools and resources a window
+/// may use
+///
+/// Scope entries name a server id, a tool name and/or a resource URI, each
+/// an exact string or a glob. They can be set for the whole plugin through
+/// the `mcp-client:scope` permission, or per command on its `allow-*`
+/// permission:
+///
+/// ```json
+/// "permissions": [
+///   { "identifier": "mcp-client:scope", "allow": [{ "server": "github" }] },
+///   {
+///     "identifier": "mcp-client:allow-plugin-execute-tool",
+///     "allow": [{ "server": "github", "tool": "search_*" }],
+///     "deny": [{ "tool": "delete_*" }]
+///   }
+/// ]
+/// ```
+///
+/// Without allow entries everything not denied is permitted. An entry
+/// without a `tool` or `resource` applies to the whole server; one with a
+/// `tool` only to tools, one with a `resource` only to resources. When both
+/// the plugin-wide and the command scope are set, both must permit access.
+///
+/// Entries with a `command` are spawn entries (see `SpawnScope`) and only
+/// apply to the servers a command starts, for the server ids they match.
+/// In the plugin-wide scope they cover every command that can start one.
+use crate::error::MCPClientError;
+use crate::policy::{glob_match, SpawnRequest, SpawnScope};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::ipc::{CommandScope, GlobalScope};
+
+/// One allow or deny entry of a capability scope
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct McpScope {
+    /// Server id; any server when unset
+    #[serde(default)]
+    pub server: Option<String>,
+    /// Tool name
+    #[serde(default)]
+    pub tool: Option<String>,
+    /// Resource URI
+    #[serde(default)]
+    pub resource: Option<String>,
+    /// Command a started server runs
+    #[serde(default)]
+    pub command: Option<String>,
+    /// Patterns for the arguments of a started server
+    #[serde(default)]
+    pub args: Option<Vec<String>>,
+    /// Patterns for the environment variables of a started server
+    #[serde(default)]
+    pub env_keys: Option<Vec<String>>,
+    /// Patterns for the working directory of a started server
+    #[serde(default)]
+    pub cwd: Option<Vec<String>>,
+}
+
+impl McpScope {
+    fn is_spawn_entry(&self) -> bool {
+        self.command.is_some()
+    }
+
+    /// Spawn rule of an entry with a `command`
+    fn spawn_scope(&self) -> Option<Arc<SpawnScope>> {
+        self.command.as_ref().map(|command| {
+            Arc::new(SpawnScope {
+                command: command.clone(),
+                args: self.args.clone(),
+                env_keys: self.env_keys.clone(),
+                cwd: self.cwd.clone(),
+            })
+        })
+    }
+
+    fn matches_server(&self, server_id: &str) -> bool {
+        self.server.as_deref().is_none_or(|pattern| glob_match(pattern, server_id))
+    }
+
+    fn is_server_wide(&self) -> bool {
+        self.tool.is_none() && self.resource.is_none()
+    }
+
+    fn matches_tool(&self, server_id: &str, tool: &str) -> bool {
+        self.matches_server(server_id)
+            && match self.tool {
+                Some(ref pattern) => glob_match(pattern, tool),
+                None => self.resource.is_none(),
+            }
+    }
+
+    fn matches_resource(&self, server_id: &str, uri: &str) -> bool {
+        self.matches_server(server_id)
+            && match self.resource {
+                Some(ref pattern) => glob_match(pattern, uri),
+                None => self.tool.is_none(),
+            }
+    }
+}
+
+/// Allow and deny entries of one scope
+#[derive(Debug, Clone, Default)]
+struct Layer {
+    allow: Vec<Arc<McpScope>>,
+    deny: Vec<Arc<McpScope>>,
+    spawn_allow: Vec<Arc<McpScope>>,
+    spawn_deny: Vec<Arc<McpScope>>,
+}
+
+impl Layer {
+    fn new(allow: Vec<Arc<McpScope>>, deny: Vec<Arc<McpScope>>) -> Self {
+        let (spawn_allow, allow) = allow.into_iter().partition(|entry| entry.is_spawn_entry());
+        let (spawn_deny, deny) = deny.into_iter().partition(|entry| entry.is_spawn_entry());
+        Self {
+            allow,
+            deny,
+            spawn_allow,
+            spawn_deny,
+        }
+    }
+
+    fn check_spawn(&self, server_id: &str, request: &SpawnRequest) -> Result<(), MCPClientError> {
+        let rules = |entries: &[Arc<McpScope>]| -> Vec<Arc<SpawnScope>> {
+            entries
+                .iter()
+                .filter(|entry| entry.matches_server(server_id))
+                .filter_map(|entry| entry.spawn_scope())
+                .collect()
+        };
+        let allow = rules(&self.spawn_allow);
+        // Allow entries for other servers still leave this one unpermitted
+        if allow.is_empty() && !self.spawn_allow.is_empty() {
+            return Err(MCPClientError::spawn_denied_by_scope(request.command));
+        }
+        SpawnScope::check(&allow, &rules(&self.spawn_deny), request)
+    }
+
+    fn permits(&self, denied: impl Fn(&McpScope) -> bool, allowed: impl Fn(&McpScope) -> bool) -> bool {
+        !self.deny.iter().any(|entry| denied(entry))
+            && (self.allow.is_empty() || self.allow.iter().any(|entry| allowed(entry)))
+    }
+}
+
+/// Scopes that apply to one command invocation. The plugin-wide scope and
+/// the command's own scope must both permit an access.
+#[derive(Debug, Clone, Default)]
+pub struct ScopeRules {
+    layers: Vec<Layer>,
+}
+
+impl ScopeRules {
+    pub fn new(allow: Vec<Arc<McpScope>>, deny: Vec<Arc<McpScope>>) -> Self {
+        Self {
+            layers: vec![Layer::new(allow, deny)],
+        }
+    }
+
+    /// Add another scope that must permit accesses as well
+    pub fn and(mut self, allow: Vec<Arc<McpScope>>, deny: Vec<Arc<McpScope>>) -> Self {
+        self.layers.push(Layer::new(allow, deny));
+        self
+    }
+
+    /// Rules of the plugin-wide scope together with the command's own
+    pub fn from_scopes(global: &GlobalScope<McpScope>, command: &CommandScope<McpScope>) -> Self {
+        Self::from_global(global).and(command.allows().clone(), command.denies().clone())
+    }
+
+    /// Rules of the plugin-wide scope only
+    pub fn from_global(global: &GlobalScope<McpScope>) -> Self {
+        Self::new(global.allows().clone(), global.denies().clone())
+    }
+
+    pub fn is_unrestricted(&self) -> bool {
+        self.layers
+            .iter()
+            .all(|layer| layer.allow.is_empty() && layer.deny.is_empty())
+    }
+
+    /// Whether the server may be used at all. Only server-wide deny
+    /// entries block a server; allow entries for one of its tools or
+    /// resources permit it.
+    pub fn server_allowed(&self, server_id: &str) -> bool {
+        self.layers.iter().all(|layer| {
+            layer.permits(
+                |entry| entry.is_server_wide() && entry.matches_server(server_id),
+                |entry| entry.matches_server(server_id),
+            )
+        })
+    }
+
+    pub fn tool_allowed(&self, server_id: &str, tool: &str) -> bool {
+        let matches = |entry: &McpScope| entry.matches_tool(server_id, tool);
+        self.layers.iter().all(|layer| layer.permits(matches, matches))
+    }
+
+    pub fn resource_allowed(&self, server_id: &str, uri: &str) -> bool {
+        let matches = |entry: &McpScope| entry.matches_resource(server_id, uri);
+        self.layers.iter().all(|layer| layer.permits(matches, matches))
+    }
+
+    pub fn check_server(&self, server_id: &str) -> Result<(), MCPClientError> {
+        if self.server_allowed(server_id) {
+            Ok(())
+        } else {
+            Err(MCPClientError::not_permitted("server", server_id))
+        }
+    }
+
+    /// Check a server the command would start against the spawn entries
+    pub fn check_spawn(&self, server_id: &str, request: &SpawnRequest) -> Result<(), MCPClientError> {
+        self.layers
+            .iter()
+            .try_for_each(|layer| layer.check_spawn(server_id, request))
+    }
+
+    pub fn check_tool(&self, server_id: &str, tool: &str) -> Result<(), MCPClientError> {
+        self.check_server(server_id)?;
+        if self.tool_allowed(server_id, tool) {
+            Ok(())
+        } else {
+            Err(MCPClientError::not_permitted("tool", tool))
+        }
+    }
+
+    pub fn check_resource(&self, server_id: &str, uri: &str) -> Result<(), MCPClientError> {
+        self.check_server(server_id)?;
+        if self.resource_allowed(server_id, uri) {
+            Ok(())
+        } else {
+            Err(MCPClientError::not_permitted("resource", uri))
+        }
+    }
+
+    /// Drop the tools of a `tools/list` result the scope doesn't permit
+    pub fn filter_tools(&self, server_id: &str, result: &mut serde_json::Value) {
+        self.filter_list(result, "tools", |tool| {
+            tool.get("name")
+                .and_then(|name| name.as_str())
+                .is_some_and(|name| self.tool_allowed(server_id, name))
+        });
+    }
+
+    /// Drop the resources of a `resources/list` result the scope doesn't permit
+    pub fn filter_resources(&self, server_id: &str, result: &mut serde_json::Value) {
+        self.filter_list(result, "resources", |resource| {
+            resource
+                .get("uri")
+                .and_then(|uri| uri.as_str())
+                .is_some_and(|uri| self.resource_allowed(server_id, uri))
+        });
+    }
+
+    fn filter_list(&self, result: &mut serde_json::Value, field: &str, keep: impl Fn(&serde_json::Value) -> bool) {
+        if self.is_unrestricted() {
+            return;
+        }
+        if let Some(items) = result.get_mut(field).and_then(|items| items.as_array_mut()) {
+            items.retain(keep);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+/// Rust-side secret resolution for server credentials
+///
+/// Secrets are referenced by name from a server's environment
+/// (`env: { GITHUB_TOKEN: { secret: "github" } }`) and only resolved to their
+/// values at spawn time, so they never pass through the webview.
+use crate::error::{ErrorCategory, MCPClientError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Placeholder written in place of secret values in events, logs and debug output
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Source of secret values implemented by the host application
+/// (for example an OS keyring or an encrypted file)
+pub trait SecretProvider: Send + Sync {
+    /// Look up the secret stored under `name`
+    fn get_secret(&self, name: &str) -> Result<String, MCPClientError>;
+}
+
+/// Simple in-memory secret provider, mainly useful for tests and prototyping
+#[derive(Default)]
+pub struct MemorySecretProvider {
+    secrets: HashMap<String, String>,
+}
+
+impl MemorySecretProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a secret to the provider
+    pub fn with_secret(mut self, name: &str, value: &str) -> Self {
+        self.secrets.insert(name.to_string(), value.to_string());
+        self
+    }
+}
+
+impl SecretProvider for MemorySecretProvider {
+    fn get_secret(&self, name: &str) -> Result<String, MCPClientError> {
+        self.secrets
+            .get(name)
+            .cloned()
+            .ok_or_else(|| MCPClientError::secret_not_found(name))
+    }
+}
+
+/// Environment variable value: either a literal or a reference to a named secret
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum EnvValue {
+    Plain(String),
+    Secret { secret: String },
+}
+
+impl From<&str> for EnvValue {
+    fn from(value: &str) -> Self {
+        EnvValue::Plain(value.to_string())
+    }
+}
+
+/// Replaces known secret values with [`REDACTED`]
+#[derive(Clone, Default)]
+pub struct SecretRedactor {
+    secrets: Vec<String>,
+}
+
+impl SecretRedactor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a secret value that must never be shown
+    pub fn add_secret(&mut self, value: &str) {
+        // Empty values would match everywhere
+        if !value.is_empty() && !self.secrets.iter().any(|s| s == value) {
+            self.secrets.push(value.to_string());
+            // Longest first so a secret containing another one is fully replaced
+            self.secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+        }
+    }
+
+    /// Whether there is anything to redact
+    pub fn is_empty(&self) -> bool {
+        self.secrets.is_empty()
+    }
+
+    /// Redact secret values from a string
+    pub fn redact(&self, text: &str) -> String {
+        let mut redacted = text.to_string();
+        for secret in &self.secrets {
+            if redacted.contains(secret.as_str()) {
+                redacted = redacted.replace(secret.as_str(), REDACTED);
+            }
+        }
+        redacted
+    }
+
+    /// Redact secret values from every string inside a JSON value
+    pub fn redact_value(&self, value: &serde_json::Value) -> serde_json::Value {
+        if self.is_empty() {
+            return value.clone();
+        }
+        match value {
+            serde_json::Value::String(s) => serde_json::Value::String(self.redact(s)),
+            serde_json::Value::Array(items) => {
+                serde_json::Value::Array(items.iter().map(|v| self.redact_value(v)).collect())
+            }
+            serde_json::Value::Object(map) => serde_json::Value::Object(
+                map.iter()
+                    .map(|(k, v)| (k.clone(), self.redact_value(v)))
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    /// Redact secret values from an error's message and details
+    pub fn redact_error(&self, mut error: MCPClientError) -> MCPClientError {
+        if self.is_empty() {
+            return error;
+        }
+        error.message = self.redact(&error.message);
+        error.details = error.details.map(|d| self.redact(&d));
+        error
+    }
+}
+
+impl fmt::Debug for SecretRedactor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Never print the secret values themselves
+        f.debug_struct("SecretRedactor")
+            .field("secrets", &self.secrets.len())
+            .finish()
+    }
+}
+
+/// Environment resolved for spawning, together with a redactor for its secrets
+#[derive(Default)]
+pub struct ResolvedEnv {
+    pub vars: HashMap<String, String>,
+    pub redactor: SecretRedactor,
+}
+
+/// Resolve an environment map, looking up secret references through `provider`
+pub fn resolve_env(
+    env: &HashMap<String, EnvValue>,
+    provider: Option<&dyn SecretProvider>,
+) -> Result<ResolvedEnv, MCPClientError> {
+    let mut resolved = ResolvedEnv::default();
+
+    for (key, value) in env {
+        match value {
+            EnvValue::Plain(value) => {
+                resolved.vars.insert(key.clone(), value.clone());
+            }
+            EnvValue::Secret { secret } => {
+                let provider = provider.ok_or_else(|| {
+                    MCPClientError::new(
+                        ErrorCategory::Configuration,
+                        "SECRET_PROVIDER_MISSING",
+                        &format!("No secret provider available to resolve '{}'", secret),
+                    )
+                    .with_details(&format!(
+                        "Environment variable {} references a secret but no provider is registered",
+                        key
+                    ))
+                    .with_suggestions(vec![
+                        "Register a secret provider with Builder::secret_provider",
+                        "Use a plain value for this environment variable",
+                    ])
+                })?;
+                let value = provider.get_secret(secret)?;
+                resolved.redactor.add_secret(&value);
+                resolved.vars.insert(key.clone(), value);
+            }
+        }
+    }
+
+    Ok(resolved)
+}
@@ -0,0 +1,135 @@
+/// Plugin-wide settings, read from the `plugins.mcp` section of
+/// `tauri.conf.json` and overridden by the Rust `Builder`
+///
+/// ```json
+/// {
+///   "plugins": {
+///     "mcp": {
+///       "requestTimeoutMs": 10000,
+///       "maxConnections": 4,
+///       "spawnPolicy": { "commands": ["npx", "uvx"], "envKeys": ["GITHUB_*"] },
+///       "approval": { "mode": "destructive", "allowedTools": ["github/search_*"] },
+///       "eventPrefix": "mcp",
+///       "logLevel": "warn"
+///     }
+///   }
+/// }
+/// ```
+use crate::approval::ApprovalPolicy;
+use crate::audit::AuditConfig;
+use crate::limits::RateLimits;
+use crate::logging::LogLevel;
+use crate::policy::SpawnPolicy;
+use crate::restart::RestartPolicy;
+use crate::sandbox::SandboxConfig;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Event prefix used unless configured otherwise
+pub const DEFAULT_EVENT_PREFIX: &str = "mcp";
+
+fn default_request_timeout_ms() -> u64 {
+    5_000
+}
+
+fn default_tool_timeout_ms() -> u64 {
+    10_000
+}
+
+fn default_exit_timeout_ms() -> u64 {
+    crate::DEFAULT_EXIT_TIMEOUT.as_millis() as u64
+}
+
+fn default_event_prefix() -> String {
+    DEFAULT_EVENT_PREFIX.to_string()
+}
+
+/// Settings shared by every connection of the plugin
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginConfig {
+    /// Timeout for `initialize`, listing and other requests
+    #[serde(default = "default_request_timeout_ms")]
+    pub request_timeout_ms: u64,
+    /// Timeout for `tools/call`
+    #[serde(default = "default_tool_timeout_ms")]
+    pub tool_timeout_ms: u64,
+    /// How long app exit waits for servers to shut down
+    #[serde(default = "default_exit_timeout_ms")]
+    pub exit_timeout_ms: u64,
+    /// Maximum number of servers connected at the same time
+    #[serde(default)]
+    pub max_connections: Option<usize>,
+    /// Commands, arguments and environment variables servers may be spawned with
+    #[serde(default)]
+    pub spawn_policy: SpawnPolicy,
+    /// Which tool calls wait for the user's approval
+    #[serde(default)]
+    pub approval: ApprovalPolicy,
+    /// Audit file of spawns and tool calls
+    #[serde(default)]
+    pub audit: AuditConfig,
+    /// Server configuration file, relative to the app config dir
+    #[serde(default)]
+    pub config_file: Option<PathBuf>,
+    /// Directory traffic `recordFile`s are written to and replay sessions
+    /// read from, relative to the app data dir. Both are refused without it.
+    #[serde(default)]
+    pub session_dir: Option<PathBuf>,
+    /// Rate limits and concurrency quotas applied to every server, on top
+    /// of the server's own
+    #[serde(default)]
+    pub limits: RateLimits,
+    /// Sandbox for servers without their own, and the floor for those with one
+    #[serde(default)]
+    pub sandbox: SandboxConfig,
+    /// Restart policy for servers that don't set their own
+    #[serde(default)]
+    pub restart: RestartPolicy,
+    /// Prefix of emitted event names (`<prefix>://server-connected`)
+    #[serde(default = "default_event_prefix")]
+    pub event_prefix: String,
+    /// Most verbose level the plugin emits through `tracing`
+    #[serde(default)]
+    pub log_level: LogLevel,
+    /// Include message payloads (tool arguments, results, server output) in debug events
+    #[serde(default)]
+    pub log_payloads: bool,
+}
+
+impl Default for PluginConfig {
+    fn default() -> Self {
+        Self {
+            request_timeout_ms: default_request_timeout_ms(),
+            tool_timeout_ms: default_tool_timeout_ms(),
+            exit_timeout_ms: default_exit_timeout_ms(),
+            max_connections: None,
+            spawn_policy: SpawnPolicy::default(),
+            approval: ApprovalPolicy::default(),
+            audit: AuditConfig::default(),
+            config_file: None,
+            session_dir: None,
+            limits: RateLimits::default(),
+            sandbox: SandboxConfig::default(),
+            restart: RestartPolicy::default(),
+            event_prefix: default_event_prefix(),
+            log_level: LogLevel::default(),
+            log_payloads: false,
+        }
+    }
+}
+
+impl PluginConfig {
+    pub fn exit_timeout(&self) -> Duration {
+        Duration::from_millis(self.exit_timeout_ms)
+    }
+
+    /// Full event name for an `mcp://...` event constant
+    pub fn event_name(&self, event: &str) -> String {
+        match event.strip_prefix("mcp://") {
+            Some(name) => format!("{}://{}", self.event_prefix, name),
+            None => event.to_string(),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+/// Graceful shutdown of server processes
+///
+/// Follows the order recommended by the MCP stdio transport: close the
+/// server's stdin, wait for it to exit, then SIGTERM, then SIGKILL.
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+fn default_stdin_timeout_ms() -> u64 {
+    2_000
+}
+
+fn default_terminate_timeout_ms() -> u64 {
+    2_000
+}
+
+/// Grace periods for each shutdown step
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ShutdownConfig {
+    /// How long to wait for the server to exit after its stdin is closed
+    #[serde(default = "default_stdin_timeout_ms")]
+    pub stdin_timeout_ms: u64,
+    /// How long to wait after SIGTERM before sending SIGKILL
+    #[serde(default = "default_terminate_timeout_ms")]
+    pub terminate_timeout_ms: u64,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            stdin_timeout_ms: default_stdin_timeout_ms(),
+            terminate_timeout_ms: default_terminate_timeout_ms(),
+        }
+    }
+}
+
+impl ShutdownConfig {
+    /// Skip the grace periods and kill right away
+    pub fn immediate() -> Self {
+        Self {
+            stdin_timeout_ms: 0,
+            terminate_timeout_ms: 0,
+        }
+    }
+
+    /// Shorten the grace periods so the whole sequence, including the
+    /// process group cleanup, fits within `deadline`
+    pub fn within(&self, deadline: Duration) -> Self {
+        let deadline_ms = deadline.as_millis().min(u64::MAX as u128) as u64;
+        Self {
+            stdin_timeout_ms: self.stdin_timeout_ms.min(deadline_ms / 2),
+            terminate_timeout_ms: self.terminate_timeout_ms.min(deadline_ms / 4),
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn stdin_timeout(&self) -> Duration {
+        Duration::from_millis(self.stdin_timeout_ms)
+    }
+
+    pub fn terminate_timeout(&self) -> Duration {
+        Duration::from_millis(self.terminate_timeout_ms)
+    }
+}
+
+/// The shutdown step that actually ended the process
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ShutdownStep {
+    /// The process had exited before shutdown started
+    AlreadyExited,
+    /// The process exited after its stdin was closed
+    StdinClosed,
+    /// The process exited after SIGTERM
+    Terminated,
+    /// The process had to be killed
+    Killed,
+}
+
+impl ShutdownStep {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            ShutdownStep::AlreadyExited => "process had already exited",
+            ShutdownStep::StdinClosed => "process exited after stdin was closed",
+            ShutdownStep::Terminated => "process exited after SIGTERM",
+            ShutdownStep::Killed => "process was killed",
+        }
+    }
+}
+
+impl std::fmt::Display for ShutdownStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.describe())
+    }
+}
@@ -0,0 +1,85 @@
+/// Lifecycle of a server connection
+///
+/// Connecting goes `Spawning` -> `Initializing` -> `Ready`, and a ready server
+/// that stops answering pings is `Degraded` until it answers again. A
+/// disconnect goes through `Disconnecting` to `Disconnected`; a process that
+/// exits on its own goes straight to `Disconnected`, then to `Reconnecting`
+/// when its restart policy applies. A server that can't be started, or whose
+/// restarts were given up, is `Failed`.
+///
+/// Every transition is emitted as `mcp://connection-changed`, with the
+/// previous state. Disconnected and failed entries stay in the connection
+/// list with their last error until the server is connected again.
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// State of a server connection
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConnectionState {
+    /// The server process is being started
+    Spawning,
+    /// The process runs and the MCP `initialize` handshake is under way
+    Initializing,
+    /// Connected and answering requests
+    Ready,
+    /// Connected, but not answering liveness pings
+    Degraded,
+    /// Waiting to restart after the process went away
+    Reconnecting,
+    /// The process is being shut down
+    Disconnecting,
+    /// The process has stopped, on request or on its own
+    Disconnected,
+    /// The server couldn't be started, or restarts were given up
+    Failed,
+}
+
+impl ConnectionState {
+    /// Whether a connection in this state may move to `next`
+    pub fn can_transition_to(self, next: ConnectionState) -> bool {
+        use ConnectionState::*;
+        matches!(
+            (self, next),
+            (Spawning, Initializing | Failed | Disconnecting | Disconnected)
+                | (Initializing, Ready | Failed | Disconnecting | Disconnected)
+                | (Ready, Degraded | Disconnecting | Disconnected)
+                | (Degraded, Ready | Disconnecting | Disconnected)
+                | (Reconnecting, Spawning | Failed | Disconnecting | Disconnected)
+                | (Disconnecting, Disconnected)
+                | (Disconnected, Spawning | Reconnecting | Failed)
+                | (Failed, Spawning | Reconnecting | Disconnected)
+        )
+    }
+
+    /// Whether a process belongs to the connection in this state
+    pub fn is_active(self) -> bool {
+        matches!(
+            self,
+            ConnectionState::Spawning
+                | ConnectionState::Initializing
+                | ConnectionState::Ready
+                | ConnectionState::Degraded
+                | ConnectionState::Disconnecting
+        )
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ConnectionState::Spawning => "spawning",
+            ConnectionState::Initializing => "initializing",
+            ConnectionState::Ready => "ready",
+            ConnectionState::Degraded => "degraded",
+            ConnectionState::Reconnecting => "reconnecting",
+            ConnectionState::Disconnecting => "disconnecting",
+            ConnectionState::Disconnected => "disconnected",
+            ConnectionState::Failed => "failed",
+        }
+    }
+}
+
+impl fmt::Display for ConnectionState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
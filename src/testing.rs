@@ -0,0 +1,391 @@
+/// In-process mock MCP server for tests (`testing` feature)
+///
+/// The mock runs on a background thread and talks to the plugin over
+/// in-memory pipes, so apps can test against the plugin without Node.js or
+/// any server installed:
+///
+/// ```ignore
+/// use tauri_plugin_mcp_client::testing::{MockFault, MockServer};
+///
+/// let server = MockServer::new("mock", "1.0.0")
+///     .with_echo_tool()
+///     .resource("file:///readme.md", "readme", "text/markdown", "# Hello")
+///     .fault("slow_tool", MockFault::NoResponse);
+/// registry.connect_mock("mock".to_string(), server).await?;
+/// ```
+///
+/// Delays, faults and notifications are triggered by a method name
+/// (`tools/list`) or, for `tools/call`, by a tool name.
+use crate::process::{PROTOCOL_VERSION, SUPPORTED_PROTOCOL_VERSIONS};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+type ToolHandler = Arc<dyn Fn(&Value) -> Result<Value, String> + Send + Sync>;
+
+/// Misbehavior the mock shows when a request triggers it
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockFault {
+    /// Answer with a line that is not valid JSON instead of the response
+    Malformed,
+    /// Never answer the request
+    NoResponse,
+    /// Exit with this code as if the server process crashed
+    Crash(i32),
+}
+
+#[derive(Clone)]
+struct MockTool {
+    name: String,
+    description: String,
+    input_schema: Value,
+    annotations: Option<Value>,
+    handler: ToolHandler,
+}
+
+#[derive(Debug, Clone)]
+struct MockResource {
+    uri: String,
+    name: String,
+    mime_type: String,
+    text: String,
+}
+
+#[derive(Debug, Clone)]
+struct MockPrompt {
+    name: String,
+    description: String,
+    arguments: Vec<String>,
+    /// Message text; `{argument}` placeholders are filled in from the request
+    template: String,
+}
+
+/// Configurable mock server, served over the in-process transport
+#[derive(Clone)]
+pub struct MockServer {
+    name: String,
+    version: String,
+    tools: Vec<MockTool>,
+    resources: Vec<MockResource>,
+    prompts: Vec<MockPrompt>,
+    delays: HashMap<String, Duration>,
+    faults: HashMap<String, MockFault>,
+    notifications: HashMap<String, Vec<Value>>,
+    page_size: Option<usize>,
+}
+
+impl MockServer {
+    pub fn new(name: &str, version: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            tools: Vec::new(),
+            resources: Vec::new(),
+            prompts: Vec::new(),
+            delays: HashMap::new(),
+            faults: HashMap::new(),
+            notifications: HashMap::new(),
+            page_size: None,
+        }
+    }
+
+    /// Add a tool. The handler gets the call arguments and returns the
+    /// `tools/call` result; an `Err` becomes a result with `isError` set.
+    pub fn tool<F>(mut self, name: &str, description: &str, input_schema: Value, handler: F) -> Self
+    where
+        F: Fn(&Value) -> Result<Value, String> + Send + Sync + 'static,
+    {
+        self.tools.push(MockTool {
+            name: name.to_string(),
+            description: description.to_string(),
+            input_schema,
+            annotations: None,
+            handler: Arc::new(handler),
+        });
+        self
+    }
+
+    /// Set the annotations of an added tool, such as `{ "readOnlyHint": true }`
+    pub fn annotate(mut self, tool: &str, annotations: Value) -> Self {
+        if let Some(tool) = self.tools.iter_mut().find(|t| t.name == tool) {
+            tool.annotations = Some(annotations);
+        }
+        self
+    }
+
+    /// Add an `echo` tool answering with `Echo: <message>`
+    pub fn with_echo_tool(self) -> Self {
+        self.tool(
+            "echo",
+            "Echo back the input message",
+            json!({
+                "type": "object",
+                "properties": { "message": { "type": "string" } },
+                "required": ["message"]
+            }),
+            |args| {
+                let message = args.get("message").and_then(|m| m.as_str()).unwrap_or("");
+                Ok(text_result(&format!("Echo: {}", message)))
+            },
+        )
+    }
+
+    /// Add a text resource
+    pub fn resource(mut self, uri: &str, name: &str, mime_type: &str, text: &str) -> Self {
+        self.resources.push(MockResource {
+            uri: uri.to_string(),
+            name: name.to_string(),
+            mime_type: mime_type.to_string(),
+            text: text.to_string(),
+        });
+        self
+    }
+
+    /// Add a prompt returning a single user message. `{argument}`
+    /// placeholders in `template` are replaced by the request arguments.
+    pub fn prompt(mut self, name: &str, description: &str, arguments: &[&str], template: &str) -> Self {
+        self.prompts.push(MockPrompt {
+            name: name.to_string(),
+            description: description.to_string(),
+            arguments: arguments.iter().map(|a| a.to_string()).collect(),
+            template: template.to_string(),
+        });
+        self
+    }
+
+    /// Wait before answering requests matching `trigger`
+    pub fn delay(mut self, trigger: &str, delay: Duration) -> Self {
+        self.delays.insert(trigger.to_string(), delay);
+        self
+    }
+
+    /// Misbehave on requests matching `trigger`
+    pub fn fault(mut self, trigger: &str, fault: MockFault) -> Self {
+        self.faults.insert(trigger.to_string(), fault);
+        self
+    }
+
+    /// Send a notification before answering requests matching `trigger`
+    pub fn notify(mut self, trigger: &str, method: &str, params: Value) -> Self {
+        self.notifications
+            .entry(trigger.to_string())
+            .or_default()
+            .push(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+        self
+    }
+
+    /// Split list results into pages of `size` items linked by `nextCursor`
+    pub fn page_size(mut self, size: usize) -> Self {
+        self.page_size = Some(size.max(1));
+        self
+    }
+
+    /// Response to a JSON-RPC message, or `None` for notifications
+    pub fn handle_message(&self, message: &Value) -> Option<Value> {
+        let method = message.get("method")?.as_str()?;
+        let id = message.get("id")?.clone();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": params
+                    .get("protocolVersion")
+                    .and_then(|v| v.as_str())
+                    .filter(|v| SUPPORTED_PROTOCOL_VERSIONS.contains(v))
+                    .unwrap_or(PROTOCOL_VERSION),
+                "capabilities": self.capabilities(),
+                "serverInfo": { "name": self.name, "version": self.version }
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => self.list(&params, "tools", self.tools.iter().map(|tool| {
+                let mut entry = json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                    "inputSchema": tool.input_schema
+                });
+                if let Some(ref annotations) = tool.annotations {
+                    entry["annotations"] = annotations.clone();
+                }
+                entry
+            })),
+            "tools/call" => self.call_tool(&params),
+            "resources/list" => self.list(&params, "resources", self.resources.iter().map(|resource| json!({
+                "uri": resource.uri,
+                "name": resource.name,
+                "mimeType": resource.mime_type
+            }))),
+            "resources/read" => self.read_resource(&params),
+            "prompts/list" => self.list(&params, "prompts", self.prompts.iter().map(|prompt| json!({
+                "name": prompt.name,
+                "description": prompt.description,
+                "arguments": prompt.arguments.iter()
+                    .map(|name| json!({ "name": name, "required": true }))
+                    .collect::<Vec<_>>()
+            }))),
+            "prompts/get" => self.get_prompt(&params),
+            _ => Err((-32601, format!("Method '{}' not found", method))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message }
+            }),
+        })
+    }
+
+    /// Answer the client's messages from `input` on `output`. Returns the
+    /// exit code of a simulated crash, or `None` once the client closes its side.
+    pub fn serve<I: BufRead, O: Write>(&self, mut input: I, mut output: O) -> std::io::Result<Option<i32>> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if input.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            let Ok(message) = serde_json::from_str::<Value>(line.trim()) else {
+                continue;
+            };
+
+            let trigger = self.trigger(&message);
+            if let Some(delay) = lookup(&self.delays, &trigger) {
+                std::thread::sleep(*delay);
+            }
+            for notification in lookup(&self.notifications, &trigger).into_iter().flatten() {
+                writeln!(output, "{}", notification)?;
+            }
+            match lookup(&self.faults, &trigger) {
+                Some(MockFault::Crash(code)) => return Ok(Some(*code)),
+                Some(MockFault::NoResponse) => {}
+                Some(MockFault::Malformed) => writeln!(output, "{{\"jsonrpc\": \"2.0\", \"id\":")?,
+                None => {
+                    if let Some(response) = self.handle_message(&message) {
+                        writeln!(output, "{}", response)?;
+                    }
+                }
+            }
+            output.flush()?;
+        }
+    }
+
+    /// Keys that can trigger behaviors for `message`, most specific first
+    fn trigger(&self, message: &Value) -> Vec<String> {
+        let mut keys = Vec::new();
+        if let Some(tool) = message.pointer("/params/name").and_then(|n| n.as_str()) {
+            if message.get("method").and_then(|m| m.as_str()) == Some("tools/call") {
+                keys.push(tool.to_string());
+            }
+        }
+        if let Some(method) = message.get("method").and_then(|m| m.as_str()) {
+            keys.push(method.to_string());
+        }
+        keys
+    }
+
+    fn capabilities(&self) -> Value {
+        let mut capabilities = serde_json::Map::new();
+        if !self.tools.is_empty() {
+            capabilities.insert("tools".to_string(), json!({}));
+        }
+        if !self.resources.is_empty() {
+            capabilities.insert("resources".to_string(), json!({}));
+        }
+        if !self.prompts.is_empty() {
+            capabilities.insert("prompts".to_string(), json!({}));
+        }
+        Value::Object(capabilities)
+    }
+
+    /// A list result, paged when a page size is set. Cursors are offsets.
+    fn list(&self, params: &Value, field: &str, items: impl Iterator<Item = Value>) -> Result<Value, (i64, String)> {
+        let items: Vec<Value> = items.collect();
+        let Some(size) = self.page_size else {
+            return Ok(json!({ field: items }));
+        };
+        let start = match params.get("cursor").and_then(|c| c.as_str()) {
+            Some(cursor) => cursor
+                .parse::<usize>()
+                .ok()
+                .filter(|start| *start < items.len())
+                .ok_or_else(|| (-32602, format!("Invalid cursor '{}'", cursor)))?,
+            None => 0,
+        };
+        let end = (start + size).min(items.len());
+        let mut result = json!({ field: &items[start..end] });
+        if end < items.len() {
+            result["nextCursor"] = json!(end.to_string());
+        }
+        Ok(result)
+    }
+
+    fn call_tool(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let name = params
+            .get("name")
+            .and_then(|n| n.as_str())
+            .ok_or_else(|| (-32602, "Invalid params for tools/call".to_string()))?;
+        let tool = self
+            .tools
+            .iter()
+            .find(|tool| tool.name == name)
+            .ok_or_else(|| (-32602, format!("Tool '{}' not found", name)))?;
+        let arguments = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+        Ok(match (tool.handler)(&arguments) {
+            Ok(result) => result,
+            Err(message) => json!({
+                "content": [{ "type": "text", "text": message }],
+                "isError": true
+            }),
+        })
+    }
+
+    fn read_resource(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let uri = params.get("uri").and_then(|u| u.as_str()).unwrap_or_default();
+        let resource = self
+            .resources
+            .iter()
+            .find(|resource| resource.uri == uri)
+            .ok_or_else(|| (-32002, format!("Resource '{}' not found", uri)))?;
+        Ok(json!({
+            "contents": [{
+                "uri": resource.uri,
+                "mimeType": resource.mime_type,
+                "text": resource.text
+            }]
+        }))
+    }
+
+    fn get_prompt(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let name = params.get("name").and_then(|n| n.as_str()).unwrap_or_default();
+        let prompt = self
+            .prompts
+            .iter()
+            .find(|prompt| prompt.name == name)
+            .ok_or_else(|| (-32602, format!("Prompt '{}' not found", name)))?;
+
+        let mut text = prompt.template.clone();
+        for argument in &prompt.arguments {
+            let value = params
+                .pointer(&format!("/arguments/{}", argument))
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| (-32602, format!("Missing required argument '{}'", argument)))?;
+            text = text.replace(&format!("{{{}}}", argument), value);
+        }
+        Ok(json!({
+            "description": prompt.description,
+            "messages": [{ "role": "user", "content": { "type": "text", "text": text } }]
+        }))
+    }
+}
+
+fn lookup<'a, T>(map: &'a HashMap<String, T>, keys: &[String]) -> Option<&'a T> {
+    keys.iter().find_map(|key| map.get(key))
+}
+
+/// `tools/call` result with a single text block
+pub fn text_result(text: &str) -> Value {
+    json!({ "content": [{ "type": "text", "text": text }] })
+}
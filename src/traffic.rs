@@ -0,0 +1,306 @@
+/// Per-server recorder of the JSON-RPC messages exchanged with a server
+///
+/// Meant for debugging misbehaving servers: every message sent and received
+/// is kept with a timestamp, its direction and, for responses, the
+/// round-trip latency of the matching request. Secrets are redacted before
+/// messages are recorded.
+use serde::{Deserialize, Serialize};
+use crate::error::MCPClientError;
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+fn default_max_entries() -> usize {
+    1_000
+}
+
+fn default_max_bytes() -> usize {
+    1024 * 1024
+}
+
+/// Per-server traffic recording settings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Oldest messages are dropped past this many entries
+    #[serde(default = "default_max_entries")]
+    pub max_entries: usize,
+    /// Oldest messages are dropped once the recorded messages exceed this size
+    #[serde(default = "default_max_bytes")]
+    pub max_bytes: usize,
+    /// Also emit every message as an `mcp://traffic` event
+    #[serde(default)]
+    pub emit_events: bool,
+    /// Also write every message to this JSON Lines file, uncapped, to
+    /// replay the session later. The file is resolved inside the plugin's
+    /// `session_dir` and truncated on each connect.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub record_file: Option<PathBuf>,
+}
+
+impl Default for TrafficConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_entries: default_max_entries(),
+            max_bytes: default_max_bytes(),
+            emit_events: false,
+            record_file: None,
+        }
+    }
+}
+
+impl TrafficConfig {
+    /// Enabled recording with the default caps
+    pub fn enabled() -> Self {
+        Self {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum TrafficDirection {
+    Sent,
+    Received,
+}
+
+impl TrafficDirection {
+    fn opposite(self) -> Self {
+        match self {
+            TrafficDirection::Sent => TrafficDirection::Received,
+            TrafficDirection::Received => TrafficDirection::Sent,
+        }
+    }
+}
+
+/// A single recorded JSON-RPC message
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TrafficEntry {
+    pub server_id: String,
+    pub direction: TrafficDirection,
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<serde_json::Value>,
+    /// Time since the matching request was sent, for responses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    /// Serialized size of the message
+    pub bytes: usize,
+    pub message: serde_json::Value,
+}
+
+type TrafficSink = Arc<dyn Fn(&TrafficEntry) + Send + Sync>;
+
+struct TrafficLog {
+    config: TrafficConfig,
+    entries: VecDeque<TrafficEntry>,
+    total_bytes: usize,
+    /// Time requests still waiting for a response were recorded, by
+    /// direction and id (both sides number their requests independently)
+    in_flight: HashMap<(TrafficDirection, String), Instant>,
+    /// Open `record_file` of the current connection
+    file: Option<std::fs::File>,
+}
+
+/// Shared, size-capped message log for one server.
+///
+/// Cloning is cheap; the registry keeps the log across reconnects and
+/// restarts so the traffic leading up to a crash stays available.
+#[derive(Clone)]
+pub struct TrafficRecorder {
+    server_id: String,
+    log: Arc<Mutex<TrafficLog>>,
+    sink: Option<TrafficSink>,
+}
+
+impl TrafficRecorder {
+    pub fn new(server_id: &str, config: TrafficConfig) -> Self {
+        Self {
+            server_id: server_id.to_string(),
+            log: Arc::new(Mutex::new(TrafficLog {
+                config,
+                entries: VecDeque::new(),
+                total_bytes: 0,
+                in_flight: HashMap::new(),
+                file: None,
+            })),
+            sink: None,
+        }
+    }
+
+    /// Call `sink` for every recorded message while `emit_events` is set
+    pub fn with_sink<F: Fn(&TrafficEntry) + Send + Sync + 'static>(mut self, sink: F) -> Self {
+        self.sink = Some(Arc::new(sink));
+        self
+    }
+
+    /// Replace the settings, trimming the log to the new caps
+    pub fn set_config(&self, config: TrafficConfig) {
+        if let Ok(mut log) = self.log.lock() {
+            log.config = config;
+            log.trim();
+        }
+    }
+
+    /// Start a new session in the configured `record_file`, if any
+    pub fn start_session(&self) -> Result<(), MCPClientError> {
+        let Ok(mut log) = self.log.lock() else {
+            return Ok(());
+        };
+        log.file = None;
+        log.in_flight.clear();
+        let Some(path) = log.config.record_file.clone() else {
+            return Ok(());
+        };
+
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                MCPClientError::system_error(&format!(
+                    "Failed to create traffic directory {}: {}",
+                    parent.display(),
+                    e
+                ))
+            })?;
+        }
+        let file = std::fs::File::create(&path).map_err(|e| {
+            MCPClientError::system_error(&format!(
+                "Failed to create traffic file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        log.file = Some(file);
+        Ok(())
+    }
+
+    pub fn config(&self) -> TrafficConfig {
+        self.log
+            .lock()
+            .map(|log| log.config.clone())
+            .unwrap_or_default()
+    }
+
+    /// Record a message. `message` should already be redacted.
+    pub fn record(&self, direction: TrafficDirection, message: &serde_json::Value) {
+        let method = message
+            .get("method")
+            .and_then(|m| m.as_str())
+            .map(str::to_string);
+        let request_id = message.get("id").cloned();
+        let bytes = serde_json::to_string(message).map(|s| s.len()).unwrap_or(0);
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+
+        let (entry, emit) = {
+            let Ok(mut log) = self.log.lock() else {
+                return;
+            };
+            if !log.config.enabled {
+                return;
+            }
+
+            // Requests start the clock, responses (no method) stop it
+            let latency_ms = match (&request_id, &method) {
+                (Some(id), Some(_)) => {
+                    log.in_flight.insert((direction, id.to_string()), Instant::now());
+                    None
+                }
+                (Some(id), None) => log
+                    .in_flight
+                    .remove(&(direction.opposite(), id.to_string()))
+                    .map(|sent| sent.elapsed().as_millis() as u64),
+                _ => None,
+            };
+
+            let entry = TrafficEntry {
+                server_id: self.server_id.clone(),
+                direction,
+                timestamp_ms,
+                method,
+                request_id,
+                latency_ms,
+                bytes,
+                message: message.clone(),
+            };
+            if let Some(ref mut file) = log.file {
+                let written = serde_json::to_string(&entry)
+                    .map_err(std::io::Error::from)
+                    .and_then(|line| writeln!(file, "{}", line));
+                if let Err(e) = written {
+                    warn_log!(server_id = %self.server_id, error = %e, "Failed to write traffic file");
+                    log.file = None;
+                }
+            }
+            log.total_bytes += bytes;
+            log.entries.push_back(entry.clone());
+            log.trim();
+            (entry, log.config.emit_events)
+        };
+
+        if let (true, Some(sink)) = (emit, &self.sink) {
+            sink(&entry);
+        }
+    }
+
+    /// Recorded messages, oldest first
+    pub fn entries(&self) -> Vec<TrafficEntry> {
+        self.log
+            .lock()
+            .map(|log| log.entries.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut log) = self.log.lock() {
+            log.entries.clear();
+            log.total_bytes = 0;
+            log.in_flight.clear();
+        }
+    }
+
+    /// Recorded messages as JSON Lines, one entry per line
+    pub fn to_jsonl(&self) -> String {
+        entries_to_jsonl(&self.entries())
+    }
+}
+
+impl TrafficLog {
+    fn trim(&mut self) {
+        while self.entries.len() > self.config.max_entries
+            || (self.total_bytes > self.config.max_bytes && !self.entries.is_empty())
+        {
+            if let Some(entry) = self.entries.pop_front() {
+                self.total_bytes -= entry.bytes;
+            }
+        }
+        // Forget requests whose response never came once the log has moved on
+        if self.in_flight.len() > self.config.max_entries {
+            self.in_flight.clear();
+        }
+    }
+}
+
+/// Serialize traffic entries as JSON Lines
+pub fn entries_to_jsonl(entries: &[TrafficEntry]) -> String {
+    entries
+        .iter()
+        .filter_map(|entry| serde_json::to_string(entry).ok())
+        .map(|line| line + "\n")
+        .collect()
+}
@@ -0,0 +1,174 @@
+#![cfg(feature = "testing")]
+
+use serde_json::json;
+use std::time::Duration;
+use tauri_plugin_mcp_client::{
+    approval::{ApprovalDecision, ApprovalManager, ApprovalPolicy, ApprovalRequest},
+    limits::{Limit, RateLimits},
+    registry::ConnectionRegistry,
+    settings::PluginConfig,
+    testing::{text_result, MockServer},
+};
+
+fn mock() -> MockServer {
+    MockServer::new("mock", "1.0.0")
+        .with_echo_tool()
+        .annotate("echo", json!({ "readOnlyHint": true }))
+        .tool("delete", "Delete a file", json!({ "type": "object" }), |_| Ok(text_result("deleted")))
+}
+
+async fn connect(approval: ApprovalPolicy) -> ConnectionRegistry<tauri::Wry> {
+    let mut registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    registry.set_settings(PluginConfig {
+        request_timeout_ms: 500,
+        tool_timeout_ms: 500,
+        approval,
+        ..PluginConfig::default()
+    });
+    registry.connect_mock("mock".to_string(), mock()).await.unwrap();
+    registry
+}
+
+async fn next_pending(registry: &ConnectionRegistry<tauri::Wry>) -> ApprovalRequest {
+    for _ in 0..100 {
+        if let Some(request) = registry.pending_approvals().into_iter().next() {
+            return request;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("no approval request");
+}
+
+/// Test which calls each approval mode asks about
+#[test]
+fn test_approval_policy_modes() {
+    let read_only = json!({ "readOnlyHint": true });
+    let not_destructive = json!({ "destructiveHint": false });
+
+    assert!(!ApprovalPolicy::default().requires_approval("fs", "write", None));
+    assert!(ApprovalPolicy::always().requires_approval("fs", "read", Some(&read_only)));
+
+    let destructive = ApprovalPolicy::destructive();
+    assert!(destructive.requires_approval("fs", "write", None));
+    assert!(destructive.requires_approval("fs", "write", Some(&json!({}))));
+    assert!(!destructive.requires_approval("fs", "read", Some(&read_only)));
+    assert!(!destructive.requires_approval("fs", "append", Some(&not_destructive)));
+
+    let allowlist = ApprovalPolicy::allowlist(["read_*", "github/search"]);
+    assert!(!allowlist.requires_approval("fs", "read_file", None));
+    assert!(!allowlist.requires_approval("github", "search", None));
+    assert!(allowlist.requires_approval("gitlab", "search", None));
+    assert!(allowlist.requires_approval("fs", "write_file", Some(&read_only)));
+
+    let policy: ApprovalPolicy =
+        serde_json::from_value(json!({ "mode": "destructive", "allowedTools": ["fs/*"] })).unwrap();
+    assert_eq!(policy.mode, ApprovalPolicy::destructive().mode);
+    assert_eq!(policy.timeout_ms, 120_000);
+    assert!(!policy.requires_approval("fs", "write", None));
+}
+
+/// Test a held call runs once approved and a remembered rejection applies to later calls
+#[tokio::test]
+async fn test_tool_call_waits_for_approval() {
+    let registry = connect(ApprovalPolicy::always().with_timeout_ms(2_000)).await;
+
+    let call = {
+        let registry = registry.clone();
+        tokio::spawn(async move { registry.execute_tool("mock", "echo", json!({ "message": "hi" })).await })
+    };
+    let request = next_pending(&registry).await;
+    assert_eq!(request.tool_name, "echo");
+    assert_eq!(request.arguments, json!({ "message": "hi" }));
+    assert!(!request.destructive);
+    registry
+        .respond_approval(&request.id, ApprovalDecision { approved: true, remember: false })
+        .unwrap();
+    let (result, _) = call.await.unwrap().unwrap();
+    assert_eq!(result["content"][0]["text"], "Echo: hi");
+
+    let call = {
+        let registry = registry.clone();
+        tokio::spawn(async move { registry.execute_tool("mock", "delete", json!({})).await })
+    };
+    let request = next_pending(&registry).await;
+    assert!(request.destructive);
+    registry
+        .respond_approval(&request.id, ApprovalDecision { approved: false, remember: true })
+        .unwrap();
+    assert_eq!(call.await.unwrap().unwrap_err().code, "TOOL_CALL_REJECTED");
+
+    // The remembered decision answers right away
+    let error = registry.execute_tool("mock", "delete", json!({})).await.unwrap_err();
+    assert_eq!(error.code, "TOOL_CALL_REJECTED");
+    assert!(registry.pending_approvals().is_empty());
+    assert_eq!(
+        registry.respond_approval(&request.id, ApprovalDecision { approved: true, remember: false }).unwrap_err().code,
+        "APPROVAL_NOT_FOUND"
+    );
+}
+
+/// Test the destructive mode only holds tools not annotated as read-only, and unanswered calls time out
+#[tokio::test]
+async fn test_destructive_mode_and_timeout() {
+    let registry = connect(ApprovalPolicy::destructive().with_timeout_ms(100)).await;
+
+    let (result, _) = registry.execute_tool("mock", "echo", json!({ "message": "safe" })).await.unwrap();
+    assert_eq!(result["content"][0]["text"], "Echo: safe");
+
+    let error = registry.execute_tool("mock", "delete", json!({})).await.unwrap_err();
+    assert_eq!(error.code, "APPROVAL_TIMEOUT");
+    assert!(registry.pending_approvals().is_empty());
+}
+
+/// Test a call waiting for approval doesn't take up a concurrency slot
+#[tokio::test]
+async fn test_pending_approval_holds_no_permit() {
+    let mut registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    registry.set_settings(PluginConfig {
+        request_timeout_ms: 500,
+        tool_timeout_ms: 500,
+        approval: ApprovalPolicy::destructive().with_timeout_ms(2_000),
+        limits: RateLimits::server(Limit::concurrent(1)),
+        ..PluginConfig::default()
+    });
+    registry.connect_mock("mock".to_string(), mock()).await.unwrap();
+
+    let call = {
+        let registry = registry.clone();
+        tokio::spawn(async move { registry.execute_tool("mock", "delete", json!({})).await })
+    };
+    let request = next_pending(&registry).await;
+    let (result, _) = registry.execute_tool("mock", "echo", json!({ "message": "meanwhile" })).await.unwrap();
+    assert_eq!(result["content"][0]["text"], "Echo: meanwhile");
+
+    registry
+        .respond_approval(&request.id, ApprovalDecision { approved: true, remember: false })
+        .unwrap();
+    let (result, _) = call.await.unwrap().unwrap();
+    assert_eq!(result["content"][0]["text"], "deleted");
+}
+
+/// Test remembered decisions are stored and loaded again
+#[test]
+fn test_remembered_decisions_persist() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("mcp-approvals.json");
+
+    let manager = ApprovalManager::new();
+    manager.set_store(path.clone()).unwrap();
+    let (request, _decision) = manager.request("fs", "write", json!({}), true).unwrap();
+    manager
+        .respond(&request.id, ApprovalDecision { approved: true, remember: true })
+        .unwrap();
+    assert_eq!(manager.remembered("fs", "write"), Some(true));
+
+    let reloaded = ApprovalManager::new();
+    reloaded.set_store(path.clone()).unwrap();
+    assert_eq!(reloaded.remembered("fs", "write"), Some(true));
+    assert_eq!(reloaded.remembered("fs", "read"), None);
+
+    reloaded.forget("fs", "write").unwrap();
+    let after_forget = ApprovalManager::new();
+    after_forget.set_store(path).unwrap();
+    assert!(after_forget.remembered_decisions().is_empty());
+}
@@ -0,0 +1,218 @@
+use serde_json::json;
+use tauri_plugin_mcp_client::audit::{
+    hash_arguments, redact_args, redact_arguments, AuditEntry, AuditEvent, AuditFile, AuditLog, AuditQuery,
+    ToolCallOutcome,
+};
+use tauri_plugin_mcp_client::{config::ServerConfig, policy::SpawnRequest};
+
+fn tool_call(timestamp_ms: u64, server_id: &str, tool_name: &str, outcome: ToolCallOutcome) -> AuditEntry {
+    AuditEntry {
+        timestamp_ms,
+        server_id: server_id.to_string(),
+        event: AuditEvent::ToolCall {
+            window: Some("main".to_string()),
+            tool_name: tool_name.to_string(),
+            arguments_hash: hash_arguments(&json!({})),
+            arguments: None,
+            duration_ms: 1,
+            outcome,
+            error_code: None,
+        },
+    }
+}
+
+/// Test argument hashes ignore key order and credential-like keys are redacted
+#[test]
+fn test_argument_hash_and_redaction() {
+    let hash = hash_arguments(&json!({ "path": "/tmp", "recursive": true }));
+    assert!(hash.starts_with("sha256:"));
+    assert_eq!(hash.len(), "sha256:".len() + 64);
+    assert_eq!(hash, hash_arguments(&json!({ "recursive": true, "path": "/tmp" })));
+    assert_ne!(hash, hash_arguments(&json!({ "path": "/etc", "recursive": true })));
+
+    let redacted = redact_arguments(&json!({
+        "query": "weather",
+        "apiKey": "abc",
+        "auth": { "Password": "hunter2", "user": "me" },
+        "headers": [{ "Authorization": "Bearer x" }]
+    }));
+    assert_eq!(
+        redacted,
+        json!({
+            "query": "weather",
+            "apiKey": "[REDACTED]",
+            "auth": { "Password": "[REDACTED]", "user": "me" },
+            "headers": [{ "Authorization": "[REDACTED]" }]
+        })
+    );
+}
+
+/// Test credentials in spawn arguments are redacted before they are recorded
+#[test]
+fn test_spawn_args_redaction() {
+    let args: Vec<String> = ["--api-key", "sk-123", "--token=abc", "GITHUB_TOKEN=ghp_x", "--port", "8080", "-y"]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+    assert_eq!(
+        redact_args(&args),
+        vec!["--api-key", "[REDACTED]", "--token=[REDACTED]", "GITHUB_TOKEN=[REDACTED]", "--port", "8080", "-y"]
+    );
+
+    let log = AuditLog::new();
+    let config = ServerConfig::new("server", args);
+    log.record_spawn("server", &SpawnRequest::from_config(&config), Ok(()));
+    match log.entries()[0].event {
+        AuditEvent::Spawn { ref args, .. } => {
+            assert_eq!(args[1], "[REDACTED]");
+            assert!(!args.iter().any(|arg| arg.contains("sk-123") || arg.contains("ghp_x")));
+        }
+        ref other => panic!("expected a spawn entry, got {:?}", other),
+    }
+}
+
+/// Test the audit file rotates and is read back oldest first
+#[test]
+fn test_audit_file_rotation() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit").join("mcp-audit.jsonl");
+    let line_len = serde_json::to_string(&tool_call(0, "fs", "read", ToolCallOutcome::Success)).unwrap().len() as u64 + 1;
+
+    // Two entries per file, the current one and two rotated files kept
+    let mut file = AuditFile::new(path.clone(), line_len * 2, 2);
+    for i in 0..7 {
+        file.append(&tool_call(i, "fs", "read", ToolCallOutcome::Success)).unwrap();
+    }
+    assert!(path.exists());
+    assert!(dir.path().join("audit/mcp-audit.jsonl.1").exists());
+    assert!(dir.path().join("audit/mcp-audit.jsonl.2").exists());
+    assert!(!dir.path().join("audit/mcp-audit.jsonl.3").exists());
+
+    let timestamps: Vec<u64> = file
+        .read(&AuditQuery::default())
+        .unwrap()
+        .iter()
+        .map(|entry| entry.timestamp_ms)
+        .collect();
+    // Entries 0 and 1 were in the oldest file, dropped on the last rotation
+    assert_eq!(timestamps, vec![2, 3, 4, 5, 6]);
+
+    // A new file for the same path appends rather than truncating
+    let mut reopened = AuditFile::new(path, line_len * 10, 2);
+    reopened.append(&tool_call(7, "fs", "read", ToolCallOutcome::Success)).unwrap();
+    assert_eq!(reopened.read(&AuditQuery::default()).unwrap().len(), 6);
+}
+
+/// Test query filters and limits over the file and in-memory log
+#[test]
+fn test_audit_query() {
+    let dir = tempfile::tempdir().unwrap();
+    let entries = [
+        tool_call(100, "fs", "read", ToolCallOutcome::Success),
+        tool_call(200, "fs", "write", ToolCallOutcome::Rejected),
+        tool_call(300, "github", "search", ToolCallOutcome::Failed),
+        tool_call(400, "fs", "write", ToolCallOutcome::Success),
+    ];
+    let mut file = AuditFile::new(dir.path().join("mcp-audit.jsonl"), 1024 * 1024, 1);
+    for entry in &entries {
+        file.append(entry).unwrap();
+    }
+
+    let query = |query: AuditQuery| -> Vec<u64> {
+        file.read(&query).unwrap().iter().map(|entry| entry.timestamp_ms).collect()
+    };
+    assert_eq!(query(AuditQuery { server_id: Some("fs".into()), ..Default::default() }), vec![100, 200, 400]);
+    assert_eq!(query(AuditQuery { tool_name: Some("write".into()), ..Default::default() }), vec![200, 400]);
+    assert_eq!(query(AuditQuery { outcome: Some(ToolCallOutcome::Rejected), ..Default::default() }), vec![200]);
+    assert_eq!(query(AuditQuery { since_ms: Some(200), until_ms: Some(400), ..Default::default() }), vec![200, 300]);
+    assert_eq!(query(AuditQuery { action: Some("spawn".into()), ..Default::default() }), Vec::<u64>::new());
+    assert_eq!(query(AuditQuery { window: Some("main".into()), ..Default::default() }).len(), 4);
+
+    // Without a file the in-memory entries are queried; limits keep the most recent
+    let log = AuditLog::new();
+    for entry in entries {
+        log.record(&entry.server_id.clone(), entry.event);
+    }
+    let recent = log.query(&AuditQuery { limit: Some(2), ..Default::default() }).unwrap();
+    assert_eq!(recent.len(), 2);
+    assert_eq!(recent[1].server_id, "fs");
+    assert!(recent.iter().all(|entry| !entry.is_blocked()));
+}
+
+/// Test tool calls are audited with their outcome, duration and error code
+#[cfg(feature = "testing")]
+#[tokio::test]
+async fn test_tool_calls_are_audited() {
+    use tauri_plugin_mcp_client::{
+        approval::ApprovalPolicy,
+        audit::{ArgumentsMode, AuditConfig},
+        registry::ConnectionRegistry,
+        settings::PluginConfig,
+        testing::MockServer,
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    registry.set_settings(PluginConfig {
+        request_timeout_ms: 500,
+        tool_timeout_ms: 500,
+        approval: ApprovalPolicy::allowlist(["echo", "fail"]).with_timeout_ms(50),
+        audit: AuditConfig {
+            arguments: ArgumentsMode::Redacted,
+            ..AuditConfig::default()
+        },
+        ..PluginConfig::default()
+    });
+    registry
+        .audit_log()
+        .set_file(AuditFile::new(dir.path().join("mcp-audit.jsonl"), 1024 * 1024, 3));
+    let server = MockServer::new("mock", "1.0.0")
+        .with_echo_tool()
+        .tool("fail", "Always fails", json!({ "type": "object" }), |_| Err("boom".to_string()))
+        .tool("delete", "Delete things", json!({ "type": "object" }), |_| Err("unreachable".to_string()));
+    registry.connect_mock("mock".to_string(), server).await.unwrap();
+
+    registry
+        .execute_tool_for(Some("main"), "mock", "echo", json!({ "message": "hi", "token": "s3cret" }))
+        .await
+        .unwrap();
+    registry.execute_tool("mock", "fail", json!({})).await.unwrap();
+    registry.execute_tool("mock", "delete", json!({})).await.unwrap_err();
+    registry.execute_tool("missing", "echo", json!({})).await.unwrap_err();
+
+    let entries = registry
+        .audit_log()
+        .query(&AuditQuery { action: Some("tool_call".into()), ..Default::default() })
+        .unwrap();
+    let summary: Vec<(String, ToolCallOutcome, Option<String>)> = entries
+        .iter()
+        .map(|entry| match entry.event {
+            AuditEvent::ToolCall { ref tool_name, outcome, ref error_code, .. } => {
+                (tool_name.clone(), outcome, error_code.clone())
+            }
+            ref other => panic!("expected a tool call, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("echo".to_string(), ToolCallOutcome::Success, None),
+            ("fail".to_string(), ToolCallOutcome::ToolError, None),
+            ("delete".to_string(), ToolCallOutcome::Rejected, Some("APPROVAL_TIMEOUT".to_string())),
+            ("echo".to_string(), ToolCallOutcome::Failed, Some("NO_PROCESS".to_string())),
+        ]
+    );
+    assert!(entries[2].is_blocked());
+
+    let json = serde_json::to_value(&entries[0]).unwrap();
+    assert_eq!(json["action"], "tool_call");
+    assert_eq!(json["window"], "main");
+    assert_eq!(json["arguments"], json!({ "message": "hi", "token": "[REDACTED]" }));
+    assert_eq!(json["arguments_hash"], hash_arguments(&json!({ "message": "hi", "token": "s3cret" })));
+    assert!(json["duration_ms"].is_u64());
+    // Waiting for approval counts towards the duration
+    match entries[2].event {
+        AuditEvent::ToolCall { duration_ms, .. } => assert!(duration_ms >= 50),
+        ref other => panic!("expected a tool call, got {:?}", other),
+    }
+}
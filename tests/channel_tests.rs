@@ -0,0 +1,80 @@
+#![cfg(unix)]
+
+use serde_json::json;
+use std::time::{Duration, Instant};
+use tauri_plugin_mcp_client::{config::ServerConfig, registry::ConnectionRegistry, settings::PluginConfig};
+
+/// Shell server that answers `initialize`, then runs `calls` on each later line
+/// with `$id` and `$name` set to the request id and tool name
+fn server(calls: &str) -> ServerConfig {
+    let script = format!(
+        r#"read line; echo '{{"jsonrpc":"2.0","id":0,"result":{{}}}}'; read line
+{}"#,
+        calls
+    );
+    ServerConfig::new("sh", vec!["-c".to_string(), script])
+}
+
+/// Answer for `$id`, naming the tool it was for
+const ANSWER: &str = r#"echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"tool\":\"$name\"}}""#;
+
+/// Read `$id` and `$name` from `$line`
+const PARSE: &str = r#"id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p'); name=$(echo "$line" | sed -n 's/.*"name":"\([a-z]*\)".*/\1/p')"#;
+
+async fn connect(server_id: &str, config: ServerConfig) -> ConnectionRegistry<tauri::Wry> {
+    let mut registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    registry.set_settings(PluginConfig {
+        request_timeout_ms: 500,
+        tool_timeout_ms: 500,
+        ..PluginConfig::default()
+    });
+    registry.connect_server_with_config(server_id.to_string(), config).await.unwrap();
+    registry
+}
+
+/// Test concurrent calls to one server each get their own response when the
+/// server answers them in a different order
+#[tokio::test]
+async fn test_interleaved_responses() {
+    // Reads three calls, then answers them last to first
+    let read = |n: u32| format!("read line; {}; id{n}=$id; name{n}=$name", PARSE);
+    let answer = |n: u32| format!("id=$id{n}; name=$name{n}; {}", ANSWER);
+    let calls = [read(1), read(2), read(3), answer(3), answer(2), answer(1), "cat > /dev/null".to_string()].join("\n");
+    let registry = connect("reorder", server(&calls)).await;
+
+    let call = |tool: &'static str| registry.execute_tool("reorder", tool, json!({}));
+    let (first, second, third) = tokio::join!(call("first"), call("second"), call("third"));
+    assert_eq!(first.unwrap().0["tool"], "first");
+    assert_eq!(second.unwrap().0["tool"], "second");
+    assert_eq!(third.unwrap().0["tool"], "third");
+    registry.disconnect_server("reorder").await.unwrap();
+}
+
+/// Test a call timing out doesn't hold up or break the calls running next to it,
+/// and its late response isn't handed to a later call
+#[tokio::test]
+async fn test_timeout_under_concurrency() {
+    // Answers `slow` after a second and everything else right away
+    let calls = format!(
+        r#"while read line; do {}
+if [ "$name" = slow ]; then (sleep 1; {}) & else {}; fi; done"#,
+        PARSE, ANSWER, ANSWER
+    );
+    let registry = connect("mixed", server(&calls)).await;
+
+    let start = Instant::now();
+    let (slow, fast) = tokio::join!(
+        registry.execute_tool("mixed", "slow", json!({})),
+        registry.execute_tool("mixed", "fast", json!({}))
+    );
+    assert_eq!(fast.unwrap().0["tool"], "fast");
+    assert_eq!(slow.unwrap_err().code, "CONNECTION_TIMEOUT");
+    assert!(start.elapsed() < Duration::from_millis(900), "calls took {:?}", start.elapsed());
+
+    // The late answer to `slow` arrives around this call
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    let (later, _) = registry.execute_tool("mixed", "later", json!({})).await.unwrap();
+    assert_eq!(later["tool"], "later");
+    assert!(registry.is_server_connected("mixed").unwrap());
+    registry.disconnect_server("mixed").await.unwrap();
+}
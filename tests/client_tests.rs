@@ -0,0 +1,106 @@
+use serde_json::json;
+use tauri_plugin_mcp_client::client::{CallToolResult, Content, GetPromptResult, Tool};
+
+/// Test typed results deserialize from MCP responses
+#[test]
+fn test_typed_results_deserialize() {
+    let tool: Tool = serde_json::from_value(json!({
+        "name": "echo",
+        "description": "Echo the input",
+        "inputSchema": { "type": "object" }
+    }))
+    .unwrap();
+    assert_eq!(tool.name, "echo");
+    assert_eq!(tool.input_schema, json!({ "type": "object" }));
+
+    let result: CallToolResult = serde_json::from_value(json!({
+        "content": [
+            { "type": "text", "text": "hello" },
+            { "type": "image", "data": "AAAA", "mimeType": "image/png" },
+            { "type": "resource_link", "uri": "file:///tmp/a" }
+        ],
+        "isError": true
+    }))
+    .unwrap();
+    assert!(result.is_error);
+    assert_eq!(result.content[0], Content::Text { text: "hello".to_string() });
+    assert_eq!(
+        result.content[1],
+        Content::Image { data: "AAAA".to_string(), mime_type: "image/png".to_string() }
+    );
+    assert_eq!(result.content[2], Content::Unknown);
+
+    let prompt: GetPromptResult = serde_json::from_value(json!({
+        "messages": [{ "role": "user", "content": { "type": "text", "text": "Review this" } }]
+    }))
+    .unwrap();
+    assert_eq!(prompt.description, None);
+    assert_eq!(prompt.messages[0].role, "user");
+}
+
+#[cfg(unix)]
+mod connected {
+    use super::*;
+    use std::collections::HashMap;
+    use tauri_plugin_mcp_client::{
+        client::McpClient, config::ServerConfig, registry::ConnectionRegistry,
+    };
+
+    /// Shell server answering by method, with `tools/list` split over two pages
+    fn scripted_server() -> ServerConfig {
+        let script = r#"while read line; do
+id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+[ -z "$id" ] && continue
+case "$line" in
+  *'"cursor":"2"'*) result='{"tools":[{"name":"second","inputSchema":{}}]}' ;;
+  *tools/list*) result='{"tools":[{"name":"first","inputSchema":{}}],"nextCursor":"2"}' ;;
+  *tools/call*) result='{"content":[{"type":"text","text":"called"}]}' ;;
+  *resources/read*) result='{"contents":[{"uri":"mem://a","text":"contents"}]}' ;;
+  *prompts/get*) result='{"messages":[{"role":"user","content":{"type":"text","text":"hi"}}]}' ;;
+  *fail*) echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"error\":{\"code\":-32601,\"message\":\"nope\"}}"; continue ;;
+  *) result='{}' ;;
+esac
+echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":$result}"
+done"#;
+        ServerConfig::new("sh", vec!["-c".to_string(), script.to_string()])
+    }
+
+    /// Test the typed client against a connected server
+    #[tokio::test]
+    async fn test_client_methods() {
+        let mcp: McpClient<tauri::Wry> = McpClient::new(ConnectionRegistry::new());
+        mcp.connect("scripted", scripted_server()).await.unwrap();
+        assert!(mcp.is_connected("scripted"));
+
+        let names: Vec<String> = mcp
+            .list_tools("scripted")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|tool| tool.name)
+            .collect();
+        assert_eq!(names, vec!["first", "second"]);
+
+        let result = mcp.call_tool("scripted", "first", json!({})).await.unwrap();
+        assert_eq!(result.content, vec![Content::Text { text: "called".to_string() }]);
+        assert!(!result.is_error);
+
+        let contents = mcp.read_resource("scripted", "mem://a").await.unwrap();
+        assert_eq!(contents[0].text.as_deref(), Some("contents"));
+
+        let prompt = mcp.get_prompt("scripted", "greet", HashMap::new()).await.unwrap();
+        assert_eq!(prompt.messages.len(), 1);
+
+        let error = mcp.request("scripted", "fail", json!({})).await.unwrap_err();
+        assert_eq!(error.code, "PROTOCOL_ERROR");
+
+        // Clones share the same connections
+        let clone = mcp.clone();
+        clone.disconnect("scripted").await.unwrap();
+        assert!(!mcp.is_connected("scripted"));
+        assert_eq!(
+            mcp.list_tools("scripted").await.unwrap_err().code,
+            "NO_PROCESS"
+        );
+    }
+}
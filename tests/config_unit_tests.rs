@@ -0,0 +1,192 @@
+use serde_json::json;
+use tauri_plugin_mcp_client::{
+    config::{
+        load_servers_file, parse_servers_file, ServerConfig, ServerConfigStore, ServersFile,
+        TransportKind,
+    },
+    registry::ConnectionRegistry,
+    secrets::EnvValue,
+};
+
+/// Test parsing the common `mcpServers` JSON shape
+#[test]
+fn test_parse_json_servers_file() {
+    let contents = json!({
+        "mcpServers": {
+            "github": {
+                "command": "npx",
+                "args": ["-y", "@modelcontextprotocol/server-github"],
+                "env": { "GITHUB_TOKEN": { "secret": "github" }, "DEBUG": "1" },
+                "autoStart": true
+            },
+            "files": {
+                "command": "node",
+                "args": ["files.js"],
+                "cwd": "/tmp"
+            }
+        }
+    })
+    .to_string();
+
+    let file = parse_servers_file(&contents, false).unwrap();
+    assert_eq!(file.mcp_servers.len(), 2);
+
+    let github = &file.mcp_servers["github"];
+    assert_eq!(github.command, "npx");
+    assert!(github.auto_start);
+    assert_eq!(github.transport, TransportKind::Stdio);
+    assert_eq!(
+        github.env["GITHUB_TOKEN"],
+        EnvValue::Secret {
+            secret: "github".to_string()
+        }
+    );
+
+    let files = &file.mcp_servers["files"];
+    assert!(!files.auto_start);
+    assert_eq!(files.cwd.as_deref(), Some("/tmp"));
+}
+
+/// Test parsing the same structure written as TOML
+#[test]
+fn test_parse_toml_servers_file() {
+    let contents = r#"
+[mcpServers.echo]
+command = "node"
+args = ["echo-server.js"]
+autoStart = true
+
+[mcpServers.echo.env]
+API_KEY = { secret = "echo-key" }
+"#;
+
+    let file = parse_servers_file(contents, true).unwrap();
+    let echo = &file.mcp_servers["echo"];
+    assert_eq!(echo.args, vec!["echo-server.js"]);
+    assert!(echo.auto_start);
+    assert_eq!(
+        echo.env["API_KEY"],
+        EnvValue::Secret {
+            secret: "echo-key".to_string()
+        }
+    );
+}
+
+/// Test invalid files produce configuration errors
+#[test]
+fn test_parse_invalid_servers_file() {
+    let error = parse_servers_file("{ not json", false).err().unwrap();
+    assert_eq!(error.code, "CONFIG_ERROR");
+}
+
+/// Test validation of server definitions
+#[test]
+fn test_server_config_validation() {
+    assert!(ServerConfig::new("node", vec![]).validate("ok").is_ok());
+    assert!(ServerConfig::new("", vec![]).validate("no-command").is_err());
+    assert!(ServerConfig::new("node", vec![]).validate(" ").is_err());
+
+    let remote = ServerConfig {
+        transport: TransportKind::Sse,
+        url: Some("https://example.com/sse".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        remote.validate("remote").err().unwrap().code,
+        "UNSUPPORTED_TRANSPORT"
+    );
+    // Remote servers can be stored before they can be connected
+    assert!(remote.validate_definition("remote").is_ok());
+    let no_url = ServerConfig { url: None, ..remote.clone() };
+    assert!(no_url.validate_definition("remote").is_err());
+
+    let dir = tempfile::tempdir().unwrap();
+    let store = ServerConfigStore::load(dir.path().join("mcp.json")).unwrap();
+    store.add("remote", remote.clone()).unwrap();
+    store.update("remote", remote).unwrap();
+    assert!(store.add("broken", no_url).is_err());
+}
+
+/// Test the mock transport parses without the testing feature but only
+/// connects with it
+#[test]
+fn test_mock_transport_is_feature_independent() {
+    let mock: ServerConfig = serde_json::from_value(json!({ "transport": "mock" })).unwrap();
+    assert_eq!(mock.transport, TransportKind::Mock);
+    assert!(mock.validate_definition("mock").is_ok());
+    assert_eq!(mock.validate("mock").is_ok(), cfg!(feature = "testing"));
+}
+
+/// Test add, update and remove are saved back to the file
+#[test]
+fn test_config_store_round_trip() {
+    for file_name in ["mcp.json", "mcp.toml"] {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(file_name);
+
+        let store = ServerConfigStore::load(path.clone()).unwrap();
+        assert!(store.list().unwrap().is_empty());
+
+        let mut config = ServerConfig::new("node", vec!["server.js".to_string()]);
+        config.auto_start = true;
+        store.add("echo", config.clone()).unwrap();
+        assert!(store.add("echo", config.clone()).is_err());
+
+        config.args.push("--verbose".to_string());
+        store.update("echo", config.clone()).unwrap();
+        assert!(store.update("missing", config.clone()).is_err());
+
+        store.add("other", ServerConfig::new("python", vec![])).unwrap();
+
+        // Reload from disk
+        let file: ServersFile = load_servers_file(&path).unwrap();
+        assert_eq!(file.mcp_servers.len(), 2);
+        assert_eq!(file.mcp_servers["echo"], config);
+
+        let reloaded = ServerConfigStore::load(path.clone()).unwrap();
+        let auto_start = reloaded.auto_start_servers().unwrap();
+        assert_eq!(auto_start.len(), 1);
+        assert_eq!(auto_start[0].0, "echo");
+
+        reloaded.remove("other").unwrap();
+        assert!(reloaded.remove("other").is_err());
+        assert_eq!(load_servers_file(&path).unwrap().mcp_servers.len(), 1);
+    }
+}
+
+/// Test a change that can't be saved is not applied in memory either
+#[test]
+fn test_config_store_failed_save() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("mcp.json");
+    let store = ServerConfigStore::load(path.clone()).unwrap();
+    store.add("echo", ServerConfig::new("node", vec![])).unwrap();
+
+    // A directory in place of the file makes the rename fail
+    std::fs::remove_file(&path).unwrap();
+    std::fs::create_dir_all(path.join("blocker")).unwrap();
+    assert!(store.add("other", ServerConfig::new("python", vec![])).is_err());
+    assert!(store.update("echo", ServerConfig::new("deno", vec![])).is_err());
+    assert!(store.remove("echo").is_err());
+
+    let servers = store.list().unwrap();
+    assert_eq!(servers.len(), 1);
+    assert_eq!(servers["echo"].command, "node");
+    assert!(!dir.path().join("mcp.json.tmp").exists());
+}
+
+/// Test connecting with an unsupported transport fails without spawning
+#[tokio::test]
+async fn test_connect_unsupported_transport() {
+    let registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    let config = ServerConfig {
+        transport: TransportKind::Http,
+        url: Some("https://example.com/mcp".to_string()),
+        ..Default::default()
+    };
+
+    let result = registry
+        .connect_server_with_config("remote".to_string(), config)
+        .await;
+    assert_eq!(result.err().unwrap().code, "UNSUPPORTED_TRANSPORT");
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "testing")]
+
+use serde_json::json;
+use tauri_plugin_mcp_client::{
+    conformance::{run_conformance, CheckStatus, ConformanceOptions, ConformanceReport},
+    registry::ConnectionRegistry,
+    settings::PluginConfig,
+    testing::{MockFault, MockServer},
+};
+
+async fn run(server: MockServer) -> ConformanceReport {
+    let mut registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    registry.set_settings(PluginConfig {
+        request_timeout_ms: 500,
+        ..PluginConfig::default()
+    });
+    registry.connect_mock("candidate".to_string(), server).await.unwrap();
+    let options = ConformanceOptions {
+        timeout_ms: 500,
+        ..ConformanceOptions::default()
+    };
+    let report = run_conformance(&registry, "candidate", &options).await;
+    registry.disconnect_server("candidate").await.unwrap();
+    report
+}
+
+fn status(report: &ConformanceReport, name: &str) -> CheckStatus {
+    report.check(name).unwrap_or_else(|| panic!("no {} check", name)).status
+}
+
+/// Test a well-behaved server passes every applicable check
+#[tokio::test]
+async fn test_conforming_server_passes() {
+    let server = MockServer::new("mock", "1.0.0")
+        .with_echo_tool()
+        .tool("add", "Add two numbers", json!({ "type": "object" }), |_| Err("unused".to_string()))
+        .tool("sub", "Subtract two numbers", json!({ "type": "object" }), |_| Err("unused".to_string()))
+        .resource("file:///readme.md", "readme", "text/markdown", "# Hello")
+        .page_size(2);
+    let report = run(server).await;
+
+    assert!(report.passed, "{:#?}", report.checks);
+    assert_eq!(report.server_id, "candidate");
+    for name in ["initialize", "ping", "unknown-method", "tools-pagination", "resources-pagination", "tools-call-error", "cancellation", "unknown-notification"] {
+        assert_eq!(status(&report, name), CheckStatus::Passed, "{}", name);
+    }
+    // The mock has no prompts
+    assert_eq!(status(&report, "prompts-pagination"), CheckStatus::Skipped);
+    assert_eq!(report.count(CheckStatus::Failed), 0);
+}
+
+/// Test misbehavior is reported per check instead of aborting the run
+#[tokio::test]
+async fn test_failures_are_reported_per_check() {
+    let server = MockServer::new("mock", "1.0.0")
+        .with_echo_tool()
+        .resource("file:///readme.md", "readme", "text/markdown", "# Hello")
+        .fault("resources/list", MockFault::Malformed)
+        .fault("conformance/unknown-method", MockFault::NoResponse);
+    let report = run(server).await;
+
+    assert!(!report.passed);
+    assert_eq!(status(&report, "unknown-method"), CheckStatus::Failed);
+    let pagination = report.check("resources-pagination").unwrap();
+    assert_eq!(pagination.status, CheckStatus::Failed);
+    assert!(pagination.message.as_deref().unwrap().contains("resources/list"));
+    // Later checks still ran
+    assert_eq!(status(&report, "tools-pagination"), CheckStatus::Passed);
+    assert_eq!(status(&report, "unknown-notification"), CheckStatus::Passed);
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["checks"][1]["status"], "passed");
+}
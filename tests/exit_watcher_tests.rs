@@ -0,0 +1,153 @@
+#![cfg(unix)]
+
+use std::time::Duration;
+use tauri_plugin_mcp_client::{
+    config::ServerConfig,
+    process::ProcessExit,
+    registry::ConnectionRegistry,
+    restart::RestartPolicy,
+    state::ConnectionState,
+};
+
+/// Shell server that answers `initialize`, logs to stderr and exits with code 3
+fn crashing_server(delay: &str) -> ServerConfig {
+    let script = format!(
+        r#"read line; echo '{{"jsonrpc":"2.0","id":0,"result":{{}}}}'; read line; sleep {}; echo "fatal: boom" >&2; exit 3"#,
+        delay
+    );
+    ServerConfig::new("sh", vec!["-c".to_string(), script])
+}
+
+async fn wait_for_status(registry: &ConnectionRegistry<tauri::Wry>, server_id: &str, status: ConnectionState) -> bool {
+    for _ in 0..50 {
+        let statuses = registry.get_connection_statuses().unwrap();
+        if statuses.iter().any(|info| info.server_id == server_id && info.status == status) {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    false
+}
+
+/// Test exit descriptions from real exit statuses
+#[test]
+fn test_process_exit_from_status() {
+    let status = std::process::Command::new("sh")
+        .args(["-c", "exit 7"])
+        .status()
+        .unwrap();
+    let exit = ProcessExit::from_status(&status, vec!["last line".to_string()]);
+    assert_eq!(exit.code, Some(7));
+    assert_eq!(exit.signal, None);
+    assert!(!exit.success);
+    assert_eq!(exit.reason(), "Process exited with code 7: last line");
+
+    let status = std::process::Command::new("sh")
+        .args(["-c", "kill -9 $$"])
+        .status()
+        .unwrap();
+    let exit = ProcessExit::from_status(&status, vec![]);
+    assert_eq!(exit.code, None);
+    assert_eq!(exit.signal, Some(9));
+    assert_eq!(exit.reason(), "Process killed by signal 9");
+}
+
+/// Test an exit is noticed without any tool call and recorded on the connection
+#[tokio::test]
+async fn test_exit_detected_without_tool_calls() {
+    let registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    registry
+        .connect_server_with_config("crashy".to_string(), crashing_server("0.3"))
+        .await
+        .unwrap();
+    assert!(registry.is_server_connected("crashy").unwrap());
+
+    assert!(wait_for_status(&registry, "crashy", ConnectionState::Disconnected).await);
+    assert!(!registry.is_server_connected("crashy").unwrap());
+
+    let info = registry
+        .get_connection_statuses()
+        .unwrap()
+        .into_iter()
+        .find(|info| info.server_id == "crashy")
+        .unwrap();
+    assert_eq!(info.last_error.as_deref(), Some("Process exited with code 3: fatal: boom"));
+    let exit = info.last_exit.unwrap();
+    assert_eq!(exit.code, Some(3));
+    assert_eq!(exit.stderr_tail, vec!["fatal: boom".to_string()]);
+}
+
+/// Test the watcher stays quiet when the server is stopped on purpose
+#[tokio::test]
+async fn test_explicit_disconnect_is_not_reported_as_exit() {
+    let registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    registry
+        .connect_server_with_config("stopped".to_string(), crashing_server("5"))
+        .await
+        .unwrap();
+
+    registry.disconnect_server("stopped").await.unwrap();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let statuses = registry.get_connection_statuses().unwrap();
+    assert_eq!(statuses[0].status, ConnectionState::Disconnected);
+    assert!(statuses[0].last_exit.is_none());
+    assert!(statuses[0].last_error.is_none());
+}
+
+/// Test the watcher triggers the restart policy
+#[tokio::test]
+async fn test_exit_triggers_restart() {
+    let registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    let mut config = crashing_server("0.3");
+    config.restart = RestartPolicy {
+        initial_backoff_ms: 50,
+        max_retries: 1,
+        ..RestartPolicy::on_failure()
+    };
+    registry
+        .connect_server_with_config("flaky".to_string(), config)
+        .await
+        .unwrap();
+
+    // Restarted once, then the retry budget is spent
+    assert!(wait_for_status(&registry, "flaky", ConnectionState::Failed).await);
+    assert!(!registry.is_server_connected("flaky").unwrap());
+}
+
+/// Secret provider that stops answering after its first lookup
+struct OneTimeSecret(std::sync::atomic::AtomicBool);
+
+impl tauri_plugin_mcp_client::secrets::SecretProvider for OneTimeSecret {
+    fn get_secret(&self, name: &str) -> Result<String, tauri_plugin_mcp_client::error::MCPClientError> {
+        if self.0.swap(true, std::sync::atomic::Ordering::SeqCst) {
+            Err(tauri_plugin_mcp_client::error::MCPClientError::secret_not_found(name))
+        } else {
+            Ok("value".to_string())
+        }
+    }
+}
+
+/// Test restart attempts failing before the spawn are recorded and retried
+#[tokio::test]
+async fn test_restart_fails_before_spawn() {
+    use tauri_plugin_mcp_client::secrets::EnvValue;
+
+    let mut registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    registry.set_secret_provider(std::sync::Arc::new(OneTimeSecret(Default::default())));
+    let mut config = crashing_server("0.2");
+    config.env.insert("TOKEN".to_string(), EnvValue::Secret { secret: "token".to_string() });
+    config.restart = RestartPolicy {
+        initial_backoff_ms: 50,
+        max_retries: 2,
+        ..RestartPolicy::on_failure()
+    };
+    registry
+        .connect_server_with_config("secretive".to_string(), config)
+        .await
+        .unwrap();
+
+    assert!(wait_for_status(&registry, "secretive", ConnectionState::Failed).await);
+    let info = registry.get_connection_statuses().unwrap().remove(0);
+    let error = info.last_error.unwrap();
+    assert!(error.contains("after 2 attempts: Secret 'token' not found"), "{}", error);
+}
@@ -0,0 +1,206 @@
+use serde_json::json;
+use std::collections::HashMap;
+use tauri_plugin_mcp_client::{
+    commands::ConnectServerRequest,
+    config::TransportKind,
+    import::{detect_source, import_servers, ConfigSource, ImportOptions},
+    secrets::EnvValue,
+};
+
+fn options() -> ImportOptions {
+    let mut env = HashMap::new();
+    env.insert("GITHUB_TOKEN".to_string(), "ghp_test".to_string());
+    ImportOptions {
+        env,
+        inputs: HashMap::new(),
+        workspace_folder: Some("/work/project".to_string()),
+    }
+}
+
+/// Test importing a Claude Desktop configuration
+#[test]
+fn test_import_claude_desktop() {
+    let contents = json!({
+        "mcpServers": {
+            "filesystem": {
+                "command": "npx",
+                "args": ["-y", "@modelcontextprotocol/server-filesystem", "/Users/me/Desktop"]
+            },
+            "github": {
+                "command": "npx",
+                "args": ["-y", "@modelcontextprotocol/server-github"],
+                "env": { "GITHUB_PERSONAL_ACCESS_TOKEN": "${GITHUB_TOKEN}" }
+            }
+        },
+        "globalShortcut": ""
+    })
+    .to_string();
+
+    let result = import_servers(&contents, None, &options()).unwrap();
+    assert_eq!(result.source, ConfigSource::ClaudeDesktop);
+    assert_eq!(result.servers.len(), 2);
+
+    let github = result.servers.iter().find(|s| s.server_id == "github").unwrap();
+    assert_eq!(
+        github.config.env["GITHUB_PERSONAL_ACCESS_TOKEN"],
+        EnvValue::Plain("ghp_test".to_string())
+    );
+    assert!(github.warnings.is_empty());
+}
+
+/// Test importing a Cursor configuration with remote servers and env placeholders
+#[test]
+fn test_import_cursor() {
+    let contents = json!({
+        "mcpServers": {
+            "local": {
+                "command": "node",
+                "args": ["${workspaceFolder}/server.js"],
+                "env": { "TOKEN": "${env:GITHUB_TOKEN}", "MODE": "${MCP_MODE:-fast}" }
+            },
+            "remote": { "url": "https://example.com/sse", "headers": { "Authorization": "x" } }
+        }
+    })
+    .to_string();
+
+    let result = import_servers(&contents, None, &options()).unwrap();
+    assert_eq!(result.source, ConfigSource::Cursor);
+
+    let local = result.servers.iter().find(|s| s.server_id == "local").unwrap();
+    assert_eq!(local.config.args, vec!["/work/project/server.js"]);
+    assert_eq!(local.config.env["TOKEN"], EnvValue::Plain("ghp_test".to_string()));
+    assert_eq!(local.config.env["MODE"], EnvValue::Plain("fast".to_string()));
+
+    let remote = result.servers.iter().find(|s| s.server_id == "remote").unwrap();
+    assert_eq!(remote.config.transport, TransportKind::Sse);
+    assert_eq!(remote.config.url.as_deref(), Some("https://example.com/sse"));
+    assert!(!remote.warnings.is_empty());
+}
+
+/// Test the app's own environment never fills in placeholders
+#[test]
+fn test_import_ignores_process_env() {
+    assert!(std::env::var("PATH").is_ok());
+    let contents = json!({
+        "mcpServers": {
+            "local": {
+                "command": "node",
+                "args": ["${env:PATH}"],
+                "env": { "KEY": "${env:PATH}", "BARE": "${HOME}", "MIXED": "x-${PATH}" }
+            }
+        }
+    })
+    .to_string();
+
+    let result = import_servers(&contents, None, &options()).unwrap();
+    let local = &result.servers[0];
+    assert_eq!(local.config.args, vec!["${env:PATH}"]);
+    assert_eq!(local.config.env["KEY"], EnvValue::Secret { secret: "PATH".to_string() });
+    assert_eq!(local.config.env["BARE"], EnvValue::Secret { secret: "HOME".to_string() });
+    assert_eq!(local.config.env["MIXED"], EnvValue::Plain("x-${PATH}".to_string()));
+    assert_eq!(local.warnings.len(), 4);
+}
+
+/// Test only the hosts' own configuration files can be read by path
+#[test]
+fn test_known_config_paths() {
+    use tauri_plugin_mcp_client::import::{is_known_config_path, known_config_paths};
+
+    assert!(known_config_paths().iter().any(|path| path.ends_with(".cursor/mcp.json")));
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("mcp.json");
+    std::fs::write(&file, "{}").unwrap();
+    assert!(!is_known_config_path(&file));
+    assert!(!is_known_config_path(std::path::Path::new("/etc/passwd")));
+}
+
+/// Test importing a VS Code mcp.json with comments, inputs and typed servers
+#[test]
+fn test_import_vscode() {
+    let contents = r#"{
+        // Prompted when the server starts
+        "inputs": [
+            { "type": "promptString", "id": "api-key", "description": "API key", "password": true },
+            { "type": "promptString", "id": "region", "description": "Region" }
+        ],
+        "servers": {
+            "search": {
+                "type": "stdio",
+                "command": "python",
+                "args": ["-m", "search_server", "--region", "${input:region}"],
+                "env": { "API_KEY": "${input:api-key}" },
+            },
+            "web": { "type": "http", "url": "https://example.com/mcp" }, /* remote */
+        }
+    }"#;
+
+    let mut options = options();
+    options.inputs.insert("region".to_string(), "eu".to_string());
+
+    let result = import_servers(contents, None, &options).unwrap();
+    assert_eq!(result.source, ConfigSource::VsCode);
+    assert_eq!(result.unresolved_inputs.len(), 1);
+    assert_eq!(result.unresolved_inputs[0].id, "api-key");
+    assert!(result.unresolved_inputs[0].password);
+
+    let search = result.servers.iter().find(|s| s.server_id == "search").unwrap();
+    assert_eq!(search.config.args[3], "eu");
+    // Unresolved inputs become secret references for the app's secret provider
+    assert_eq!(
+        search.config.env["API_KEY"],
+        EnvValue::Secret {
+            secret: "api-key".to_string()
+        }
+    );
+
+    let web = result.servers.iter().find(|s| s.server_id == "web").unwrap();
+    assert_eq!(web.config.transport, TransportKind::Http);
+}
+
+/// Test the `mcp` section of VS Code settings.json is recognized
+#[test]
+fn test_detect_vscode_settings() {
+    let settings = json!({ "editor.fontSize": 14, "mcp": { "servers": {} } });
+    assert_eq!(detect_source(&settings), Some(ConfigSource::VsCode));
+    assert_eq!(detect_source(&json!({ "other": {} })), None);
+}
+
+/// Test invalid entries are reported
+#[test]
+fn test_import_errors() {
+    // Broken entries are skipped and the rest are still imported
+    let file = json!({
+        "servers": {
+            "broken": { "args": [] },
+            "future": { "type": "websocket", "url": "wss://example.com" },
+            "ok": { "command": "node" }
+        }
+    })
+    .to_string();
+    let result = import_servers(&file, None, &options()).unwrap();
+    assert_eq!(result.servers.len(), 1);
+    assert_eq!(result.servers[0].server_id, "ok");
+    let skipped: Vec<&str> = result.skipped.iter().map(|s| s.server_id.as_str()).collect();
+    assert_eq!(skipped, ["broken", "future"]);
+    assert!(result.skipped[1].warnings[0].contains("Unknown server type 'websocket'"));
+
+    assert!(import_servers("{}", None, &options()).is_err());
+    assert!(import_servers("not json", Some(ConfigSource::Cursor), &options()).is_err());
+}
+
+/// Test imported servers can be passed straight to plugin_connect_server
+#[test]
+fn test_imported_server_is_connect_request() {
+    let contents = json!({
+        "mcpServers": { "echo": { "command": "node", "args": ["echo.js"], "env": { "A": "1" } } }
+    })
+    .to_string();
+    let result = import_servers(&contents, None, &options()).unwrap();
+
+    let value = serde_json::to_value(&result.servers[0]).unwrap();
+    let request: ConnectServerRequest = serde_json::from_value(value).unwrap();
+    assert_eq!(request.server_id, "echo");
+    assert_eq!(request.command, "node");
+    assert_eq!(request.args, vec!["echo.js"]);
+    assert_eq!(request.env["A"], EnvValue::Plain("1".to_string()));
+}
@@ -1,6 +1,7 @@
 use tauri_plugin_mcp_client::{
     registry::ConnectionRegistry,
     process::MCPProcess,
+    state::ConnectionState,
 };
 use serde_json::json;
 use std::time::Duration;
@@ -28,9 +29,11 @@ async fn test_plugin_connection_lifecycle() {
             let disconnect_result = registry.disconnect_server(&server_id).await;
             assert!(disconnect_result.is_ok());
 
-            // Verify server no longer in connection list
+            // Verify server is listed as disconnected
             let connections_after = registry.get_connection_statuses().unwrap();
-            assert!(!connections_after.iter().any(|c| c.server_id == server_id));
+            assert!(connections_after
+                .iter()
+                .any(|c| c.server_id == server_id && c.status == ConnectionState::Disconnected));
         }
         Err(_) => {
             // Expected to fail with echo command, but we tested the flow
@@ -237,7 +240,6 @@ async fn test_process_cleanup() {
     let disconnect_result = registry.disconnect_server(server_id).await;
     assert!(disconnect_result.is_ok());
     
-    // Server should not be in connections list
-    let connections = registry.get_connection_statuses().unwrap();
-    assert!(!connections.iter().any(|c| c.server_id == server_id));
+    // Server should not be listed as connected
+    assert!(!registry.is_server_connected(server_id).unwrap());
 }
\ No newline at end of file
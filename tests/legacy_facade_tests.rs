@@ -0,0 +1,54 @@
+#![cfg(unix)]
+
+use serde_json::json;
+use tauri_plugin_mcp_client::process::{
+    cleanup_all_mcp_processes, execute_mcp_tool, get_all_server_connection_statuses,
+    is_mcp_process_running, list_mcp_tools, start_mcp_process, stop_mcp_process,
+};
+
+/// Shell server answering every request with an empty result
+fn echo_server_args() -> Vec<String> {
+    let script = r#"while read line; do id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p'); if [ -n "$id" ]; then echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"tools\":[]}}"; fi; done"#;
+    vec!["-c".to_string(), script.to_string()]
+}
+
+/// Test the free functions drive the shared connection registry
+#[tokio::test]
+async fn test_free_functions_share_registry_state() {
+    start_mcp_process("legacy-a".to_string(), "sh".to_string(), echo_server_args())
+        .await
+        .unwrap();
+    start_mcp_process("legacy-b".to_string(), "sh".to_string(), echo_server_args())
+        .await
+        .unwrap();
+
+    assert!(is_mcp_process_running("legacy-a"));
+    let statuses = get_all_server_connection_statuses();
+    assert_eq!(statuses.get("legacy-a"), Some(&true));
+    assert_eq!(statuses.get("legacy-b"), Some(&true));
+
+    assert_eq!(list_mcp_tools("legacy-a").unwrap(), json!({ "tools": [] }));
+    let (result, _duration) = execute_mcp_tool("legacy-a", "noop", json!({})).unwrap();
+    assert_eq!(result, json!({ "tools": [] }));
+
+    stop_mcp_process("legacy-a");
+    assert!(!is_mcp_process_running("legacy-a"));
+    assert_eq!(
+        list_mcp_tools("legacy-a").err().unwrap().code,
+        "NO_PROCESS"
+    );
+
+    // A server that failed to start has no process
+    assert!(start_mcp_process(
+        "legacy-c".to_string(),
+        "nonexistent-mcp-command-12345".to_string(),
+        vec![]
+    )
+    .await
+    .is_err());
+    assert!(!is_mcp_process_running("legacy-c"));
+    assert!(!get_all_server_connection_statuses().contains_key("legacy-c"));
+
+    cleanup_all_mcp_processes();
+    assert!(get_all_server_connection_statuses().is_empty());
+}
@@ -0,0 +1,219 @@
+use serde_json::json;
+use std::time::Duration;
+use tauri_plugin_mcp_client::config::{parse_servers_file, ServerConfig};
+use tauri_plugin_mcp_client::limits::{Limit, LimitMode, RateLimits, ServerLimiter};
+
+/// Test limits parse from the configuration shape
+#[test]
+fn test_limits_config() {
+    let limits: RateLimits = serde_json::from_value(json!({
+        "ratePerSec": 5,
+        "maxConcurrent": 2,
+        "tools": { "search_*": { "ratePerSec": 0.5, "burst": 3 } },
+        "mode": "queue"
+    }))
+    .unwrap();
+    assert_eq!(limits.server, Limit::rate(5.0).with_max_concurrent(2));
+    assert_eq!(limits.tools["search_*"], Limit::rate(0.5).with_burst(3));
+    assert_eq!(limits.mode, LimitMode::Queue);
+    assert_eq!(limits.queue_timeout_ms, 30_000);
+    assert!(!limits.is_default());
+
+    assert!(RateLimits::default().is_default());
+    assert!(ServerLimiter::new("fs", vec![RateLimits::default()]).is_unlimited());
+    assert!(ServerLimiter::new("fs", vec![RateLimits::default()]).usage().is_none());
+}
+
+/// Test rates that can't refill a bucket are rejected when validated and loaded
+#[test]
+fn test_invalid_rates_rejected() {
+    assert!(RateLimits::server(Limit::rate(0.5)).validate("limits").is_ok());
+    assert!(RateLimits::server(Limit::concurrent(1)).validate("limits").is_ok());
+    for rate in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        let error = RateLimits::server(Limit::rate(rate)).validate("limits").unwrap_err();
+        assert_eq!(error.code, "CONFIG_ERROR");
+    }
+    let error = RateLimits::default()
+        .with_tool("search_*", Limit::rate(-2.0))
+        .validate("fs.limits")
+        .unwrap_err();
+    assert!(error.message.contains("fs.limits.tools.search_*.ratePerSec"), "{}", error.message);
+
+    let mut config = ServerConfig::new("npx", vec![]);
+    config.limits = RateLimits::server(Limit::rate(0.0));
+    assert!(config.validate_definition("fs").is_err());
+
+    let file = r#"{ "mcpServers": { "fs": { "command": "npx", "limits": { "ratePerSec": -1 } } } }"#;
+    assert!(parse_servers_file(file, false).is_err());
+    let file = "[mcpServers.fs]\ncommand = \"npx\"\nlimits = { ratePerSec = nan }\n";
+    assert!(parse_servers_file(file, true).is_err());
+}
+
+/// Test the token bucket rejects calls past its burst and refills over time
+#[test]
+fn test_rate_limit_rejects() {
+    let limiter = ServerLimiter::new("fs", vec![RateLimits::server(Limit::rate(20.0).with_burst(2))]);
+
+    let _first = limiter.try_acquire("read").unwrap();
+    let _second = limiter.try_acquire("read").unwrap();
+    let error = limiter.try_acquire("read").err().unwrap();
+    assert_eq!(error.code, "RATE_LIMITED");
+    assert!(error.details.unwrap().starts_with("Retry in"));
+
+    std::thread::sleep(Duration::from_millis(60));
+    let _third = limiter.try_acquire("read").unwrap();
+
+    let usage = limiter.usage().unwrap();
+    assert_eq!(usage.server.calls, 3);
+    assert_eq!(usage.server.limited, 1);
+    assert_eq!(usage.server.rate_per_sec, Some(20.0));
+}
+
+/// Test concurrency slots are held until the permit is dropped
+#[test]
+fn test_concurrency_limit() {
+    let limiter = ServerLimiter::new("fs", vec![RateLimits::server(Limit::concurrent(1))]);
+
+    let permit = limiter.try_acquire("write").unwrap();
+    assert_eq!(limiter.usage().unwrap().server.in_flight, 1);
+    assert_eq!(limiter.try_acquire("read").err().unwrap().code, "RATE_LIMITED");
+
+    drop(permit);
+    assert_eq!(limiter.usage().unwrap().server.in_flight, 0);
+    let _permit = limiter.try_acquire("read").unwrap();
+}
+
+/// Test queued calls wait for a free slot or a token, up to the queue timeout
+#[tokio::test]
+async fn test_queue_mode() {
+    let limits = RateLimits::server(Limit::concurrent(1)).queued(Duration::from_millis(500));
+    let limiter = std::sync::Arc::new(ServerLimiter::new("fs", vec![limits]));
+
+    let permit = limiter.acquire("write").await.unwrap();
+    let waiting = {
+        let limiter = limiter.clone();
+        tokio::spawn(async move { limiter.acquire("write").await.map(|_| ()) })
+    };
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(limiter.usage().unwrap().server.queued, 1);
+    drop(permit);
+    waiting.await.unwrap().unwrap();
+
+    let limits = RateLimits::server(Limit::rate(1.0)).queued(Duration::from_millis(100));
+    let limiter = ServerLimiter::new("fs", vec![limits]);
+    let _first = limiter.acquire("read").await.unwrap();
+    // The next token is a second away, past the queue timeout
+    assert_eq!(limiter.acquire("read").await.err().unwrap().code, "RATE_LIMITED");
+
+    let limits = RateLimits::server(Limit::rate(20.0).with_burst(1)).queued(Duration::from_secs(1));
+    let limiter = ServerLimiter::new("fs", vec![limits]);
+    let _first = limiter.acquire("read").await.unwrap();
+    let start = std::time::Instant::now();
+    let _second = limiter.acquire("read").await.unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(30));
+}
+
+/// Test plugin-wide and server limits both apply, with per-tool limits
+#[test]
+fn test_limit_layers() {
+    let plugin_wide = RateLimits::default().with_tool("delete_*", Limit::concurrent(1));
+    let server = RateLimits::server(Limit::concurrent(2));
+    let limiter = ServerLimiter::new("fs", vec![plugin_wide, server]);
+
+    let delete = limiter.try_acquire("delete_file").unwrap();
+    assert_eq!(limiter.try_acquire("delete_dir").err().unwrap().code, "RATE_LIMITED");
+    let read = limiter.try_acquire("read").unwrap();
+    // The server allows two calls in flight
+    assert_eq!(limiter.try_acquire("list").err().unwrap().code, "RATE_LIMITED");
+
+    let usage = limiter.usage().unwrap();
+    assert_eq!(usage.server.in_flight, 2);
+    assert_eq!(usage.server.max_concurrent, Some(2));
+    assert_eq!(usage.tools["delete_*"].in_flight, 1);
+    assert_eq!(usage.tools["delete_*"].limited, 1);
+    drop((delete, read));
+}
+
+/// Test a call rejected by one gate doesn't use up tokens of the others
+#[test]
+fn test_rejected_call_keeps_tokens() {
+    let plugin_wide = RateLimits::server(Limit::rate(1.0).with_burst(10));
+    let server = RateLimits::default().with_tool("search", Limit::rate(0.01));
+    let limiter = ServerLimiter::new("fs", vec![plugin_wide, server]);
+
+    let _first = limiter.try_acquire("search").unwrap();
+    for _ in 0..5 {
+        assert_eq!(limiter.try_acquire("search").err().unwrap().code, "RATE_LIMITED");
+    }
+
+    let usage = limiter.usage().unwrap();
+    assert_eq!(usage.server.tokens_available, Some(9.0));
+    assert_eq!(usage.server.calls, 1);
+    assert_eq!(usage.tools["search"].limited, 5);
+}
+
+/// Test the registry limits tool calls and reports usage in the connection status
+#[cfg(feature = "testing")]
+#[tokio::test]
+async fn test_registry_rate_limits() {
+    use tauri_plugin_mcp_client::{registry::ConnectionRegistry, settings::PluginConfig, testing::MockServer};
+
+    let mut registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    registry.set_settings(PluginConfig {
+        request_timeout_ms: 500,
+        tool_timeout_ms: 500,
+        limits: RateLimits::server(Limit::rate(0.5).with_burst(1)),
+        ..PluginConfig::default()
+    });
+    registry
+        .connect_mock("mock".to_string(), MockServer::new("mock", "1.0.0").with_echo_tool())
+        .await
+        .unwrap();
+
+    registry.execute_tool("mock", "echo", json!({ "message": "one" })).await.unwrap();
+    let error = registry.execute_tool("mock", "echo", json!({ "message": "two" })).await.unwrap_err();
+    assert_eq!(error.code, "RATE_LIMITED");
+
+    let statuses = registry.get_connection_statuses().unwrap();
+    let usage = statuses[0].usage.as_ref().unwrap();
+    assert_eq!(usage.server.calls, 1);
+    assert_eq!(usage.server.limited, 1);
+    assert_eq!(usage.server.tokens_available, Some(0.0));
+    let json = serde_json::to_value(&statuses[0]).unwrap();
+    assert_eq!(json["usage"]["limited"], 1);
+}
+
+/// Test calls to one server run at the same time, up to `maxConcurrent`
+#[cfg(unix)]
+#[tokio::test]
+async fn test_concurrent_calls_to_one_server() {
+    use tauri_plugin_mcp_client::{config::ServerConfig, registry::ConnectionRegistry, settings::PluginConfig};
+
+    let mut registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    registry.set_settings(PluginConfig {
+        limits: RateLimits::server(Limit::concurrent(2)),
+        ..PluginConfig::default()
+    });
+    // Answers every call half a second after it arrives
+    let script = r#"read line; echo '{"jsonrpc":"2.0","id":0,"result":{}}'; read line
+while read line; do id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p'); (sleep 0.5; echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{}}") & done"#;
+    registry
+        .connect_server_with_config("slow".to_string(), ServerConfig::new("sh", vec!["-c".to_string(), script.to_string()]))
+        .await
+        .unwrap();
+
+    let call = || registry.execute_tool("slow", "wait", json!({}));
+    let in_flight = async {
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        registry.get_connection_statuses().unwrap()[0].usage.as_ref().unwrap().server.in_flight
+    };
+    let start = std::time::Instant::now();
+    let (first, second, third, in_flight) = tokio::join!(call(), call(), call(), in_flight);
+    assert!(start.elapsed() < Duration::from_millis(900), "calls took {:?}", start.elapsed());
+    first.unwrap();
+    second.unwrap();
+    assert_eq!(third.unwrap_err().code, "RATE_LIMITED");
+    assert_eq!(in_flight, 2);
+
+    registry.disconnect_server("slow").await.unwrap();
+}
@@ -0,0 +1,156 @@
+use serde_json::json;
+use std::time::Duration;
+use tauri_plugin_mcp_client::{
+    config::ServerConfig,
+    liveness::{LatencyStats, LivenessConfig},
+    registry::{ConnectionInfo, ConnectionRegistry},
+    state::ConnectionState,
+};
+
+/// Test liveness checks are off by default and partially configurable
+#[test]
+fn test_liveness_config_defaults() {
+    let config = LivenessConfig::default();
+    assert!(!config.enabled);
+    assert!(config.is_default());
+    assert!(!LivenessConfig::enabled().is_default());
+
+    let config: LivenessConfig = serde_json::from_value(json!({
+        "enabled": true,
+        "intervalMs": 1000,
+        "restartUnresponsive": true
+    }))
+    .unwrap();
+    assert_eq!(config.interval(), Duration::from_millis(1000));
+    assert_eq!(config.timeout_ms, LivenessConfig::default().timeout_ms);
+    assert_eq!(config.failure_threshold, 3);
+    assert!(config.restart_unresponsive);
+}
+
+/// Test latency statistics bookkeeping
+#[test]
+fn test_latency_stats() {
+    let mut stats = LatencyStats::default();
+    stats.record_success(10, 100);
+    stats.record_success(30, 101);
+    stats.record_failure(102);
+    stats.record_failure(103);
+
+    assert_eq!(stats.samples, 2);
+    assert_eq!(stats.last_ms, Some(30));
+    assert_eq!(stats.min_ms, Some(10));
+    assert_eq!(stats.max_ms, Some(30));
+    assert_eq!(stats.avg_ms, 20.0);
+    assert_eq!(stats.failures, 2);
+    assert_eq!(stats.consecutive_failures, 2);
+    assert_eq!(stats.last_ping_at, Some(103));
+
+    stats.record_success(20, 104);
+    assert_eq!(stats.consecutive_failures, 0);
+    assert_eq!(stats.failures, 2);
+}
+
+#[cfg(unix)]
+fn fast_liveness() -> LivenessConfig {
+    LivenessConfig {
+        enabled: true,
+        interval_ms: 100,
+        timeout_ms: 200,
+        failure_threshold: 2,
+        restart_unresponsive: false,
+    }
+}
+
+#[cfg(unix)]
+async fn wait_for_info(
+    registry: &ConnectionRegistry<tauri::Wry>,
+    server_id: &str,
+    predicate: impl Fn(&ConnectionInfo) -> bool,
+) -> Option<ConnectionInfo> {
+    for _ in 0..50 {
+        let statuses = registry.get_connection_statuses().unwrap();
+        if let Some(info) = statuses.into_iter().find(|info| info.server_id == server_id) {
+            if predicate(&info) {
+                return Some(info);
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    None
+}
+
+/// Test a responsive server collects latency samples and stays connected
+#[cfg(unix)]
+#[tokio::test]
+async fn test_responsive_server_records_latency() {
+    // Echo an empty result for every request id
+    let script = r#"while read line; do id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p'); if [ -n "$id" ]; then echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{}}"; fi; done"#;
+    let mut config = ServerConfig::new("sh", vec!["-c".to_string(), script.to_string()]);
+    config.liveness = fast_liveness();
+
+    let registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    registry
+        .connect_server_with_config("echo".to_string(), config)
+        .await
+        .unwrap();
+
+    let info = wait_for_info(&registry, "echo", |info| {
+        info.latency.as_ref().map(|l| l.samples >= 2).unwrap_or(false)
+    })
+    .await
+    .expect("latency samples were not recorded");
+    assert_eq!(info.status, ConnectionState::Ready);
+    assert_eq!(info.latency.unwrap().consecutive_failures, 0);
+
+    registry.disconnect_server("echo").await.unwrap();
+}
+
+/// Test a server that stops answering is marked unresponsive
+#[cfg(unix)]
+#[tokio::test]
+async fn test_hung_server_becomes_unresponsive() {
+    let script = r#"read line; echo '{"jsonrpc":"2.0","id":0,"result":{}}'; sleep 30"#;
+    let mut config = ServerConfig::new("sh", vec!["-c".to_string(), script.to_string()]);
+    config.liveness = fast_liveness();
+
+    let registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    registry
+        .connect_server_with_config("hung".to_string(), config)
+        .await
+        .unwrap();
+
+    let info = wait_for_info(&registry, "hung", |info| info.status == ConnectionState::Degraded)
+        .await
+        .expect("server was not marked unresponsive");
+    assert!(info.latency.unwrap().consecutive_failures >= 2);
+    assert!(!registry.is_server_connected("hung").unwrap());
+
+    registry.disconnect_server("hung").await.unwrap();
+}
+
+/// Test a ping waiting on a hung server doesn't hold up requests to other servers
+#[cfg(unix)]
+#[tokio::test]
+async fn test_ping_does_not_block_other_servers() {
+    let hung_script = r#"read line; echo '{"jsonrpc":"2.0","id":0,"result":{}}'; sleep 30"#;
+    let mut hung = ServerConfig::new("sh", vec!["-c".to_string(), hung_script.to_string()]);
+    hung.liveness = LivenessConfig {
+        interval_ms: 10,
+        timeout_ms: 3000,
+        ..fast_liveness()
+    };
+    let echo_script = r#"while read line; do id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p'); if [ -n "$id" ]; then echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{}}"; fi; done"#;
+    let echo = ServerConfig::new("sh", vec!["-c".to_string(), echo_script.to_string()]);
+
+    let registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    registry.connect_server_with_config("hung".to_string(), hung).await.unwrap();
+    registry.connect_server_with_config("echo".to_string(), echo).await.unwrap();
+
+    // Let the first ping to the hung server start waiting
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let start = std::time::Instant::now();
+    registry.request("echo", "ping", json!({}), 2000).await.unwrap();
+    assert!(start.elapsed() < Duration::from_secs(1), "request took {:?}", start.elapsed());
+
+    registry.disconnect_all(Duration::from_secs(2), "Test finished").await;
+}
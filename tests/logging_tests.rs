@@ -0,0 +1,37 @@
+use serde_json::json;
+use tauri_plugin_mcp_client::{
+    logging::{self, LogLevel},
+    settings::PluginConfig,
+    Builder,
+};
+
+/// Test payload logging is off unless configured
+#[test]
+fn test_payload_logging_is_opt_in() {
+    assert!(!PluginConfig::default().log_payloads);
+    assert!(!logging::payloads_enabled());
+
+    let config: PluginConfig = serde_json::from_value(json!({ "logPayloads": true })).unwrap();
+    assert!(config.log_payloads);
+    assert!(Builder::new().log_payloads(true).resolve_config(None).log_payloads);
+}
+
+/// Test the level cap applied before events reach the subscriber
+#[test]
+fn test_log_level_cap() {
+    assert_eq!(serde_json::to_value(LogLevel::Trace).unwrap(), json!("trace"));
+    assert!(LogLevel::Warn < LogLevel::Debug);
+
+    logging::set_level(LogLevel::Warn);
+    assert!(logging::enabled(LogLevel::Error));
+    assert!(logging::enabled(LogLevel::Warn));
+    assert!(!logging::enabled(LogLevel::Info));
+
+    logging::set_level(LogLevel::Off);
+    assert!(!logging::enabled(LogLevel::Error));
+    assert!(!logging::enabled(LogLevel::Off));
+
+    logging::set_level(LogLevel::Debug);
+    assert!(logging::enabled(LogLevel::Debug));
+    assert!(!logging::enabled(LogLevel::Trace));
+}
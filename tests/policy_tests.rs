@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri_plugin_mcp_client::{
+    audit::AuditEvent,
+    config::ServerConfig,
+    error::ErrorCategory,
+    policy::{command_matches, glob_match, is_dangerous_env_key, SpawnPolicy, SpawnRequest, SpawnScope},
+    registry::ConnectionRegistry,
+    scope::{McpScope, ScopeRules},
+    secrets::EnvValue,
+    settings::PluginConfig,
+};
+
+fn server(command: &str, args: &[&str], env: &[&str]) -> ServerConfig {
+    ServerConfig {
+        env: env
+            .iter()
+            .map(|key| (key.to_string(), EnvValue::Plain("value".to_string())))
+            .collect::<HashMap<_, _>>(),
+        ..ServerConfig::new(command, args.iter().map(|a| a.to_string()).collect())
+    }
+}
+
+fn scope(json: serde_json::Value) -> Vec<Arc<SpawnScope>> {
+    serde_json::from_value::<Vec<SpawnScope>>(json)
+        .unwrap()
+        .into_iter()
+        .map(Arc::new)
+        .collect()
+}
+
+/// Test glob patterns and command matching
+#[test]
+fn test_glob_match() {
+    assert!(glob_match("npx", "npx"));
+    assert!(glob_match("@modelcontextprotocol/*", "@modelcontextprotocol/server-github"));
+    assert!(glob_match("*TOKEN*", "GITHUB_TOKEN"));
+    assert!(glob_match("server-?", "server-a"));
+    assert!(glob_match("a*b*c", "aXbYbZc"));
+    assert!(!glob_match("server-?", "server-ab"));
+    assert!(!glob_match("npx", "npxx"));
+    assert!(!glob_match("a*c", "abcd"));
+
+    assert!(command_matches("npx", "npx"));
+    assert!(command_matches("/opt/mcp/*", "/opt/mcp/server"));
+    assert!(!command_matches("/opt/mcp/*", "server"));
+    // Bare names only match commands looked up on the PATH
+    assert!(!command_matches("npx", "/tmp/x/npx"));
+    assert!(!command_matches("npx", "./npx"));
+    // Wildcards stay within one path component, and `..` never matches
+    assert!(!command_matches("/opt/mcp/*", "/opt/mcp/../../bin/sh"));
+    assert!(!command_matches("/opt/mcp/*", "/opt/mcp/sub/server"));
+    assert!(!command_matches("/opt/*/server", "/opt/../server"));
+    assert!(!command_matches("bin/*", "bin/server"));
+}
+
+/// Test the spawn policy checks commands, arguments and env keys
+#[test]
+fn test_spawn_policy() {
+    let policy: SpawnPolicy = serde_json::from_value(serde_json::json!({
+        "commands": ["npx"],
+        "args": ["-y", "@modelcontextprotocol/*"],
+        "envKeys": ["GITHUB_*"]
+    }))
+    .unwrap();
+    assert_eq!(
+        policy,
+        SpawnPolicy::commands(["npx"])
+            .with_args(["-y", "@modelcontextprotocol/*"])
+            .with_env_keys(["GITHUB_*"])
+    );
+
+    let allowed = server("npx", &["-y", "@modelcontextprotocol/server-github"], &["GITHUB_TOKEN"]);
+    assert!(policy.check(&SpawnRequest::from_config(&allowed)).is_ok());
+
+    for (config, code) in [
+        (server("bash", &[], &[]), "COMMAND_NOT_ALLOWED"),
+        (server("npx", &["-y", "evil-server"], &[]), "ARGUMENT_NOT_ALLOWED"),
+        (server("npx", &["-y"], &["LD_PRELOAD"]), "ENV_NOT_ALLOWED"),
+    ] {
+        let error = policy.check(&SpawnRequest::from_config(&config)).unwrap_err();
+        assert_eq!(error.category, ErrorCategory::Permission);
+        assert_eq!(error.code, code);
+    }
+    assert!(SpawnPolicy::default().is_unrestricted());
+}
+
+/// Test capability scope entries permit and forbid spawns
+#[test]
+fn test_spawn_scope() {
+    let allow = scope(serde_json::json!([{ "command": "npx", "args": ["-y", "@modelcontextprotocol/*"] }]));
+    let deny = scope(serde_json::json!([{ "command": "npx", "envKeys": ["*TOKEN*"] }, { "command": "bash" }]));
+
+    let check = |config: &ServerConfig| SpawnScope::check(&allow, &deny, &SpawnRequest::from_config(config));
+    assert!(check(&server("npx", &["-y", "@modelcontextprotocol/server-memory"], &["DEBUG"])).is_ok());
+    assert_eq!(check(&server("uvx", &[], &[])).unwrap_err().code, "SPAWN_NOT_PERMITTED");
+    assert!(check(&server("npx", &["-y", "other"], &[])).is_err());
+    assert!(check(&server("npx", &["-y", "@modelcontextprotocol/server-github"], &["GITHUB_TOKEN"])).is_err());
+
+    // Without allow entries only deny entries apply, and denied names are
+    // caught when run through a path too
+    let request = server("/bin/bash", &["-c", "true"], &[]);
+    assert!(SpawnScope::check(&[], &deny, &SpawnRequest::from_config(&request)).is_err());
+    assert!(SpawnScope::check(&[], &deny, &SpawnRequest::from_config(&server("uvx", &[], &[]))).is_ok());
+    let request = server("/usr/../bin/sh", &[], &[]);
+    assert!(SpawnScope::check(&[], &deny, &SpawnRequest::from_config(&request)).is_err());
+
+    // Loader and interpreter variables must be named exactly by an allow entry
+    let preload = server("npx", &["-y", "@modelcontextprotocol/server-memory"], &["LD_PRELOAD"]);
+    let error = check(&preload).unwrap_err();
+    assert!(error.details.unwrap().contains("LD_PRELOAD"));
+    assert!(SpawnScope::check(&[], &[], &SpawnRequest::from_config(&server("npx", &[], &["NODE_OPTIONS"]))).is_err());
+    let wildcard = scope(serde_json::json!([{ "command": "npx", "envKeys": ["*"] }]));
+    assert!(SpawnScope::check(&wildcard, &[], &SpawnRequest::from_config(&preload)).is_err());
+    let named = scope(serde_json::json!([{ "command": "npx", "envKeys": ["LD_PRELOAD"] }]));
+    assert!(SpawnScope::check(&named, &[], &SpawnRequest::from_config(&preload)).is_ok());
+    assert!(is_dangerous_env_key("dyld_insert_libraries"));
+    assert!(!is_dangerous_env_key("GITHUB_TOKEN"));
+
+    // A working directory needs an allow entry listing it
+    let mut in_dir = server("npx", &["-y", "@modelcontextprotocol/server-memory"], &[]);
+    in_dir.cwd = Some("/work/project".to_string());
+    assert!(check(&in_dir).is_err());
+    let with_cwd = scope(serde_json::json!([{ "command": "npx", "cwd": ["/work/*"] }]));
+    assert!(SpawnScope::check(&with_cwd, &[], &SpawnRequest::from_config(&in_dir)).is_ok());
+    in_dir.cwd = Some("/work/../etc".to_string());
+    assert!(SpawnScope::check(&with_cwd, &[], &SpawnRequest::from_config(&in_dir)).is_err());
+    let deny_etc = scope(serde_json::json!([{ "command": "npx", "cwd": ["/etc*"] }]));
+    in_dir.cwd = Some("/etc/ssh".to_string());
+    assert!(SpawnScope::check(&[], &deny_etc, &SpawnRequest::from_config(&in_dir)).is_err());
+}
+
+/// Test spawn entries of capability scopes, and that the registry applies
+/// them on every connect made in a scope
+#[cfg(unix)]
+#[tokio::test]
+async fn test_spawn_entries_in_scope_rules() {
+    let entries = |json: serde_json::Value| -> Vec<Arc<McpScope>> {
+        serde_json::from_value::<Vec<McpScope>>(json)
+            .unwrap()
+            .into_iter()
+            .map(Arc::new)
+            .collect()
+    };
+    let rules = ScopeRules::new(
+        entries(serde_json::json!([{ "server": "github" }, { "server": "github", "command": "npx" }])),
+        entries(serde_json::json!([{ "command": "npx", "envKeys": ["*TOKEN*"] }])),
+    );
+
+    // Spawn entries don't affect which servers may be used
+    assert!(rules.check_server("github").is_ok());
+    assert!(rules.check_server("other").is_err());
+    let npx = server("npx", &["-y", "server-github"], &[]);
+    assert!(rules.check_spawn("github", &SpawnRequest::from_config(&npx)).is_ok());
+    assert_eq!(rules.check_spawn("other", &SpawnRequest::from_config(&npx)).unwrap_err().code, "SPAWN_NOT_PERMITTED");
+    assert!(rules.check_spawn("github", &SpawnRequest::from_config(&server("sh", &[], &[]))).is_err());
+    assert!(rules.check_spawn("github", &SpawnRequest::from_config(&server("npx", &[], &["GITHUB_TOKEN"]))).is_err());
+    assert!(ScopeRules::default().check_spawn("any", &SpawnRequest::from_config(&server("sh", &[], &[]))).is_ok());
+
+    // Connects in a scope are checked and audited like the spawn policy
+    let registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    let only_npx = ScopeRules::new(entries(serde_json::json!([{ "command": "npx" }])), vec![]);
+    let error = registry
+        .connect_server_in_scope("blocked".to_string(), server("sh", &["-c", "cat"], &[]), &only_npx)
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, "SPAWN_NOT_PERMITTED");
+    assert!(!registry.is_server_connected("blocked").unwrap());
+    let audited = registry.audit_log().entries();
+    assert_eq!(audited.len(), 1);
+    assert!(audited[0].is_blocked());
+}
+
+/// Test blocked and allowed spawns are recorded in the audit log
+#[cfg(unix)]
+#[tokio::test]
+async fn test_spawns_are_audited() {
+    let mut registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    registry.set_settings(PluginConfig {
+        spawn_policy: SpawnPolicy::commands(["sh"]).with_args(["-c", "while read *"]),
+        ..PluginConfig::default()
+    });
+
+    let error = registry
+        .connect_server_with_config("blocked".to_string(), server("sh", &["-c", "rm -rf ~"], &[]))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, "ARGUMENT_NOT_ALLOWED");
+    assert!(!registry.is_server_connected("blocked").unwrap());
+
+    let script = r#"while read line; do id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p'); if [ -n "$id" ]; then echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{}}"; fi; done"#;
+    registry
+        .connect_server_with_config("allowed".to_string(), server("sh", &["-c", script], &[]))
+        .await
+        .unwrap();
+    registry.disconnect_server("allowed").await.unwrap();
+
+    let entries = registry.audit_log().entries();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].server_id, "blocked");
+    assert!(entries[0].is_blocked());
+    match entries[0].event {
+        AuditEvent::Spawn { ref error_code, ref args, .. } => {
+            assert_eq!(error_code.as_deref(), Some("ARGUMENT_NOT_ALLOWED"));
+            assert_eq!(args[1], "rm -rf ~");
+        }
+        ref other => panic!("expected a spawn entry, got {:?}", other),
+    }
+    assert!(!entries[1].is_blocked());
+
+    let json = serde_json::to_value(&entries[0]).unwrap();
+    assert_eq!(json["action"], "spawn");
+    assert_eq!(json["allowed"], false);
+}
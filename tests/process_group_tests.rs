@@ -0,0 +1,90 @@
+#![cfg(target_os = "linux")]
+
+use std::path::Path;
+use std::time::Duration;
+use tauri_plugin_mcp_client::{
+    config::ServerConfig, registry::ConnectionRegistry, shutdown::ShutdownConfig,
+};
+
+/// A process counts as gone once it no longer exists or is an unreaped zombie
+fn process_gone(pid: u32) -> bool {
+    match std::fs::read_to_string(format!("/proc/{}/stat", pid)) {
+        Ok(stat) => stat
+            .rsplit(')')
+            .next()
+            .map(|rest| rest.trim_start().starts_with('Z'))
+            .unwrap_or(true),
+        Err(_) => true,
+    }
+}
+
+/// Shell server that starts a background grandchild, records its pid,
+/// answers `initialize` and then runs `tail`
+fn server_with_grandchild(pid_file: &Path, tail: &str) -> ServerConfig {
+    let script = format!(
+        r#"sleep 60 & echo $! > '{}'; read line; echo '{{"jsonrpc":"2.0","id":0,"result":{{}}}}'; read line; {}"#,
+        pid_file.display(),
+        tail
+    );
+    let mut config = ServerConfig::new("sh", vec!["-c".to_string(), script]);
+    config.shutdown = ShutdownConfig {
+        stdin_timeout_ms: 200,
+        terminate_timeout_ms: 500,
+    };
+    config
+}
+
+fn read_pid(pid_file: &Path) -> u32 {
+    std::fs::read_to_string(pid_file).unwrap().trim().parse().unwrap()
+}
+
+async fn wait_until_gone(pid: u32) -> bool {
+    for _ in 0..40 {
+        if process_gone(pid) {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    false
+}
+
+/// Test disconnecting tears down processes spawned by the server
+#[tokio::test]
+async fn test_disconnect_kills_grandchildren() {
+    let dir = tempfile::tempdir().unwrap();
+    let pid_file = dir.path().join("grandchild.pid");
+    let registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+
+    registry
+        .connect_server_with_config(
+            "wrapper".to_string(),
+            server_with_grandchild(&pid_file, "while true; do sleep 1; done"),
+        )
+        .await
+        .unwrap();
+
+    let grandchild = read_pid(&pid_file);
+    assert!(!process_gone(grandchild));
+
+    registry.disconnect_server("wrapper").await.unwrap();
+    assert!(wait_until_gone(grandchild).await, "grandchild {} survived disconnect", grandchild);
+}
+
+/// Test a server that exits on its own does not leave orphans behind
+#[tokio::test]
+async fn test_crashed_server_grandchildren_are_cleaned_up() {
+    let dir = tempfile::tempdir().unwrap();
+    let pid_file = dir.path().join("grandchild.pid");
+    let registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+
+    registry
+        .connect_server_with_config(
+            "crashing-wrapper".to_string(),
+            server_with_grandchild(&pid_file, "sleep 0.2; exit 1"),
+        )
+        .await
+        .unwrap();
+
+    let grandchild = read_pid(&pid_file);
+    assert!(wait_until_gone(grandchild).await, "grandchild {} survived server exit", grandchild);
+}
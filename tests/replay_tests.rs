@@ -0,0 +1,204 @@
+use serde_json::{json, Value};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use tauri_plugin_mcp_client::{
+    config::{ServerConfig, TransportKind},
+    registry::ConnectionRegistry,
+    replay::{session_path, ReplaySession, NOT_RECORDED},
+    settings::PluginConfig,
+    traffic::TrafficConfig,
+};
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures")
+}
+
+fn fixture(name: &str) -> String {
+    fixtures_dir().join(name).to_string_lossy().into_owned()
+}
+
+/// Registry recording to and replaying from `session_dir`
+fn registry_with_sessions(session_dir: &Path) -> ConnectionRegistry<tauri::Wry> {
+    let mut registry = ConnectionRegistry::new();
+    registry.set_settings(PluginConfig {
+        session_dir: Some(session_dir.to_path_buf()),
+        ..PluginConfig::default()
+    });
+    registry
+}
+
+fn replay_server(session: &str) -> ServerConfig {
+    ServerConfig {
+        transport: TransportKind::Replay,
+        session: Some(session.to_string()),
+        ..ServerConfig::default()
+    }
+}
+
+/// Serve `messages` through a session and collect the replies
+fn serve(session: &ReplaySession, messages: &[Value]) -> Vec<Value> {
+    let input: String = messages.iter().map(|m| format!("{}\n", m)).collect();
+    let mut output = Vec::new();
+    session.serve(Cursor::new(input), &mut output).unwrap();
+    String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+/// Test recorded replies are played back with the live request ids
+#[test]
+fn test_replay_maps_request_ids() {
+    let session = ReplaySession::load(fixture("echo_session.jsonl").as_ref()).unwrap();
+    assert_eq!(session.len(), 8);
+
+    let replies = serve(
+        &session,
+        &[
+            json!({ "jsonrpc": "2.0", "id": 40, "method": "initialize", "params": {} }),
+            json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+            json!({ "jsonrpc": "2.0", "id": 41, "method": "tools/call", "params": { "name": "echo" } }),
+            json!({ "jsonrpc": "2.0", "id": 42, "method": "tools/call", "params": { "name": "echo" } }),
+        ],
+    );
+    assert_eq!(replies.len(), 4);
+    assert_eq!(replies[0]["id"], 40);
+    assert_eq!(replies[0]["result"]["serverInfo"]["name"], "echo");
+    // Notifications sent by the server are replayed as recorded
+    assert_eq!(replies[1]["method"], "notifications/progress");
+    assert_eq!(replies[2]["id"], 41);
+    assert_eq!(replies[2]["result"]["content"][0]["text"], "hello");
+    // The recording has a single call
+    assert_eq!(replies[3]["id"], 42);
+    assert_eq!(replies[3]["error"]["code"], NOT_RECORDED);
+}
+
+/// Test invalid sessions and replay configs are rejected
+#[test]
+fn test_invalid_sessions() {
+    let error = ReplaySession::from_jsonl("{\"direction\":\"sent\"}\nnot json").unwrap_err();
+    assert_eq!(error.code, "CONFIG_ERROR");
+    assert!(error.details.unwrap().contains("line 1"));
+    assert!(ReplaySession::from_jsonl("\n\n").unwrap().is_empty());
+
+    let error = ReplaySession::load("/nonexistent/session.jsonl".as_ref()).unwrap_err();
+    assert_eq!(error.code, "SESSION_READ_FAILED");
+
+    let config = ServerConfig {
+        transport: TransportKind::Replay,
+        ..ServerConfig::default()
+    };
+    assert!(config.validate("replayed").is_err());
+    assert!(replay_server("session.jsonl").validate("replayed").is_ok());
+}
+
+/// Test a tool flow runs against a recording, with no server installed
+#[tokio::test]
+async fn test_tool_flow_from_recording() {
+    let registry = registry_with_sessions(&fixtures_dir());
+    registry
+        .connect_server_with_config("echo".to_string(), replay_server("echo_session.jsonl"))
+        .await
+        .unwrap();
+
+    let tools = registry.list_tools("echo").await.unwrap();
+    assert_eq!(tools["tools"][0]["name"], "echo");
+
+    let (result, _) = registry
+        .execute_tool("echo", "echo", json!({ "message": "hello" }))
+        .await
+        .unwrap();
+    assert_eq!(result["content"][0]["text"], "hello");
+
+    let error = registry.list_tools("echo").await.unwrap_err();
+    assert!(error.details.unwrap().contains("No recorded response for tools/list"));
+    registry.disconnect_server("echo").await.unwrap();
+}
+
+/// Test a live session recorded to a file replays the same results
+#[cfg(unix)]
+#[tokio::test]
+async fn test_record_then_replay() {
+    let dir = tempfile::tempdir().unwrap();
+    let session = dir.path().join("sessions").join("echo.jsonl");
+    let script = r#"while read line; do id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p'); if [ -n "$id" ]; then echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"tools\":[{\"name\":\"sh\"}]}}"; fi; done"#;
+    let live = ServerConfig {
+        traffic: TrafficConfig {
+            record_file: Some(session.clone()),
+            ..TrafficConfig::enabled()
+        },
+        ..ServerConfig::new("sh", vec!["-c".to_string(), script.to_string()])
+    };
+
+    let registry = registry_with_sessions(dir.path());
+    registry.connect_server_with_config("live".to_string(), live).await.unwrap();
+    let recorded = registry.list_tools("live").await.unwrap();
+    registry.disconnect_server("live").await.unwrap();
+
+    let lines = std::fs::read_to_string(&session).unwrap().lines().count();
+    assert_eq!(lines, registry.traffic("live").unwrap().len());
+
+    registry
+        .connect_server_with_config("replayed".to_string(), replay_server("sessions/echo.jsonl"))
+        .await
+        .unwrap();
+    assert_eq!(registry.list_tools("replayed").await.unwrap(), recorded);
+    registry.disconnect_server("replayed").await.unwrap();
+}
+
+/// Test record and replay files must stay inside the session directory
+#[tokio::test]
+async fn test_session_paths_confined() {
+    let dir = tempfile::tempdir().unwrap();
+    let sessions = dir.path().join("sessions");
+    std::fs::create_dir(&sessions).unwrap();
+    std::fs::write(dir.path().join("secret.txt"), "secret").unwrap();
+
+    assert_eq!(
+        session_path(Some(&sessions), "a/b.jsonl".as_ref()).unwrap(),
+        sessions.join("a/b.jsonl")
+    );
+    assert!(session_path(Some(&sessions), &sessions.join("b.jsonl")).is_ok());
+    for path in ["../secret.txt", "a/../../secret.txt"] {
+        let error = session_path(Some(&sessions), path.as_ref()).unwrap_err();
+        assert_eq!(error.code, "SESSION_PATH_NOT_ALLOWED");
+    }
+    assert!(session_path(Some(&sessions), &dir.path().join("secret.txt")).is_err());
+    assert!(session_path(None, "b.jsonl".as_ref()).is_err());
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(dir.path(), sessions.join("link")).unwrap();
+        assert!(session_path(Some(&sessions), "link/secret.txt".as_ref()).is_err());
+    }
+
+    // Neither recording nor replay reach outside the directory
+    let registry = registry_with_sessions(&sessions);
+    let error = registry
+        .connect_server_with_config("replayed".to_string(), replay_server("../secret.txt"))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, "SESSION_PATH_NOT_ALLOWED");
+
+    let recording = ServerConfig {
+        traffic: TrafficConfig {
+            record_file: Some(dir.path().join("secret.txt")),
+            ..TrafficConfig::enabled()
+        },
+        ..ServerConfig::new("sh", vec!["-c".to_string(), "cat".to_string()])
+    };
+    let error = registry
+        .connect_server_with_config("live".to_string(), recording)
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, "SESSION_PATH_NOT_ALLOWED");
+    assert_eq!(std::fs::read_to_string(dir.path().join("secret.txt")).unwrap(), "secret");
+
+    // Without a session directory replay is refused
+    let registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    let error = registry
+        .connect_server_with_config("echo".to_string(), replay_server(&fixture("echo_session.jsonl")))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, "SESSION_PATH_NOT_ALLOWED");
+}
@@ -0,0 +1,156 @@
+use serde_json::json;
+use std::time::Duration;
+use tauri_plugin_mcp_client::resources::{
+    ResourceMonitorConfig, ResourceSample, ResourceStats, ResourceThresholds, ThresholdAction,
+};
+
+/// Test monitoring is off by default and thresholds sit at the top level
+#[test]
+fn test_resource_monitor_config() {
+    let config = ResourceMonitorConfig::default();
+    assert!(!config.enabled);
+    assert!(config.is_default());
+    assert_eq!(config.interval(), Duration::from_secs(10));
+
+    let config: ResourceMonitorConfig = serde_json::from_value(json!({
+        "enabled": true,
+        "intervalMs": 1000,
+        "maxRssMb": 512,
+        "maxOpenFds": 100,
+        "action": "restart"
+    }))
+    .unwrap();
+    assert_eq!(config.thresholds.max_rss_mb, Some(512));
+    assert_eq!(config.thresholds.max_open_fds, Some(100));
+    assert_eq!(config.thresholds.max_cpu_percent, None);
+    assert_eq!(config.breach_samples, 3);
+    assert_eq!(config.action, ThresholdAction::Restart);
+    assert_eq!(serde_json::to_value(&config).unwrap()["maxRssMb"], 512);
+}
+
+/// Test thresholds and the streak of samples over them
+#[test]
+fn test_thresholds_and_stats() {
+    let thresholds = ResourceThresholds {
+        max_rss_mb: Some(100),
+        max_cpu_percent: Some(50.0),
+        ..ResourceThresholds::default()
+    };
+    let sample = |rss_mb: u64, cpu_percent: f64| ResourceSample {
+        processes: 1,
+        cpu_percent,
+        rss_bytes: rss_mb * 1024 * 1024,
+        threads: 4,
+        ..ResourceSample::default()
+    };
+
+    assert!(thresholds.exceeded(&sample(100, 50.0)).is_empty());
+    let exceeded = thresholds.exceeded(&sample(150, 80.0));
+    assert_eq!(exceeded.iter().map(|b| b.resource.as_str()).collect::<Vec<_>>(), ["rss", "cpu"]);
+    assert_eq!(exceeded[0].limit, (100 * 1024 * 1024) as f64);
+
+    let mut stats = ResourceStats::default();
+    stats.record(sample(150, 0.0), thresholds.exceeded(&sample(150, 0.0)), 100);
+    stats.record(sample(200, 0.0), thresholds.exceeded(&sample(200, 0.0)), 101);
+    assert_eq!(stats.consecutive_breaches, 2);
+    stats.record(sample(50, 0.0), Vec::new(), 102);
+    assert_eq!(stats.consecutive_breaches, 0);
+    assert!(stats.exceeded.is_empty());
+    assert_eq!(stats.samples, 3);
+    assert_eq!(stats.peak_rss_bytes, 200 * 1024 * 1024);
+    assert_eq!(stats.last.unwrap().rss_bytes, 50 * 1024 * 1024);
+    assert_eq!(stats.last_sample_at, Some(102));
+}
+
+/// Test a process tree is sampled from /proc, children included
+#[cfg(target_os = "linux")]
+#[test]
+fn test_sample_process_tree() {
+    use tauri_plugin_mcp_client::resources::{sample_process_tree, ResourceSampler};
+
+    let mut child = std::process::Command::new("sh")
+        .args(["-c", "sleep 5 & sleep 5 & while :; do :; done"])
+        .spawn()
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+
+    let mut sampler = ResourceSampler::new(child.id());
+    let first = sampler.sample().unwrap();
+    assert_eq!(first.processes, 3);
+    assert!(first.threads >= 3);
+    assert!(first.rss_bytes > 0);
+    assert!(first.open_fds >= 3);
+    assert_eq!(first.cpu_percent, 0.0);
+
+    std::thread::sleep(Duration::from_millis(300));
+    let second = sampler.sample().unwrap();
+    // The shell loop keeps a core busy
+    assert!(second.cpu_percent > 30.0, "{:?}", second);
+    assert!(second.cpu_time_ms > first.cpu_time_ms);
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+    assert!(sample_process_tree(child.id()).is_none());
+}
+
+#[cfg(target_os = "linux")]
+const ECHO_SERVER: &str = r#"echo $$ >> "$PID_FILE"; while read line; do id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p'); if [ -n "$id" ]; then echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{}}"; fi; done"#;
+
+/// Test the registry samples servers, flags thresholds and restarts on them
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_registry_resource_monitoring() {
+    use tauri_plugin_mcp_client::{
+        config::ServerConfig, registry::ConnectionRegistry, restart::RestartPolicy, secrets::EnvValue,
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let server = |name: &str, action: ThresholdAction| {
+        let mut config = ServerConfig::new("sh", vec!["-c".to_string(), ECHO_SERVER.to_string()]);
+        let pid_file = dir.path().join(name).to_string_lossy().into_owned();
+        config.env.insert("PID_FILE".to_string(), EnvValue::Plain(pid_file));
+        config.resources = ResourceMonitorConfig {
+            action,
+            breach_samples: 2,
+            ..ResourceMonitorConfig::enabled()
+                .with_interval(Duration::from_millis(100))
+                .with_thresholds(ResourceThresholds { max_threads: Some(0), ..Default::default() })
+        };
+        config.restart = RestartPolicy { initial_backoff_ms: 50, ..RestartPolicy::on_failure() };
+        config
+    };
+
+    let registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    registry.connect_server_with_config("warned".to_string(), server("warned", ThresholdAction::Warn)).await.unwrap();
+    registry.connect_server_with_config("restarted".to_string(), server("restarted", ThresholdAction::Restart)).await.unwrap();
+
+    let mut warned = None;
+    for _ in 0..50 {
+        let statuses = registry.get_connection_statuses().unwrap();
+        let info = statuses.into_iter().find(|info| info.server_id == "warned").unwrap();
+        if info.resources.as_ref().is_some_and(|stats| stats.consecutive_breaches >= 3) {
+            warned = info.resources;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    let stats = warned.expect("resource samples were not recorded");
+    assert_eq!(stats.exceeded[0].resource, "threads");
+    assert!(stats.last.unwrap().threads >= 1);
+    // Warnings leave the server running
+    assert!(registry.is_server_connected("warned").unwrap());
+    assert_eq!(std::fs::read_to_string(dir.path().join("warned")).unwrap().lines().count(), 1);
+
+    let mut starts = 0;
+    for _ in 0..50 {
+        starts = std::fs::read_to_string(dir.path().join("restarted")).unwrap().lines().count();
+        if starts >= 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert!(starts >= 2, "server over its thresholds was not restarted");
+
+    registry.disconnect_server("warned").await.unwrap();
+    registry.disconnect_server("restarted").await.unwrap();
+}
@@ -0,0 +1,101 @@
+use serde_json::json;
+use std::time::Duration;
+use tauri_plugin_mcp_client::{
+    config::{parse_servers_file, ServerConfig},
+    registry::ConnectionRegistry,
+    restart::{RestartMode, RestartPolicy},
+    state::ConnectionState,
+};
+
+/// Test the default policy never restarts and is omitted when serialized
+#[test]
+fn test_default_policy() {
+    let policy = RestartPolicy::default();
+    assert_eq!(policy.mode, RestartMode::Never);
+    assert!(policy.is_default());
+    assert!(!policy.should_restart(false));
+    assert!(!policy.should_restart(true));
+
+    let value = serde_json::to_value(ServerConfig::new("node", vec![])).unwrap();
+    assert!(value.get("restart").is_none());
+}
+
+/// Test which exits trigger a restart for each mode
+#[test]
+fn test_should_restart() {
+    let on_failure = RestartPolicy::on_failure();
+    assert!(on_failure.should_restart(false));
+    assert!(!on_failure.should_restart(true));
+
+    let always = RestartPolicy::always();
+    assert!(always.should_restart(false));
+    assert!(always.should_restart(true));
+}
+
+/// Test exponential backoff growth and capping
+#[test]
+fn test_backoff_for_attempt() {
+    let policy = RestartPolicy {
+        initial_backoff_ms: 100,
+        max_backoff_ms: 1_000,
+        multiplier: 2.0,
+        ..RestartPolicy::on_failure()
+    };
+
+    assert_eq!(policy.backoff_for_attempt(0), Duration::from_millis(100));
+    assert_eq!(policy.backoff_for_attempt(1), Duration::from_millis(200));
+    assert_eq!(policy.backoff_for_attempt(3), Duration::from_millis(800));
+    assert_eq!(policy.backoff_for_attempt(4), Duration::from_millis(1_000));
+    assert_eq!(policy.backoff_for_attempt(u32::MAX), Duration::from_millis(1_000));
+
+    // Multipliers below 1 never shrink the delay
+    let flat = RestartPolicy {
+        multiplier: 0.5,
+        ..policy
+    };
+    assert_eq!(flat.backoff_for_attempt(5), Duration::from_millis(100));
+}
+
+/// Test partial policies in config files fall back to the defaults
+#[test]
+fn test_policy_deserialization() {
+    let file = parse_servers_file(
+        &json!({
+            "mcpServers": {
+                "flaky": {
+                    "command": "node",
+                    "args": ["server.js"],
+                    "restart": { "mode": "on-failure", "maxRetries": 3 }
+                }
+            }
+        })
+        .to_string(),
+        false,
+    )
+    .unwrap();
+
+    let policy = &file.mcp_servers["flaky"].restart;
+    assert_eq!(policy.mode, RestartMode::OnFailure);
+    assert_eq!(policy.max_retries, 3);
+    assert_eq!(policy.initial_backoff_ms, RestartPolicy::default().initial_backoff_ms);
+    assert_eq!(policy.max_backoff_ms, RestartPolicy::default().max_backoff_ms);
+}
+
+/// Test a failed initial connect is reported directly and not retried
+#[tokio::test]
+async fn test_failed_connect_is_not_restarted() {
+    let registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    let mut config = ServerConfig::new("nonexistent-mcp-command-12345", vec![]);
+    config.restart = RestartPolicy::always();
+
+    let result = registry
+        .connect_server_with_config("flaky-server".to_string(), config)
+        .await;
+    assert!(result.is_err());
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!registry.is_server_connected("flaky-server").unwrap());
+    let statuses = registry.get_connection_statuses().unwrap();
+    assert_eq!(statuses[0].status, ConnectionState::Failed);
+    assert!(statuses[0].last_error.is_some());
+}
@@ -0,0 +1,185 @@
+use serde_json::json;
+use tauri_plugin_mcp_client::sandbox::{self, SandboxConfig, SandboxProfile};
+
+fn output(config: &SandboxConfig, script: &str) -> String {
+    let output = sandbox::command(config, "sh", &["-c".to_string(), script.to_string()], None)
+        .unwrap()
+        .output()
+        .unwrap();
+    String::from_utf8_lossy(&output.stdout).into_owned() + &String::from_utf8_lossy(&output.stderr)
+}
+
+/// Test the sandbox parses from the configuration shape
+#[test]
+fn test_sandbox_config() {
+    let config: SandboxConfig = serde_json::from_value(json!({
+        "profile": "isolated",
+        "memoryMb": 512,
+        "maxOpenFiles": 64,
+        "readPaths": ["~/.nvm"],
+        "writePaths": ["/tmp/notes"],
+        "network": true
+    }))
+    .unwrap();
+    assert_eq!(
+        config,
+        SandboxConfig::isolated()
+            .with_memory_mb(512)
+            .with_max_open_files(64)
+            .with_read_path("~/.nvm")
+            .with_write_path("/tmp/notes")
+            .with_network()
+    );
+    assert!(SandboxConfig::default().is_default());
+    assert!(!SandboxConfig::default().is_enabled());
+    assert!(SandboxConfig::default().with_cpu_secs(10).is_enabled());
+    assert!(SandboxProfile::Isolated > SandboxProfile::Restricted);
+
+    let error = SandboxConfig::restricted().with_read_path("relative/dir").check().unwrap_err();
+    assert_eq!(error.code, "CONFIG_ERROR");
+}
+
+/// Test a server's sandbox can only tighten the plugin-wide one
+#[test]
+fn test_effective_sandbox() {
+    let plugin = SandboxConfig::restricted()
+        .with_memory_mb(1024)
+        .with_write_path("/srv/data")
+        .with_read_path("/opt/tools");
+
+    // Servers without a sandbox get the plugin-wide one
+    assert_eq!(SandboxConfig::effective(&plugin, &SandboxConfig::default()).unwrap(), plugin);
+
+    let server = SandboxConfig::isolated()
+        .with_memory_mb(4096)
+        .with_cpu_secs(60)
+        .with_network()
+        .with_write_path("/srv/data/notes")
+        .with_read_path("/opt/tools/node");
+    let effective = SandboxConfig::effective(&plugin, &server).unwrap();
+    assert_eq!(effective.profile, SandboxProfile::Isolated);
+    assert_eq!(effective.memory_mb, Some(1024));
+    assert_eq!(effective.cpu_secs, Some(60));
+    assert!(!effective.network);
+
+    let weaker = SandboxConfig { profile: SandboxProfile::Off, ..server.clone() };
+    assert_eq!(SandboxConfig::effective(&plugin, &weaker).unwrap().profile, SandboxProfile::Restricted);
+
+    // Read-only paths can't be written to
+    let error = SandboxConfig::effective(&plugin, &SandboxConfig::restricted().with_write_path("/opt/tools")).unwrap_err();
+    assert_eq!(error.code, "SANDBOX_PATH_NOT_ALLOWED");
+    let error = SandboxConfig::effective(&plugin, &SandboxConfig::restricted().with_read_path("/home")).unwrap_err();
+    assert_eq!(error.code, "SANDBOX_PATH_NOT_ALLOWED");
+    assert!(SandboxConfig::effective(&plugin, &SandboxConfig::restricted().with_read_path("/srv/data")).is_ok());
+
+    // Without a plugin-wide profile, servers declare what they like
+    let limits_only = SandboxConfig::default().with_max_open_files(128);
+    let effective = SandboxConfig::effective(&limits_only, &server).unwrap();
+    assert!(effective.network);
+    assert_eq!(effective.max_open_files, Some(128));
+}
+
+/// Test server paths can't leave the plugin-wide ones through `..` or symlinks
+#[test]
+fn test_sandbox_path_escapes() {
+    let plugin = SandboxConfig::restricted().with_write_path("~/Documents/notes");
+    let escape = SandboxConfig::restricted().with_write_path("~/Documents/notes/../../.ssh");
+    let error = SandboxConfig::effective(&plugin, &escape).unwrap_err();
+    assert_eq!(error.code, "CONFIG_ERROR");
+
+    let dir = tempfile::tempdir().unwrap();
+    let allowed = dir.path().join("allowed");
+    std::fs::create_dir_all(allowed.join("sub")).unwrap();
+    std::fs::create_dir(dir.path().join("secret")).unwrap();
+    let plugin = SandboxConfig::restricted().with_write_path(allowed.to_str().unwrap());
+    let inside = |path: &std::path::Path| {
+        SandboxConfig::effective(&plugin, &SandboxConfig::restricted().with_write_path(path.to_str().unwrap()))
+    };
+    assert!(inside(&allowed.join("sub")).is_ok());
+    assert!(inside(&allowed.join("sub/missing")).is_ok());
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(dir.path().join("secret"), allowed.join("link")).unwrap();
+        assert_eq!(inside(&allowed.join("link")).unwrap_err().code, "SANDBOX_PATH_NOT_ALLOWED");
+        assert_eq!(inside(&allowed.join("link/missing")).unwrap_err().code, "SANDBOX_PATH_NOT_ALLOWED");
+    }
+}
+
+/// Test the isolated profile refuses a working directory it doesn't mount
+#[test]
+fn test_isolated_cwd_must_be_mounted() {
+    let dir = tempfile::tempdir().unwrap();
+    let mounted = dir.path().join("mounted");
+    std::fs::create_dir_all(mounted.join("sub")).unwrap();
+    std::fs::create_dir(dir.path().join("other")).unwrap();
+    let config = SandboxConfig::isolated().with_read_path(mounted.to_str().unwrap());
+    let run = |cwd: &std::path::Path| sandbox::command(&config, "true", &[], Some(cwd)).map(|_| ());
+
+    let error = run(&dir.path().join("other")).unwrap_err();
+    assert_eq!(error.code, "CONFIG_ERROR");
+    assert!(error.details.unwrap().contains("readPaths"));
+    assert_eq!(run(&mounted.join("sub/../../other")).unwrap_err().code, "CONFIG_ERROR");
+    // Inside a declared path only the availability of bubblewrap matters
+    if let Err(error) = run(&mounted.join("sub")) {
+        assert_eq!(error.code, "SANDBOX_UNAVAILABLE");
+    }
+}
+
+/// Test resource limits are applied to the spawned process
+#[cfg(unix)]
+#[test]
+fn test_resource_limits() {
+    let config = SandboxConfig::default().with_max_open_files(64).with_cpu_secs(30);
+    assert_eq!(output(&config, "ulimit -n; ulimit -t").split_whitespace().collect::<Vec<_>>(), ["64", "30"]);
+}
+
+/// Test the restricted profile sets `no_new_privs`, loads the seccomp filter
+/// and blocks IP sockets and new namespaces
+#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+#[test]
+fn test_restricted_profile() {
+    let status = output(&SandboxConfig::restricted(), "cat /proc/self/status");
+    assert!(status.contains("NoNewPrivs:\t1"), "{}", status);
+    assert!(status.contains("Seccomp:\t2"), "{}", status);
+
+    if std::path::Path::new("/bin/bash").exists() {
+        let connect = |config: &SandboxConfig| {
+            let output = sandbox::command(config, "bash", &["-c".to_string(), "exec 3<>/dev/tcp/127.0.0.1/9".to_string()], None)
+                .unwrap()
+                .output()
+                .unwrap();
+            String::from_utf8_lossy(&output.stderr).into_owned()
+        };
+        assert!(connect(&SandboxConfig::restricted()).contains("Permission denied"));
+        assert!(!connect(&SandboxConfig::restricted().with_network()).contains("Permission denied"));
+    }
+
+    if std::path::Path::new("/usr/bin/unshare").exists() {
+        let unshared = output(&SandboxConfig::restricted(), "unshare --user --map-root-user true && echo unshared");
+        assert!(!unshared.contains("unshared"), "{}", unshared);
+    }
+}
+
+/// Test connecting fails before spawning when the sandbox can't be set up
+#[tokio::test]
+async fn test_sandbox_checked_on_connect() {
+    use tauri_plugin_mcp_client::{config::ServerConfig, registry::ConnectionRegistry, settings::PluginConfig};
+
+    let mut registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    registry.set_settings(PluginConfig {
+        sandbox: SandboxConfig::restricted().with_read_path("/usr/share"),
+        ..PluginConfig::default()
+    });
+    let server = ServerConfig {
+        sandbox: SandboxConfig::restricted().with_read_path("/etc"),
+        ..ServerConfig::new("sh", vec!["-c".to_string(), "cat".to_string()])
+    };
+    let error = registry.connect_server_with_config("sandboxed".to_string(), server).await.unwrap_err();
+    assert_eq!(error.code, "SANDBOX_PATH_NOT_ALLOWED");
+    assert!(!registry.is_server_connected("sandboxed").unwrap());
+
+    if sandbox::bubblewrap_path().is_none() {
+        let error = SandboxConfig::isolated().check().unwrap_err();
+        assert_eq!(error.code, "SANDBOX_UNAVAILABLE");
+    }
+}
@@ -0,0 +1,80 @@
+use serde_json::json;
+use std::sync::Arc;
+use tauri_plugin_mcp_client::scope::{McpScope, ScopeRules};
+
+fn entries(value: serde_json::Value) -> Vec<Arc<McpScope>> {
+    serde_json::from_value::<Vec<McpScope>>(value)
+        .unwrap()
+        .into_iter()
+        .map(Arc::new)
+        .collect()
+}
+
+/// Test server, tool and resource entries with globs
+#[test]
+fn test_scope_entries() {
+    let rules = ScopeRules::new(
+        entries(json!([
+            { "server": "github", "tool": "search_*" },
+            { "server": "docs" },
+            { "server": "files", "resource": "file:///public/*" }
+        ])),
+        entries(json!([{ "tool": "*_delete" }, { "server": "docs", "resource": "*secret*" }])),
+    );
+
+    assert!(rules.server_allowed("github"));
+    assert!(rules.server_allowed("files"));
+    assert!(!rules.server_allowed("shell"));
+
+    assert!(rules.tool_allowed("github", "search_code"));
+    assert!(!rules.tool_allowed("github", "create_issue"));
+    // Server-wide entries cover every tool, deny entries still apply
+    assert!(rules.tool_allowed("docs", "lookup"));
+    assert!(!rules.tool_allowed("docs", "page_delete"));
+    // Resource entries don't grant tools
+    assert!(!rules.tool_allowed("files", "read"));
+
+    assert!(rules.resource_allowed("files", "file:///public/readme.md"));
+    assert!(!rules.resource_allowed("files", "file:///etc/passwd"));
+    assert!(rules.resource_allowed("docs", "docs://guide"));
+    assert!(!rules.resource_allowed("docs", "docs://secret-plans"));
+    // A deny entry for some tools doesn't block the server
+    assert!(rules.check_server("docs").is_ok());
+
+    let error = rules.check_tool("github", "create_issue").unwrap_err();
+    assert_eq!(error.code, "TOOL_NOT_PERMITTED");
+    assert_eq!(rules.check_tool("shell", "run").unwrap_err().code, "SERVER_NOT_PERMITTED");
+    assert_eq!(
+        rules.check_resource("files", "file:///etc/passwd").unwrap_err().code,
+        "RESOURCE_NOT_PERMITTED"
+    );
+}
+
+/// Test the plugin-wide and command scopes must both permit access
+#[test]
+fn test_scope_layers() {
+    let unrestricted = ScopeRules::default();
+    assert!(unrestricted.is_unrestricted());
+    assert!(unrestricted.tool_allowed("any", "tool"));
+
+    let rules = ScopeRules::new(entries(json!([{ "server": "github" }])), Vec::new())
+        .and(entries(json!([{ "tool": "search_*" }])), Vec::new());
+    assert!(rules.tool_allowed("github", "search_code"));
+    assert!(!rules.tool_allowed("github", "delete_repo"));
+    assert!(!rules.tool_allowed("gitlab", "search_code"));
+}
+
+/// Test listings leave out what the scope doesn't permit
+#[test]
+fn test_scope_filters_listings() {
+    let rules = ScopeRules::new(Vec::new(), entries(json!([{ "tool": "admin_*" }, { "resource": "secret://*" }])));
+
+    let mut tools = json!({ "tools": [{ "name": "search" }, { "name": "admin_reset" }], "nextCursor": "2" });
+    rules.filter_tools("github", &mut tools);
+    assert_eq!(tools, json!({ "tools": [{ "name": "search" }], "nextCursor": "2" }));
+
+    let mut resources = json!({ "resources": [{ "uri": "secret://keys" }, { "uri": "file:///a" }] });
+    rules.filter_resources("github", &mut resources);
+    assert_eq!(resources["resources"].as_array().unwrap().len(), 1);
+    assert_eq!(resources["resources"][0]["uri"], "file:///a");
+}
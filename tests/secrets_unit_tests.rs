@@ -0,0 +1,114 @@
+use serde_json::json;
+use std::collections::HashMap;
+use tauri_plugin_mcp_client::{
+    config::ServerConfig,
+    error::ErrorCategory,
+    registry::ConnectionRegistry,
+    secrets::{resolve_env, EnvValue, MemorySecretProvider, SecretRedactor, REDACTED},
+};
+
+/// Test that env values deserialize from both literal and secret forms
+#[test]
+fn test_env_value_deserialization() {
+    let env: HashMap<String, EnvValue> = serde_json::from_value(json!({
+        "LOG_LEVEL": "debug",
+        "GITHUB_TOKEN": { "secret": "github" }
+    }))
+    .unwrap();
+
+    assert_eq!(env["LOG_LEVEL"], EnvValue::Plain("debug".to_string()));
+    assert_eq!(
+        env["GITHUB_TOKEN"],
+        EnvValue::Secret {
+            secret: "github".to_string()
+        }
+    );
+}
+
+/// Test resolving secrets through a provider
+#[test]
+fn test_resolve_env_with_provider() {
+    let provider = MemorySecretProvider::new().with_secret("github", "ghp_supersecret");
+    let mut env = HashMap::new();
+    env.insert("LOG_LEVEL".to_string(), EnvValue::from("debug"));
+    env.insert(
+        "GITHUB_TOKEN".to_string(),
+        EnvValue::Secret {
+            secret: "github".to_string(),
+        },
+    );
+
+    let resolved = resolve_env(&env, Some(&provider)).unwrap();
+    assert_eq!(resolved.vars["LOG_LEVEL"], "debug");
+    assert_eq!(resolved.vars["GITHUB_TOKEN"], "ghp_supersecret");
+
+    // Only secret values are redacted
+    let redacted = resolved.redactor.redact("token=ghp_supersecret level=debug");
+    assert_eq!(redacted, format!("token={} level=debug", REDACTED));
+}
+
+/// Test missing secrets and missing providers fail with configuration errors
+#[test]
+fn test_resolve_env_errors() {
+    let mut env = HashMap::new();
+    env.insert(
+        "API_KEY".to_string(),
+        EnvValue::Secret {
+            secret: "missing".to_string(),
+        },
+    );
+
+    let no_provider = resolve_env(&env, None).err().unwrap();
+    assert_eq!(no_provider.category, ErrorCategory::Configuration);
+    assert_eq!(no_provider.code, "SECRET_PROVIDER_MISSING");
+
+    let provider = MemorySecretProvider::new();
+    let not_found = resolve_env(&env, Some(&provider)).err().unwrap();
+    assert_eq!(not_found.code, "SECRET_NOT_FOUND");
+    assert!(not_found.message.contains("missing"));
+}
+
+/// Test redaction of nested JSON values and errors
+#[test]
+fn test_redactor_json_and_errors() {
+    let mut redactor = SecretRedactor::new();
+    redactor.add_secret("s3cr3t");
+    redactor.add_secret("");
+
+    let value = json!({
+        "stderr": "auth failed for s3cr3t",
+        "lines": ["ok", "key=s3cr3t"],
+        "count": 2
+    });
+    let redacted = redactor.redact_value(&value);
+    assert_eq!(redacted["stderr"], format!("auth failed for {}", REDACTED));
+    assert_eq!(redacted["lines"][1], format!("key={}", REDACTED));
+    assert_eq!(redacted["count"], 2);
+
+    let error = tauri_plugin_mcp_client::error::MCPClientError::system_error("leaked s3cr3t");
+    let error = redactor.redact_error(error);
+    assert!(!error.details.unwrap().contains("s3cr3t"));
+
+    // Debug output never shows the secret values
+    assert!(!format!("{:?}", redactor).contains("s3cr3t"));
+}
+
+/// Test connecting with an unresolvable secret fails before spawning
+#[tokio::test]
+async fn test_connect_with_unresolved_secret() {
+    let registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    let mut config = ServerConfig::new("cat", vec![]);
+    config.env.insert(
+        "API_KEY".to_string(),
+        EnvValue::Secret {
+            secret: "api".to_string(),
+        },
+    );
+
+    let result = registry
+        .connect_server_with_config("secret-server".to_string(), config)
+        .await;
+
+    assert_eq!(result.err().unwrap().code, "SECRET_PROVIDER_MISSING");
+    assert!(!registry.is_server_connected("secret-server").unwrap());
+}
@@ -0,0 +1,252 @@
+use serde_json::json;
+use std::time::Duration;
+use tauri_plugin_mcp_client::{
+    handlers::ClientHandlers, logging::LogLevel, policy::SpawnPolicy, restart::RestartPolicy,
+    settings::PluginConfig, Builder,
+};
+
+/// Test the `plugins.mcp` section deserializes with defaults
+#[test]
+fn test_plugin_config_from_tauri_conf() {
+    let config: PluginConfig = serde_json::from_value(json!({
+        "maxConnections": 2,
+        "spawnPolicy": { "commands": ["npx"] },
+        "logLevel": "warn",
+        "restart": { "mode": "on-failure" }
+    }))
+    .unwrap();
+    assert_eq!(config.max_connections, Some(2));
+    assert_eq!(config.spawn_policy, SpawnPolicy::commands(["npx"]));
+    assert_eq!(config.log_level, LogLevel::Warn);
+    assert_eq!(config.restart, RestartPolicy::on_failure());
+    assert_eq!(config.request_timeout_ms, 5_000);
+    assert_eq!(config.tool_timeout_ms, 10_000);
+    assert_eq!(config.event_prefix, "mcp");
+
+    let empty: PluginConfig = serde_json::from_value(json!({})).unwrap();
+    assert_eq!(empty, PluginConfig::default());
+}
+
+/// Test builder settings take precedence over the config file
+#[test]
+fn test_builder_overrides_config() {
+    let base = PluginConfig {
+        max_connections: Some(2),
+        event_prefix: "from-conf".to_string(),
+        ..PluginConfig::default()
+    };
+
+    let resolved = Builder::new()
+        .request_timeout(Duration::from_secs(1))
+        .event_prefix("my-app")
+        .log_level(LogLevel::Error)
+        .resolve_config(Some(base));
+    assert_eq!(resolved.request_timeout_ms, 1_000);
+    assert_eq!(resolved.event_prefix, "my-app");
+    assert_eq!(resolved.log_level, LogLevel::Error);
+    // Untouched settings keep the config file value
+    assert_eq!(resolved.max_connections, Some(2));
+
+    assert_eq!(Builder::new().resolve_config(None), PluginConfig::default());
+}
+
+/// Test the event prefix
+#[test]
+fn test_event_names() {
+    let config = PluginConfig {
+        event_prefix: "my-app".to_string(),
+        ..PluginConfig::default()
+    };
+    assert_eq!(config.event_name("mcp://server-connected"), "my-app://server-connected");
+}
+
+/// Test server requests are answered by the registered handlers
+#[test]
+fn test_handlers_respond() {
+    let mut handlers = ClientHandlers::default();
+    assert_eq!(handlers.capabilities(), json!({}));
+
+    handlers.roots = Some(std::sync::Arc::new(|_server_id: &str, _params: serde_json::Value| {
+        Ok(json!({ "roots": [{ "uri": "file:///work", "name": "work" }] }))
+    }));
+    assert_eq!(handlers.capabilities(), json!({ "roots": { "listChanged": false } }));
+
+    let response = handlers.respond("s", &json!({ "jsonrpc": "2.0", "id": 7, "method": "roots/list" }));
+    assert_eq!(response["id"], 7);
+    assert_eq!(response["result"]["roots"][0]["name"], "work");
+
+    let response = handlers.respond("s", &json!({ "jsonrpc": "2.0", "id": 8, "method": "sampling/createMessage" }));
+    assert_eq!(response["error"]["code"], -32601);
+
+    // Elicitation isn't offered to servers on protocol versions without it
+    handlers.elicitation = Some(std::sync::Arc::new(|_server_id: &str, _params: serde_json::Value| {
+        Ok(json!({ "action": "decline" }))
+    }));
+    let request = json!({ "jsonrpc": "2.0", "id": 9, "method": "elicitation/create" });
+    assert_eq!(handlers.for_protocol("2025-06-18").respond("s", &request)["result"]["action"], "decline");
+    assert_eq!(handlers.for_protocol("2024-11-05").respond("s", &request)["error"]["code"], -32601);
+    assert!(handlers.for_protocol("2025-03-26").capabilities().get("elicitation").is_none());
+}
+
+#[cfg(unix)]
+mod registry {
+    use super::*;
+    use tauri_plugin_mcp_client::{config::ServerConfig, registry::ConnectionRegistry};
+
+    fn echo_server() -> ServerConfig {
+        let script = r#"while read line; do id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p'); if [ -n "$id" ]; then echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"tools\":[]}}"; fi; done"#;
+        ServerConfig::new("sh", vec!["-c".to_string(), script.to_string()])
+    }
+
+    /// Test the allow-list and connection limit are enforced on connect
+    #[tokio::test]
+    async fn test_connect_enforces_settings() {
+        let mut registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+        registry.set_settings(PluginConfig {
+            max_connections: Some(1),
+            spawn_policy: SpawnPolicy::commands(["sh"]),
+            ..PluginConfig::default()
+        });
+
+        let error = registry
+            .connect_server_with_config("python".to_string(), ServerConfig::new("python3", vec![]))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, "COMMAND_NOT_ALLOWED");
+
+        registry.connect_server_with_config("first".to_string(), echo_server()).await.unwrap();
+        let error = registry
+            .connect_server_with_config("second".to_string(), echo_server())
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, "CONNECTION_LIMIT_REACHED");
+
+        // Reconnecting the running server is still possible
+        registry.connect_server_with_config("first".to_string(), echo_server()).await.unwrap();
+        registry.disconnect_server("first").await.unwrap();
+    }
+
+    /// Test a server request sent during a call is answered before the call completes
+    #[tokio::test]
+    async fn test_server_request_answered_mid_call() {
+        let mut registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+        registry.set_handlers(ClientHandlers {
+            sampling: Some(std::sync::Arc::new(|server_id: &str, _params: serde_json::Value| {
+                Ok(json!({ "role": "assistant", "content": { "type": "text", "text": server_id } }))
+            })),
+            ..ClientHandlers::default()
+        });
+
+        // On tools/call, asks for a sample and returns the handler's answer as the tool result
+        let script = r#"read line; echo '{"jsonrpc":"2.0","id":0,"result":{}}'; read line
+read line; id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+echo '{"jsonrpc":"2.0","id":"s1","method":"sampling/createMessage","params":{}}'
+read answer
+echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"answer\":$answer}}"
+cat > /dev/null"#;
+        registry
+            .connect_server_with_config(
+                "sampler".to_string(),
+                ServerConfig::new("sh", vec!["-c".to_string(), script.to_string()]),
+            )
+            .await
+            .unwrap();
+
+        let (result, _) = registry.execute_tool("sampler", "ask", json!({})).await.unwrap();
+        assert_eq!(result["answer"]["id"], "s1");
+        assert_eq!(result["answer"]["result"]["content"]["text"], "sampler");
+        registry.disconnect_server("sampler").await.unwrap();
+    }
+
+    /// Test a slow handler doesn't hold up the response to a pending call
+    #[tokio::test]
+    async fn test_slow_handler_runs_off_the_call() {
+        let mut registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+        registry.set_handlers(ClientHandlers {
+            elicitation: Some(std::sync::Arc::new(|_server_id: &str, _params: serde_json::Value| {
+                std::thread::sleep(std::time::Duration::from_secs(2));
+                Ok(json!({ "action": "decline" }))
+            })),
+            ..ClientHandlers::default()
+        });
+
+        // On tools/call, asks the user for input but answers without waiting for it
+        let script = r#"read line; echo '{"jsonrpc":"2.0","id":0,"result":{}}'; read line
+read line; id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+echo '{"jsonrpc":"2.0","id":"e1","method":"elicitation/create","params":{}}'
+echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{}}"
+cat > /dev/null"#;
+        registry
+            .connect_server_with_config(
+                "asker".to_string(),
+                ServerConfig::new("sh", vec!["-c".to_string(), script.to_string()]),
+            )
+            .await
+            .unwrap();
+
+        let start = std::time::Instant::now();
+        registry.execute_tool("asker", "ask", json!({})).await.unwrap();
+        assert!(start.elapsed() < std::time::Duration::from_secs(1), "call took {:?}", start.elapsed());
+        registry.disconnect_server("asker").await.unwrap();
+    }
+
+    /// Test a flood of server requests runs on a few threads and the overflow is refused
+    #[tokio::test]
+    async fn test_server_requests_are_bounded() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let active = Arc::new(AtomicUsize::new(0));
+        let most_active = Arc::new(AtomicUsize::new(0));
+        let handled = Arc::new(AtomicUsize::new(0));
+        let mut registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+        registry.set_handlers(ClientHandlers {
+            elicitation: Some({
+                let (active, most_active, handled) = (active.clone(), most_active.clone(), handled.clone());
+                Arc::new(move |_server_id: &str, _params: serde_json::Value| {
+                    let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    most_active.fetch_max(now, Ordering::SeqCst);
+                    std::thread::sleep(std::time::Duration::from_millis(100));
+                    active.fetch_sub(1, Ordering::SeqCst);
+                    handled.fetch_add(1, Ordering::SeqCst);
+                    Ok(json!({ "action": "decline" }))
+                })
+            }),
+            ..ClientHandlers::default()
+        });
+
+        // Sends 60 requests at once after initializing
+        let script = r#"read line; echo '{"jsonrpc":"2.0","id":0,"result":{}}'; read line
+i=0; while [ $i -lt 60 ]; do i=$((i+1)); echo "{\"jsonrpc\":\"2.0\",\"id\":\"e$i\",\"method\":\"elicitation/create\",\"params\":{}}"; done
+cat > /dev/null"#;
+        registry
+            .connect_server_with_config(
+                "flood".to_string(),
+                ServerConfig::new("sh", vec!["-c".to_string(), script.to_string()]),
+            )
+            .await
+            .unwrap();
+
+        tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+        assert!(most_active.load(Ordering::SeqCst) <= 4, "{} handlers ran at once", most_active.load(Ordering::SeqCst));
+        let handled = handled.load(Ordering::SeqCst);
+        assert!(handled > 0 && handled < 60, "{} requests were handled", handled);
+        registry.disconnect_server("flood").await.unwrap();
+    }
+
+    /// Test a server answering with a protocol version the client doesn't speak is refused
+    #[tokio::test]
+    async fn test_unsupported_protocol_version() {
+        let registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+        let script = r#"read line; echo '{"jsonrpc":"2.0","id":0,"result":{"protocolVersion":"2023-01-01","capabilities":{}}}'; cat > /dev/null"#;
+        let error = registry
+            .connect_server_with_config(
+                "old".to_string(),
+                ServerConfig::new("sh", vec!["-c".to_string(), script.to_string()]),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, "UNSUPPORTED_PROTOCOL_VERSION");
+        assert!(!registry.is_server_connected("old").unwrap());
+    }
+}
@@ -0,0 +1,127 @@
+use serde_json::json;
+use std::time::{Duration, Instant};
+use tauri_plugin_mcp_client::shutdown::{ShutdownConfig, ShutdownStep};
+
+/// Test shutdown config defaults and serialization
+#[test]
+fn test_shutdown_config_defaults() {
+    let config = ShutdownConfig::default();
+    assert!(config.is_default());
+    assert_eq!(config.stdin_timeout(), Duration::from_millis(2_000));
+    assert_eq!(config.terminate_timeout(), Duration::from_millis(2_000));
+
+    let config: ShutdownConfig = serde_json::from_value(json!({ "stdinTimeoutMs": 500 })).unwrap();
+    assert_eq!(config.stdin_timeout_ms, 500);
+    assert_eq!(config.terminate_timeout_ms, 2_000);
+
+    assert_eq!(serde_json::to_value(ShutdownStep::StdinClosed).unwrap(), json!("stdin-closed"));
+    assert_eq!(ShutdownStep::Killed.to_string(), "process was killed");
+}
+
+/// Test grace periods are shortened to fit an exit deadline
+#[test]
+fn test_shutdown_config_within_deadline() {
+    let config = ShutdownConfig::default().within(Duration::from_millis(1_000));
+    assert_eq!(config.stdin_timeout_ms, 500);
+    assert_eq!(config.terminate_timeout_ms, 250);
+
+    // Shorter settings are kept
+    let short = ShutdownConfig {
+        stdin_timeout_ms: 100,
+        terminate_timeout_ms: 100,
+    };
+    assert_eq!(short.within(Duration::from_secs(60)), short);
+    assert_eq!(ShutdownConfig::default().within(Duration::ZERO), ShutdownConfig::immediate());
+}
+
+#[cfg(unix)]
+mod sequence {
+    use super::*;
+    use tauri_plugin_mcp_client::{
+        config::ServerConfig, process::MCPProcess, registry::ConnectionRegistry, state::ConnectionState,
+    };
+
+    fn fast_config() -> ShutdownConfig {
+        ShutdownConfig {
+            stdin_timeout_ms: 200,
+            terminate_timeout_ms: 200,
+        }
+    }
+
+    async fn stop_script(script: &str) -> (Option<ShutdownStep>, Duration) {
+        let mut process = MCPProcess::new("shutdown-test".to_string());
+        process.set_shutdown_config(fast_config());
+        process
+            .start("sh", &["-c".to_string(), script.to_string()])
+            .await
+            .unwrap();
+        // Let the shell install its traps
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let start = Instant::now();
+        let step = process.stop();
+        (step, start.elapsed())
+    }
+
+    /// Test a server that exits on EOF stops at the first step
+    #[tokio::test]
+    async fn test_stdin_close_ends_process() {
+        let (step, elapsed) = stop_script("cat > /dev/null").await;
+        assert_eq!(step, Some(ShutdownStep::StdinClosed));
+        assert!(elapsed < Duration::from_millis(200));
+    }
+
+    /// Test a server ignoring stdin is ended by SIGTERM
+    #[tokio::test]
+    async fn test_sigterm_ends_process() {
+        let (step, _) = stop_script("exec sleep 30").await;
+        assert_eq!(step, Some(ShutdownStep::Terminated));
+    }
+
+    /// Test a server ignoring SIGTERM is killed
+    #[tokio::test]
+    async fn test_sigkill_as_last_resort() {
+        let (step, elapsed) = stop_script("trap '' TERM; while true; do sleep 0.05; done").await;
+        assert_eq!(step, Some(ShutdownStep::Killed));
+        assert!(elapsed >= Duration::from_millis(400));
+    }
+
+    /// Test stopping an exited or never started process
+    #[tokio::test]
+    async fn test_already_exited_process() {
+        let (step, _) = stop_script("exit 0").await;
+        assert_eq!(step, Some(ShutdownStep::AlreadyExited));
+
+        let mut process = MCPProcess::new("never-started".to_string());
+        assert_eq!(process.stop(), None);
+    }
+
+    /// Test app-exit shutdown stops every server within the deadline
+    #[tokio::test]
+    async fn test_disconnect_all_respects_deadline() {
+        let registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+        // Answers initialize, then ignores stdin and SIGTERM
+        let script = r#"trap '' TERM; read line; echo '{"jsonrpc":"2.0","id":0,"result":{}}'; read line; while true; do sleep 0.05; done"#;
+        for server_id in ["first", "second"] {
+            registry
+                .connect_server_with_config(
+                    server_id.to_string(),
+                    ServerConfig::new("sh", vec!["-c".to_string(), script.to_string()]),
+                )
+                .await
+                .unwrap();
+        }
+        assert_eq!(registry.get_connection_statuses().unwrap().len(), 2);
+
+        let start = Instant::now();
+        registry
+            .disconnect_all(Duration::from_millis(800), "Application exiting")
+            .await;
+
+        // Both servers are stopped in parallel and need SIGKILL
+        assert!(start.elapsed() < Duration::from_millis(1_500));
+        let statuses = registry.get_connection_statuses().unwrap();
+        assert!(statuses.iter().all(|info| info.status == ConnectionState::Disconnected));
+        assert!(!registry.is_server_connected("first").unwrap());
+    }
+}
@@ -0,0 +1,85 @@
+use tauri_plugin_mcp_client::state::ConnectionState;
+
+/// Test which transitions the state machine allows
+#[test]
+fn test_transitions() {
+    use ConnectionState::*;
+
+    let lifecycle = [Spawning, Initializing, Ready, Degraded, Ready, Disconnecting, Disconnected, Reconnecting, Spawning];
+    for pair in lifecycle.windows(2) {
+        assert!(pair[0].can_transition_to(pair[1]), "{} -> {}", pair[0], pair[1]);
+    }
+    assert!(Spawning.can_transition_to(Failed));
+    assert!(Failed.can_transition_to(Spawning));
+    assert!(Failed.can_transition_to(Reconnecting));
+
+    assert!(!Ready.can_transition_to(Spawning));
+    assert!(!Ready.can_transition_to(Ready));
+    assert!(!Disconnecting.can_transition_to(Ready));
+    assert!(!Disconnected.can_transition_to(Ready));
+    assert!(!Disconnected.can_transition_to(Disconnecting));
+    assert!(!Spawning.can_transition_to(Spawning));
+
+    assert!(Degraded.is_active());
+    assert!(!Reconnecting.is_active());
+    assert_eq!(serde_json::to_value(Disconnecting).unwrap(), "disconnecting");
+    assert_eq!(serde_json::from_value::<ConnectionState>("ready".into()).unwrap(), Ready);
+    assert_eq!(Failed.to_string(), "failed");
+}
+
+/// Test disconnected and failed servers stay listed with their last error
+#[cfg(unix)]
+#[tokio::test]
+async fn test_registry_keeps_stopped_servers() {
+    use tauri_plugin_mcp_client::{config::ServerConfig, registry::ConnectionRegistry};
+
+    let registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    let status = |server_id: &str| {
+        registry
+            .get_connection_statuses()
+            .unwrap()
+            .into_iter()
+            .find(|info| info.server_id == server_id)
+            .unwrap()
+    };
+
+    let error = registry
+        .connect_server_with_config("missing".to_string(), ServerConfig::new("nonexistent-mcp-command-12345", vec![]))
+        .await
+        .unwrap_err();
+    let info = status("missing");
+    assert_eq!(info.status, ConnectionState::Failed);
+    assert_eq!(info.last_error.as_deref(), Some(error.message.as_str()));
+    assert_eq!(info.command, "nonexistent-mcp-command-12345");
+
+    let script = r#"while read line; do id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p'); if [ -n "$id" ]; then echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{}}"; fi; done"#;
+    let echo = ServerConfig::new("sh", vec!["-c".to_string(), script.to_string()]);
+    registry.connect_server_with_config("echo".to_string(), echo.clone()).await.unwrap();
+    assert_eq!(status("echo").status, ConnectionState::Ready);
+    assert!(status("echo").connected_at.is_some());
+
+    registry.disconnect_server("echo").await.unwrap();
+    assert_eq!(status("echo").status, ConnectionState::Disconnected);
+    assert!(!registry.is_server_connected("echo").unwrap());
+
+    // A failed server can be connected again, which clears its error
+    registry.connect_server_with_config("missing".to_string(), echo.clone()).await.unwrap();
+    let info = status("missing");
+    assert_eq!(info.status, ConnectionState::Ready);
+    assert!(info.last_error.is_none());
+
+    // Connecting a ready server again replaces its process
+    registry.connect_server_with_config("missing".to_string(), echo).await.unwrap();
+    assert_eq!(status("missing").status, ConnectionState::Ready);
+
+    // Disconnecting an unknown server doesn't add an entry
+    registry.disconnect_server("unknown").await.unwrap();
+    assert!(registry.get_connection_statuses().unwrap().iter().all(|info| info.server_id != "unknown"));
+
+    registry.disconnect_all(std::time::Duration::from_secs(2), "Test finished").await;
+    assert!(registry
+        .get_connection_statuses()
+        .unwrap()
+        .iter()
+        .all(|info| info.status == ConnectionState::Disconnected));
+}
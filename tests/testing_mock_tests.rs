@@ -0,0 +1,157 @@
+#![cfg(feature = "testing")]
+
+use serde_json::json;
+use std::time::{Duration, Instant};
+use tauri_plugin_mcp_client::{
+    client::{CallToolResult, Content, McpClient},
+    config::{ServerConfig, TransportKind},
+    liveness::LivenessConfig,
+    registry::ConnectionRegistry,
+    restart::RestartPolicy,
+    settings::PluginConfig,
+    testing::{text_result, MockFault, MockServer},
+};
+
+fn mock() -> MockServer {
+    MockServer::new("mock", "1.0.0")
+        .with_echo_tool()
+        .tool("fail", "Always fails", json!({ "type": "object" }), |_| Err("boom".to_string()))
+        .tool("add", "Add two numbers", json!({ "type": "object" }), |args| {
+            let sum = args["a"].as_i64().unwrap_or(0) + args["b"].as_i64().unwrap_or(0);
+            Ok(text_result(&sum.to_string()))
+        })
+        .resource("file:///readme.md", "readme", "text/markdown", "# Hello")
+        .prompt("greet", "Greet someone", &["name"], "Say hello to {name}")
+}
+
+fn text(result: &CallToolResult) -> String {
+    match result.content.first() {
+        Some(Content::Text { text }) => text.clone(),
+        other => panic!("expected text content, got {:?}", other),
+    }
+}
+
+async fn connect(server: MockServer, restart: RestartPolicy) -> (ConnectionRegistry<tauri::Wry>, McpClient<tauri::Wry>) {
+    let mut registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+    registry.set_settings(PluginConfig {
+        request_timeout_ms: 500,
+        tool_timeout_ms: 500,
+        restart,
+        ..PluginConfig::default()
+    });
+    registry.connect_mock("mock".to_string(), server).await.unwrap();
+    let client = McpClient::new(registry.clone());
+    (registry, client)
+}
+
+/// Test tools, resources and prompts through the typed client
+#[tokio::test]
+async fn test_mock_tools_resources_prompts() {
+    let (registry, client) = connect(mock(), RestartPolicy::default()).await;
+
+    let tools = client.list_tools("mock").await.unwrap();
+    assert_eq!(tools.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(), vec!["echo", "fail", "add"]);
+
+    let result = client.call_tool("mock", "add", json!({ "a": 2, "b": 3 })).await.unwrap();
+    assert_eq!(text(&result), "5");
+    let result = client.call_tool("mock", "fail", json!({})).await.unwrap();
+    assert!(result.is_error);
+
+    let resources = client.list_resources("mock").await.unwrap();
+    assert_eq!(resources[0].uri, "file:///readme.md");
+    let contents = client.read_resource("mock", "file:///readme.md").await.unwrap();
+    assert_eq!(contents[0].text.as_deref(), Some("# Hello"));
+
+    let prompt = client
+        .get_prompt("mock", "greet", [("name".to_string(), "Ada".to_string())].into())
+        .await
+        .unwrap();
+    assert_eq!(prompt.messages.len(), 1);
+    assert!(serde_json::to_string(&prompt.messages[0]).unwrap().contains("Say hello to Ada"));
+
+    registry.disconnect_server("mock").await.unwrap();
+}
+
+/// Test delays, malformed output and notifications
+#[tokio::test]
+async fn test_mock_delays_and_faults() {
+    let server = mock()
+        .delay("echo", Duration::from_millis(100))
+        .fault("fail", MockFault::Malformed)
+        .fault("resources/list", MockFault::NoResponse)
+        .notify("add", "notifications/progress", json!({ "progress": 1 }));
+    let (registry, client) = connect(server, RestartPolicy::default()).await;
+
+    let start = Instant::now();
+    client.call_tool("mock", "echo", json!({ "message": "hi" })).await.unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(100));
+
+    // Notifications before the response are skipped
+    assert_eq!(text(&client.call_tool("mock", "add", json!({ "a": 1, "b": 1 })).await.unwrap()), "2");
+
+    assert!(client.call_tool("mock", "fail", json!({})).await.is_err());
+    assert!(client.list_resources("mock").await.is_err());
+
+    registry.disconnect_server("mock").await.unwrap();
+}
+
+/// Test a simulated crash is reported as a process exit and restarted
+#[tokio::test]
+async fn test_mock_crash_restarts() {
+    let (registry, client) = connect(mock().fault("fail", MockFault::Crash(3)), RestartPolicy::on_failure()).await;
+
+    assert!(client.call_tool("mock", "fail", json!({})).await.is_err());
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let exit = loop {
+        let info = registry
+            .get_connection_statuses()
+            .unwrap()
+            .into_iter()
+            .find(|info| info.server_id == "mock");
+        if let Some(exit) = info.and_then(|info| info.last_exit) {
+            break exit;
+        }
+        assert!(Instant::now() < deadline, "crash was not noticed");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    };
+    assert_eq!(exit.code, Some(3));
+
+    // The on-failure policy brings the mock back
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !registry.is_server_connected("mock").unwrap() {
+        assert!(Instant::now() < deadline, "mock was not restarted");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(text(&client.call_tool("mock", "echo", json!({ "message": "back" })).await.unwrap()), "Echo: back");
+    registry.disconnect_server("mock").await.unwrap();
+}
+
+/// Test reconnecting an in-process server stops the liveness checks of the
+/// connection it replaced
+#[tokio::test]
+async fn test_reconnect_stops_old_liveness_checks() {
+    let (registry, _client) = connect(mock(), RestartPolicy::default()).await;
+    let config = ServerConfig {
+        transport: TransportKind::Mock,
+        liveness: LivenessConfig {
+            enabled: true,
+            interval_ms: 20,
+            ..LivenessConfig::default()
+        },
+        ..ServerConfig::default()
+    };
+    registry.connect_server_with_config("mock".to_string(), config).await.unwrap();
+
+    // Reconnect without liveness checks; nothing should ping it any more
+    registry.connect_mock("mock".to_string(), mock()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let info = registry
+        .get_connection_statuses()
+        .unwrap()
+        .into_iter()
+        .find(|info| info.server_id == "mock")
+        .unwrap();
+    assert!(info.latency.is_none(), "old liveness checks still ran: {:?}", info.latency);
+    registry.disconnect_server("mock").await.unwrap();
+}
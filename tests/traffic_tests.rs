@@ -0,0 +1,118 @@
+use serde_json::json;
+use tauri_plugin_mcp_client::traffic::{TrafficConfig, TrafficDirection, TrafficEntry, TrafficRecorder};
+
+/// Test responses are matched to their request and carry a latency
+#[test]
+fn test_records_direction_and_latency() {
+    let recorder = TrafficRecorder::new("s", TrafficConfig::enabled());
+    recorder.record(TrafficDirection::Sent, &json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" }));
+    // A server request reusing the same id must not be taken for our response
+    recorder.record(TrafficDirection::Received, &json!({ "jsonrpc": "2.0", "id": 1, "method": "roots/list" }));
+    recorder.record(TrafficDirection::Received, &json!({ "jsonrpc": "2.0", "id": 1, "result": {} }));
+    recorder.record(TrafficDirection::Received, &json!({ "jsonrpc": "2.0", "method": "notifications/progress" }));
+
+    let entries = recorder.entries();
+    assert_eq!(entries.len(), 4);
+    assert_eq!(entries[0].direction, TrafficDirection::Sent);
+    assert_eq!(entries[0].method.as_deref(), Some("tools/list"));
+    assert_eq!(entries[0].latency_ms, None);
+    assert_eq!(entries[1].latency_ms, None);
+    assert!(entries[2].latency_ms.is_some());
+    assert_eq!(entries[2].request_id, Some(json!(1)));
+    assert_eq!(entries[3].request_id, None);
+    assert!(entries.iter().all(|entry| entry.server_id == "s" && entry.bytes > 0));
+}
+
+/// Test the oldest entries are dropped past the caps
+#[test]
+fn test_size_caps() {
+    let recorder = TrafficRecorder::new(
+        "s",
+        TrafficConfig {
+            max_entries: 3,
+            ..TrafficConfig::enabled()
+        },
+    );
+    for id in 0..5 {
+        recorder.record(TrafficDirection::Sent, &json!({ "id": id, "method": "ping" }));
+    }
+    let ids: Vec<_> = recorder.entries().iter().map(|entry| entry.request_id.clone()).collect();
+    assert_eq!(ids, vec![Some(json!(2)), Some(json!(3)), Some(json!(4))]);
+
+    let message = json!({ "id": 9, "method": "ping" });
+    let size = serde_json::to_string(&message).unwrap().len();
+    recorder.set_config(TrafficConfig {
+        max_bytes: size * 2,
+        ..TrafficConfig::enabled()
+    });
+    assert_eq!(recorder.entries().len(), 2);
+
+    recorder.clear();
+    assert!(recorder.entries().is_empty());
+
+    // Nothing is recorded while disabled
+    recorder.set_config(TrafficConfig::default());
+    recorder.record(TrafficDirection::Sent, &message);
+    assert!(recorder.entries().is_empty());
+}
+
+/// Test the JSONL export and config defaults
+#[test]
+fn test_jsonl_export() {
+    let recorder = TrafficRecorder::new("s", TrafficConfig::enabled());
+    recorder.record(TrafficDirection::Sent, &json!({ "id": 1, "method": "ping" }));
+    recorder.record(TrafficDirection::Received, &json!({ "id": 1, "result": {} }));
+
+    let jsonl = recorder.to_jsonl();
+    let lines: Vec<TrafficEntry> = jsonl
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines, recorder.entries());
+    assert!(jsonl.contains(r#""direction":"received""#));
+
+    let config: TrafficConfig = serde_json::from_value(json!({ "enabled": true, "maxEntries": 10 })).unwrap();
+    assert_eq!(config.max_entries, 10);
+    assert_eq!(config.max_bytes, TrafficConfig::default().max_bytes);
+    assert!(TrafficConfig::default().is_default());
+}
+
+#[cfg(unix)]
+mod registry {
+    use super::*;
+    use tauri_plugin_mcp_client::{config::ServerConfig, registry::ConnectionRegistry};
+
+    fn echo_server() -> ServerConfig {
+        let script = r#"while read line; do id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p'); if [ -n "$id" ]; then echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"tools\":[]}}"; fi; done"#;
+        ServerConfig {
+            traffic: TrafficConfig::enabled(),
+            ..ServerConfig::new("sh", vec!["-c".to_string(), script.to_string()])
+        }
+    }
+
+    /// Test traffic is recorded through the registry and kept across reconnects
+    #[tokio::test]
+    async fn test_registry_records_traffic() {
+        let registry: ConnectionRegistry<tauri::Wry> = ConnectionRegistry::new();
+        assert_eq!(registry.traffic("recorded").unwrap_err().code, "TRAFFIC_NOT_RECORDED");
+
+        registry.connect_server_with_config("recorded".to_string(), echo_server()).await.unwrap();
+        registry.list_tools("recorded").await.unwrap();
+
+        let entries = registry.traffic("recorded").unwrap();
+        let methods: Vec<_> = entries.iter().filter_map(|entry| entry.method.as_deref()).collect();
+        assert_eq!(methods, vec!["initialize", "notifications/initialized", "tools/list"]);
+        let response = entries.last().unwrap();
+        assert_eq!(response.direction, TrafficDirection::Received);
+        assert!(response.latency_ms.is_some());
+
+        let before = entries.len();
+        registry.connect_server_with_config("recorded".to_string(), echo_server()).await.unwrap();
+        assert!(registry.traffic("recorded").unwrap().len() > before);
+        assert_eq!(registry.export_traffic("recorded").unwrap().lines().count(), registry.traffic("recorded").unwrap().len());
+
+        registry.clear_traffic("recorded").unwrap();
+        assert!(registry.traffic("recorded").unwrap().is_empty());
+        registry.disconnect_server("recorded").await.unwrap();
+    }
+}
@@ -1,5 +1,6 @@
 use tauri_plugin_mcp_client::{
     registry::ConnectionInfo,
+    state::ConnectionState,
     error::{MCPClientError, ErrorCategory},
 };
 use serde_json::json;
@@ -110,14 +111,19 @@ fn test_connection_info_structure() {
         server_id: "test-server".to_string(),
         command: "node".to_string(),
         args: vec!["server.js".to_string(), "--port".to_string(), "3000".to_string()],
-        status: "connected".to_string(),
+        status: ConnectionState::Ready,
         connected_at: Some(1234567890),
+        last_error: None,
+        last_exit: None,
+        latency: None,
+        usage: None,
+        resources: None,
     };
     
     assert_eq!(connection_info.server_id, "test-server");
     assert_eq!(connection_info.command, "node");
     assert_eq!(connection_info.args.len(), 3);
-    assert_eq!(connection_info.status, "connected");
+    assert_eq!(connection_info.status, ConnectionState::Ready);
     assert!(connection_info.connected_at.is_some());
 }
 